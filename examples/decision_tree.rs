@@ -101,7 +101,7 @@ fn gini_index(groups: &[&LocalDataFrame], classes: &[bool]) -> f64 {
         }
         let mut score = 0.0;
         for class in classes {
-            let p = match group.data.get(group.n_cols() - 1).unwrap() {
+            let p = match &*group.data[group.n_cols() - 1] {
                 Column::Bool(c) => c.iter().fold(0.0, |acc, v| {
                     if v.unwrap() == *class {
                         acc + 1.0
@@ -336,7 +336,7 @@ fn evaluation(
                     training.combine(folds.get(j).unwrap().clone()).unwrap();
             }
         }
-        let actual = match &testing.data.get(testing.n_cols() - 1).unwrap() {
+        let actual = match &*testing.data[testing.n_cols() - 1] {
             Column::Bool(b) => b.clone(),
             _ => panic!("nope"),
         };
@@ -40,16 +40,11 @@ async fn main() -> Result<(), LiquidError> {
     } else if app.node_id == 3 {
         let df2 = app.kv.wait_and_get(&ck).await?;
         let df1 = app.kv.wait_and_get(&verif).await?;
-        match (df1.get(0, 0)?, df2.get(0, 0)?) {
-            (Data::Int(x), Data::Int(y)) => {
-                if x == y {
-                    println!("SUCCESS")
-                } else {
-                    println!("FAILURE")
-                };
-            }
-            _ => unreachable!(),
-        }
+        if df1 == df2 {
+            println!("SUCCESS")
+        } else {
+            println!("FAILURE")
+        };
     }
     Ok(())
 }
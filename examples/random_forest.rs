@@ -127,7 +127,7 @@ fn gini_index(groups: &[&LocalDataFrame], classes: &[bool]) -> f64 {
         for class in classes {
             // calulcate the proportion of labels in this `group` that belongs
             // to the label `class`
-            let p = match group.data.get(group.n_cols() - 1).unwrap() {
+            let p = match &*group.data[group.n_cols() - 1] {
                 Column::Bool(c) => c.iter().fold(0.0, |acc, v| {
                     if v.unwrap() == *class {
                         acc + 1.0
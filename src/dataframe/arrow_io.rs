@@ -0,0 +1,82 @@
+//! Conversions between the `Column`s of a `LocalDataFrame` and Arrow arrays,
+//! used to exchange data with other Arrow based tools through
+//! `RecordBatch`es and Arrow IPC files.
+use crate::error::LiquidError;
+use arrow::array::{
+    Array, ArrayRef, AsArray, BooleanArray, Float64Array, Int64Array,
+    StringArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType as ArrowType, Float64Type, Int64Type};
+use sorer::dataframe::Column;
+use std::sync::Arc;
+
+/// Converts `col` to an Arrow array of the matching type.
+pub(crate) fn to_array(col: &Column) -> ArrayRef {
+    match col {
+        Column::Bool(c) => Arc::new(BooleanArray::from(c.clone())),
+        Column::Int(c) => Arc::new(Int64Array::from(c.clone())),
+        Column::Float(c) => Arc::new(Float64Array::from(c.clone())),
+        Column::String(c) => {
+            Arc::new(c.iter().map(|s| s.as_deref()).collect::<StringArray>())
+        }
+    }
+}
+
+/// Returns the Arrow type of the array `to_array` creates for `col`.
+pub(crate) fn to_arrow_type(col: &Column) -> ArrowType {
+    match col {
+        Column::Bool(_) => ArrowType::Boolean,
+        Column::Int(_) => ArrowType::Int64,
+        Column::Float(_) => ArrowType::Float64,
+        Column::String(_) => ArrowType::Utf8,
+    }
+}
+
+/// Converts the given Arrow `array` to a `Column`. Signed integers and
+/// unsigned integers of up to 32 bits are widened to an `Int` column, floats
+/// of any width to a `Float` column, and both string types to a `String`
+/// column.
+///
+/// # Errors
+/// If the array is of any other type, e.g. a `UInt64`, `Date32` or `List`
+/// array, a `LiquidError::InvalidArgument` is returned.
+pub(crate) fn from_array(array: &dyn Array) -> Result<Column, LiquidError> {
+    let target = match array.data_type() {
+        ArrowType::Boolean => ArrowType::Boolean,
+        ArrowType::Int8
+        | ArrowType::Int16
+        | ArrowType::Int32
+        | ArrowType::Int64
+        | ArrowType::UInt8
+        | ArrowType::UInt16
+        | ArrowType::UInt32 => ArrowType::Int64,
+        ArrowType::Float16 | ArrowType::Float32 | ArrowType::Float64 => {
+            ArrowType::Float64
+        }
+        ArrowType::Utf8 | ArrowType::LargeUtf8 => ArrowType::Utf8,
+        other => {
+            return Err(LiquidError::InvalidArgument(format!(
+                "Arrow arrays of type {} are not supported",
+                other
+            )))
+        }
+    };
+    let array = cast(array, &target)?;
+    Ok(match target {
+        ArrowType::Boolean => Column::Bool(array.as_boolean().iter().collect()),
+        ArrowType::Int64 => {
+            Column::Int(array.as_primitive::<Int64Type>().iter().collect())
+        }
+        ArrowType::Float64 => {
+            Column::Float(array.as_primitive::<Float64Type>().iter().collect())
+        }
+        _ => Column::String(
+            array
+                .as_string::<i32>()
+                .iter()
+                .map(|s| s.map(String::from))
+                .collect(),
+        ),
+    })
+}
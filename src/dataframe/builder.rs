@@ -0,0 +1,104 @@
+//! Defines a `DataFrameBuilder`, a fluent way to construct a
+//! `LocalDataFrame` one named column at a time.
+use crate::dataframe::{LocalDataFrame, Schema};
+use crate::error::LiquidError;
+use sorer::dataframe::Column;
+
+/// Generates a `with_<type>_col` method that adds a column of the given type
+macro_rules! with_col {
+    ($func_name:ident, $type:ty, $sorer_type:ident) => {
+        /// Adds a column with the given `name` and `values` after the columns
+        /// that were already added. Use `None` for null values.
+        pub fn $func_name(
+            self,
+            name: &str,
+            values: Vec<Option<$type>>,
+        ) -> Self {
+            self.with_col(name, Column::$sorer_type(values))
+        }
+    };
+}
+
+/// A builder for a [`LocalDataFrame`] that adds one named column at a time
+/// with e.g. [`with_int_col`] and checks that the columns fit together when
+/// [`build`] is called, so columns can be added in any order.
+///
+/// [`LocalDataFrame`]: struct.LocalDataFrame.html
+/// [`with_int_col`]: struct.DataFrameBuilder.html#method.with_int_col
+/// [`build`]: struct.DataFrameBuilder.html#method.build
+#[derive(Clone, Debug, Default)]
+pub struct DataFrameBuilder {
+    /// The names and values of the columns added so far, in order
+    columns: Vec<(String, Column)>,
+}
+
+impl DataFrameBuilder {
+    /// Creates a new `DataFrameBuilder` with no columns.
+    pub fn new() -> Self {
+        DataFrameBuilder::default()
+    }
+
+    /// Adds the given `col` with the given `name` after the columns that were
+    /// already added.
+    pub fn with_col(mut self, name: &str, col: Column) -> Self {
+        self.columns.push((name.to_string(), col));
+        self
+    }
+
+    with_col!(with_bool_col, bool, Bool);
+    with_col!(with_int_col, i64, Int);
+    with_col!(with_float_col, f64, Float);
+    with_col!(with_string_col, String, String);
+
+    /// Creates a `LocalDataFrame` with the columns that were added, in the
+    /// order they were added.
+    ///
+    /// # Errors
+    /// If the columns don't all have the same length, a
+    /// `LiquidError::RowIndexOutOfBounds` is returned, and if two columns
+    /// have the same name, a `LiquidError::NameAlreadyExists` is returned.
+    pub fn build(self) -> Result<LocalDataFrame, LiquidError> {
+        let mut df = LocalDataFrame::new(&Schema::new());
+        for (name, col) in self.columns {
+            df.append_column_checked(col, Some(name))?;
+        }
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sorer::dataframe::Data;
+    use sorer::schema::DataType;
+
+    #[test]
+    fn test_builder() {
+        let df = DataFrameBuilder::new()
+            .with_int_col("id", vec![Some(1), Some(2), None])
+            .with_float_col("score", vec![Some(0.5), None, Some(1.5)])
+            .with_bool_col("ok", vec![Some(true), Some(false), Some(true)])
+            .with_string_col("tag", vec![None, Some("b".to_string()), None])
+            .build()
+            .unwrap();
+        assert_eq!(df.n_rows(), 3);
+        assert_eq!(df.get_col_idx("tag"), Some(3));
+        assert_eq!(df.get_schema().col_type(1).unwrap(), &DataType::Float);
+        assert_eq!(df.get(0, 1).unwrap(), Data::Int(2));
+        assert_eq!(df.get(3, 1).unwrap(), Data::String("b".to_string()));
+        assert_eq!(df.get(1, 1).unwrap(), Data::Null);
+
+        let uneven = DataFrameBuilder::new()
+            .with_int_col("a", vec![Some(1)])
+            .with_int_col("b", vec![Some(1), Some(2)])
+            .build();
+        assert!(matches!(uneven, Err(LiquidError::RowIndexOutOfBounds)));
+        let duplicate = DataFrameBuilder::new()
+            .with_int_col("a", vec![Some(1)])
+            .with_col("a", Column::Bool(vec![None]))
+            .build();
+        assert!(matches!(duplicate, Err(LiquidError::NameAlreadyExists)));
+        assert_eq!(DataFrameBuilder::new().build().unwrap().n_cols(), 0);
+    }
+}
@@ -0,0 +1,377 @@
+//! Defines the `CsvOptions` used to read CSV files into a `LocalDataFrame`
+//! and the helpers that infer the `Schema` of a CSV file and parse its
+//! records into `Column`s.
+use crate::dataframe::{schema::no_column_named, Schema};
+use crate::error::LiquidError;
+use crate::SOR_SCHEMA_INFERENCE_ROWS;
+use csv::{ByteRecordsIter, Reader, ReaderBuilder, StringRecord};
+use log::warn;
+use sorer::dataframe::{Column, Data};
+use sorer::schema::DataType;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io;
+
+/// Options for reading a CSV file with [`LocalDataFrame::from_csv`], created
+/// with `CsvOptions::default()` and changed with the `with_*` methods. By
+/// default fields are separated by `,`, quoted with `"`, the first record is
+/// a header with the column names, and the type of each column is inferred
+/// from the first 500 records.
+///
+/// [`LocalDataFrame::from_csv`]: struct.LocalDataFrame.html#method.from_csv
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    /// The byte that separates fields
+    delimiter: u8,
+    /// Whether the first record is a header with the names of the columns
+    has_header: bool,
+    /// The byte fields are quoted with, or `None` if quotes have no special
+    /// meaning
+    quote: Option<u8>,
+    /// The number of records after the header the types of the columns are
+    /// inferred from
+    inference_rows: usize,
+    /// The types of columns that are given instead of inferred, by column
+    /// name, or column index if the file has no header
+    col_types: HashMap<String, DataType>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            has_header: true,
+            quote: Some(b'"'),
+            inference_rows: SOR_SCHEMA_INFERENCE_ROWS,
+            col_types: HashMap::new(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Separates fields with the given `delimiter` instead of `,`, e.g.
+    /// `b'\t'` for tab separated files.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets whether the first record is a header with the names of the
+    /// columns. Without a header the columns have no names.
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Quotes fields with the given `quote` byte instead of `"`, or, if it is
+    /// `None`, gives quotes no special meaning.
+    pub fn with_quote(mut self, quote: Option<u8>) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Infers the types of the columns from the first `inference_rows`
+    /// records after the header instead of the first 500.
+    pub fn with_inference_rows(mut self, inference_rows: usize) -> Self {
+        self.inference_rows = inference_rows;
+        self
+    }
+
+    /// Gives the column named `col` the type `data_type` instead of
+    /// inferring it. If the file has no header, `col` is the index of the
+    /// column, e.g. `"0"` for the first column.
+    pub fn with_col_type(mut self, col: &str, data_type: DataType) -> Self {
+        self.col_types.insert(col.to_string(), data_type);
+        self
+    }
+
+    /// Opens the file at `file_name` for reading with these options. The
+    /// header, if there is one, is read as a normal record, and records may
+    /// have any number of fields.
+    pub(crate) fn reader(
+        &self,
+        file_name: &str,
+    ) -> Result<Reader<File>, LiquidError> {
+        Ok(ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(false)
+            .quoting(self.quote.is_some())
+            .quote(self.quote.unwrap_or(b'"'))
+            .flexible(true)
+            .from_reader(File::open(file_name)?))
+    }
+}
+
+/// An iterator over the records of a CSV file that are valid UTF-8, created
+/// with `records` or `data_records`. Records that are not are malformed, so
+/// they are skipped and counted. An error reading the file is returned as an
+/// `Err` item, after which the records should not be used.
+pub(crate) struct Records<'a> {
+    /// The records of the file, which may not be valid UTF-8
+    byte_records: ByteRecordsIter<'a, File>,
+    /// The number of records that were skipped so far because they are not
+    /// valid UTF-8
+    pub(crate) n_malformed: usize,
+}
+
+impl Iterator for Records<'_> {
+    type Item = Result<StringRecord, LiquidError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.byte_records.next()? {
+                Ok(record) => match StringRecord::from_byte_record(record) {
+                    Ok(record) => return Some(Ok(record)),
+                    Err(_) => self.n_malformed += 1,
+                },
+                Err(e) => return Some(Err(io::Error::from(e).into())),
+            }
+        }
+    }
+}
+
+/// Returns the `Records` of `reader`, including the header if there is one.
+pub(crate) fn records(reader: &mut Reader<File>) -> Records {
+    Records {
+        byte_records: reader.byte_records(),
+        n_malformed: 0,
+    }
+}
+
+/// Returns the `Records` of `reader` after the header, if `options` says
+/// there is one.
+///
+/// # Errors
+/// If the header can't be read, a `LiquidError::NetworkError` is returned.
+pub(crate) fn data_records<'a>(
+    reader: &'a mut Reader<File>,
+    options: &CsvOptions,
+) -> Result<Records<'a>, LiquidError> {
+    let mut records = records(reader);
+    if options.has_header {
+        records.next().transpose()?;
+    }
+    Ok(records)
+}
+
+/// Logs a warning with the number of records that were discarded because
+/// they are malformed, if there were any.
+pub(crate) fn warn_discarded(discarded: usize) {
+    if discarded > 0 {
+        warn!("Discarded {} malformed CSV records", discarded);
+    }
+}
+
+/// Infers the `Schema` of the CSV file at `file_name` read with `options`.
+/// The columns are named after the header, if there is one, and there are as
+/// many columns as fields in the header, or as in the longest record used
+/// for inference if there is no header. Empty fields are null, and every
+/// other column gets the most general type of its values in the first
+/// records, where `true` and `false` are `Bool`s and `Bool`s mixed with
+/// numbers are `String`s. Columns with no values are `Bool`s.
+///
+/// # Errors
+/// If the file can't be read, a `LiquidError::NetworkError` is returned,
+/// if the header has a name twice, a `LiquidError::NameAlreadyExists` is
+/// returned, and if a type is given for a column that doesn't exist, a
+/// `LiquidError::InvalidArgument` is returned.
+pub(crate) fn infer_schema(
+    file_name: &str,
+    options: &CsvOptions,
+) -> Result<Schema, LiquidError> {
+    let mut reader = options.reader(file_name)?;
+    let mut records = records(&mut reader);
+    let header = if options.has_header {
+        records.next().transpose()?
+    } else {
+        None
+    };
+    let sample = records
+        .take(options.inference_rows)
+        .collect::<Result<Vec<StringRecord>, LiquidError>>()?;
+    let width = match &header {
+        Some(header) => header.len(),
+        None => sample.iter().map(|record| record.len()).max().unwrap_or(0),
+    };
+
+    let mut schema = Schema::new();
+    let mut labels = HashSet::new();
+    for col_idx in 0..width {
+        let name = header
+            .as_ref()
+            .map(|header| header[col_idx].to_string())
+            .filter(|name| !name.is_empty());
+        let label = name.clone().unwrap_or_else(|| col_idx.to_string());
+        let data_type = match options.col_types.get(&label) {
+            Some(data_type) => data_type.clone(),
+            None => infer_type(
+                sample.iter().filter_map(|record| record.get(col_idx)),
+            ),
+        };
+        schema.add_column(data_type, name)?;
+        labels.insert(label);
+    }
+    if let Some(missing) = options
+        .col_types
+        .keys()
+        .find(|label| !labels.contains(*label))
+    {
+        return Err(no_column_named(missing));
+    }
+
+    Ok(schema)
+}
+
+/// Returns the most general type of the given `fields` of a column, see
+/// `infer_schema`.
+fn infer_type<'a>(fields: impl Iterator<Item = &'a str>) -> DataType {
+    let (mut bools, mut ints, mut floats, mut strings) =
+        (false, false, false, false);
+    for field in fields.filter(|field| !field.is_empty()) {
+        if parse_bool(field).is_some() {
+            bools = true;
+        } else if field.parse::<i64>().is_ok() {
+            ints = true;
+        } else if field.parse::<f64>().is_ok() {
+            floats = true;
+        } else {
+            strings = true;
+        }
+    }
+    if strings || (bools && (ints || floats)) {
+        DataType::String
+    } else if floats {
+        DataType::Float
+    } else if ints {
+        DataType::Int
+    } else {
+        DataType::Bool
+    }
+}
+
+/// Parses `true` or `false`, ignoring case.
+fn parse_bool(field: &str) -> Option<bool> {
+    if field.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if field.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Parses the given `field` as a value of `data_type`, where an empty field
+/// is `Data::Null`. `Bool`s may also be `0` or `1`. Returns `None` if the
+/// field is malformed.
+fn parse_field(data_type: &DataType, field: &str) -> Option<Data> {
+    if field.is_empty() {
+        return Some(Data::Null);
+    }
+    match data_type {
+        DataType::Bool => match field {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => parse_bool(field),
+        }
+        .map(Data::Bool),
+        DataType::Int => field.parse().ok().map(Data::Int),
+        DataType::Float => field.parse().ok().map(Data::Float),
+        DataType::String => Some(Data::String(field.to_string())),
+    }
+}
+
+/// Parses the given `records` into `Column`s of the types in `schema`.
+/// Records with fewer fields than the `schema` are null in the missing
+/// columns, and records with more fields or a field that is malformed for
+/// its column are discarded. Returns the `Column`s and the number of
+/// discarded records.
+pub(crate) fn parse_records(
+    schema: &Schema,
+    records: &[StringRecord],
+) -> (Vec<Column>, usize) {
+    let mut columns: Vec<Column> = schema
+        .schema
+        .iter()
+        .map(|data_type| match data_type {
+            DataType::Bool => Column::Bool(Vec::new()),
+            DataType::Int => Column::Int(Vec::new()),
+            DataType::Float => Column::Float(Vec::new()),
+            DataType::String => Column::String(Vec::new()),
+        })
+        .collect();
+    let mut discarded = 0;
+    for record in records {
+        if record.len() > schema.width() {
+            discarded += 1;
+            continue;
+        }
+        let row: Option<Vec<Data>> = schema
+            .schema
+            .iter()
+            .enumerate()
+            .map(|(col_idx, data_type)| {
+                parse_field(data_type, record.get(col_idx).unwrap_or(""))
+            })
+            .collect();
+        let row = match row {
+            Some(row) => row,
+            None => {
+                discarded += 1;
+                continue;
+            }
+        };
+        for (col, value) in columns.iter_mut().zip(row) {
+            match (col, value) {
+                (Column::Bool(c), Data::Bool(x)) => c.push(Some(x)),
+                (Column::Int(c), Data::Int(x)) => c.push(Some(x)),
+                (Column::Float(c), Data::Float(x)) => c.push(Some(x)),
+                (Column::String(c), Data::String(x)) => c.push(Some(x)),
+                (Column::Bool(c), _) => c.push(None),
+                (Column::Int(c), _) => c.push(None),
+                (Column::Float(c), _) => c.push(None),
+                (Column::String(c), _) => c.push(None),
+            }
+        }
+    }
+
+    (columns, discarded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_type() {
+        assert_eq!(infer_type(vec!["1", "", "-2"].into_iter()), DataType::Int);
+        assert_eq!(infer_type(vec!["1", "2.5"].into_iter()), DataType::Float);
+        assert_eq!(
+            infer_type(vec!["TRUE", "false"].into_iter()),
+            DataType::Bool
+        );
+        assert_eq!(infer_type(vec!["true", "1"].into_iter()), DataType::String);
+        assert_eq!(infer_type(vec!["1", "a"].into_iter()), DataType::String);
+        assert_eq!(infer_type(vec![""].into_iter()), DataType::Bool);
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let schema = Schema::from("IS");
+        let records: Vec<StringRecord> = vec![
+            vec!["1", "a"],
+            vec!["x", "b"],
+            vec!["3"],
+            vec!["4", "d", "extra"],
+        ]
+        .into_iter()
+        .map(StringRecord::from)
+        .collect();
+        let (columns, discarded) = parse_records(&schema, &records);
+        assert_eq!(columns[0], Column::Int(vec![Some(1), Some(3)]));
+        assert_eq!(
+            columns[1],
+            Column::String(vec![Some("a".to_string()), None])
+        );
+        assert_eq!(discarded, 2);
+    }
+}
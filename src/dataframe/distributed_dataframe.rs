@@ -1,25 +1,33 @@
 //! Defines functionality for a data frame that is split across different
 //! physical machines.
-use crate::dataframe::{local_dataframe::LocalDataFrame, Row, Rower, Schema};
+use crate::dataframe::{
+    csv_io,
+    local_dataframe::{sample_size, LocalDataFrame},
+    CsvOptions, JoinKind, Row, Rower, Schema, Window,
+};
 use crate::error::LiquidError;
 use crate::kv::{KVStore, Key};
 use crate::network::{Client, FramedStream};
 use bincode::{deserialize, serialize};
+use futures::executor;
 use futures::stream::{SelectAll, StreamExt};
 use log::{debug, info};
-use rand::{self, Rng};
+use rand::{self, rngs::StdRng, Rng, SeedableRng};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sorer::dataframe::{Column, Data, SorTerator};
+use sorer::schema::DataType;
 use std::cmp;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::{
     mpsc::{self, Receiver, Sender},
     Mutex, Notify, RwLock,
 };
+use tokio::task;
 
 /// Represents a distributed, immutable data frame which contains data stored
 /// in a columnar format and a well defined [`Schema`]. Provides convenient
@@ -70,6 +78,10 @@ pub struct DistributedDataFrame {
     blob_receiver: Mutex<Receiver<Vec<u8>>>,
     /// Used for processing filter results TODO: maybe a better way to do this
     filter_results: Mutex<Receiver<DistributedDFMsg>>,
+    /// The number of times this `DistributedDataFrame` has been joined with
+    /// another one, used to give every joined `DistributedDataFrame` a unique
+    /// name. Since every node must call `join`, it is the same on every node
+    n_joins: AtomicUsize,
 }
 
 /// Represents the kinds of messages sent between `DistributedDataFrame`s
@@ -81,8 +93,9 @@ pub(crate) enum DistributedDFMsg {
     /// A message used to respond to `GetRow` messages with the requested row
     Row(Row),
     /// A message used to tell the 1st node the results from using the `filter`
-    /// method. If there were no rows after filtering, then `filtered_df_key`
-    /// is `None` and `num_rows` is `0`.
+    /// method, or every other node the results from using the `join` method.
+    /// If there were no rows in the result, then `filtered_df_key` is `None`
+    /// and `num_rows` is `0`.
     FilterResult {
         num_rows: usize,
         filtered_df_key: Option<Key>,
@@ -98,6 +111,11 @@ pub(crate) enum DistributedDFMsg {
         schema: Schema,
         df_chunk_map: HashMap<Range<usize>, Key>,
     },
+    /// Sent by node 1 instead of an `Initialization` message when it fails
+    /// to create the chunks of a new `DistributedDataFrame`, so that the
+    /// other nodes return an error instead of waiting. Contains the
+    /// description of the error
+    InitializationFailed(String),
 }
 
 impl DistributedDataFrame {
@@ -106,6 +124,11 @@ impl DistributedDataFrame {
     /// Node 1 will then parse that file and distribute chunks to other nodes
     /// over the network, so if network latency is a concern you should not
     /// use this method.
+    ///
+    /// Reading and parsing the file is blocking, so it is done on a thread
+    /// where blocking is acceptable with `tokio::task::spawn_blocking`. This
+    /// keeps the runtime of node 1 responsive, e.g. to messages from other
+    /// nodes, while a large file is parsed.
     pub(crate) async fn from_sor(
         server_addr: &str,
         my_ip: &str,
@@ -114,24 +137,162 @@ impl DistributedDataFrame {
         df_name: &str,
         num_nodes: usize,
     ) -> Result<Arc<Self>, LiquidError> {
-        // make a chunking iterator for the sor file
-        let sor_terator = if kv.id == 1 {
-            let total_newlines = count_new_lines(file_name);
-            let max_rows_per_node = total_newlines / num_nodes;
-            let schema = sorer::schema::infer_schema(file_name);
-            info!(
-                "Total newlines: {} max rows per node: {}",
-                total_newlines, max_rows_per_node
-            );
-            info!("Inferred schema: {:?}", &schema);
-            Some(SorTerator::new(file_name, schema, max_rows_per_node))
+        // parse the sor file in chunks on a blocking thread, at most
+        // `num_nodes` parsed chunks wait to be distributed at a time
+        let chunks = if kv.id == 1 {
+            let (mut sender, receiver) = mpsc::channel(num_nodes);
+            let file_name = file_name.to_string();
+            task::spawn_blocking(move || {
+                let total_newlines = count_new_lines(&file_name);
+                let max_rows_per_node = total_newlines / num_nodes;
+                let schema = sorer::schema::infer_schema(&file_name);
+                info!(
+                    "Total newlines: {} max rows per node: {}",
+                    total_newlines, max_rows_per_node
+                );
+                info!("Inferred schema: {:?}", &schema);
+                let sor_terator =
+                    SorTerator::new(&file_name, schema, max_rows_per_node);
+                for chunk in sor_terator {
+                    let chunk = Ok(LocalDataFrame::from(chunk));
+                    if executor::block_on(sender.send(chunk)).is_err() {
+                        // the data frame could not be created
+                        break;
+                    }
+                }
+            });
+            Some(receiver)
         } else {
             None
         };
-        DistributedDataFrame::from_iter(
+        DistributedDataFrame::from_chunks(
             server_addr,
             my_ip,
-            sor_terator,
+            chunks,
+            kv,
+            df_name,
+            num_nodes,
+        )
+        .await
+    }
+
+    /// Creates a new `DistributedDataFrame` from the CSV file with the given
+    /// `file_name`, read with the given `options`. As with [`from_sor`], it
+    /// is assumed that node 1 contains the file. Node 1 infers the `Schema`
+    /// from the first records, counts the records, then parses the file in
+    /// chunks of at most the number of records divided by the number of
+    /// nodes and distributes them, see [`LocalDataFrame::from_csv`] for how
+    /// records are parsed. The columns are named after the header, if there
+    /// is one.
+    ///
+    /// Reading and parsing the file is blocking, so it is done on a thread
+    /// where blocking is acceptable with `tokio::task::spawn_blocking`. If
+    /// node 1 can't read the file, it returns the error and every other node
+    /// returns a `LiquidError::RemoteError`. The number of malformed records
+    /// that were discarded is logged as a warning on node 1.
+    ///
+    /// [`from_sor`]: struct.DistributedDataFrame.html#method.from_sor
+    /// [`LocalDataFrame::from_csv`]: struct.LocalDataFrame.html#method.from_csv
+    pub(crate) async fn from_csv(
+        server_addr: &str,
+        my_ip: &str,
+        file_name: &str,
+        options: &CsvOptions,
+        kv: Arc<KVStore<LocalDataFrame>>,
+        df_name: &str,
+        num_nodes: usize,
+    ) -> Result<Arc<Self>, LiquidError> {
+        let chunks = if kv.id == 1 {
+            let (mut sender, receiver) = mpsc::channel(num_nodes);
+            let file_name = file_name.to_string();
+            let options = options.clone();
+            task::spawn_blocking(move || {
+                let sent = send_csv_chunks(
+                    &file_name,
+                    &options,
+                    num_nodes,
+                    &mut sender,
+                );
+                if let Err(e) = sent {
+                    // the data frame fails whether this is sent or not
+                    let _ = executor::block_on(sender.send(Err(e)));
+                }
+            });
+            Some(receiver)
+        } else {
+            None
+        };
+        DistributedDataFrame::from_chunks(
+            server_addr,
+            my_ip,
+            chunks,
+            kv,
+            df_name,
+            num_nodes,
+        )
+        .await
+    }
+
+    /// Creates a new `DistributedDataFrame` from the Parquet file with the
+    /// given `file_name`, which is assumed to be on node 1. Instead of
+    /// splitting the file by bytes like [`from_sor`], node 1 assigns
+    /// consecutive row groups to every node so that each node gets about the
+    /// same number of rows, reads them with [`LocalDataFrame::from_parquet`]
+    /// and distributes them. Row groups are never split, so a file with fewer
+    /// row groups than nodes leaves some nodes without a chunk. Unlike a
+    /// `SoR` file, the columns keep the names they have in the file.
+    ///
+    /// Node 1 reads the row groups of one node at a time on a blocking
+    /// thread, and at most `num_nodes` chunks wait to be distributed at a
+    /// time. If node 1 can't read the file, it returns the error and every
+    /// other node returns a `LiquidError::RemoteError`. The metadata of the
+    /// file is read before any chunk, so a missing or invalid file fails
+    /// before anything is distributed.
+    ///
+    /// [`from_sor`]: struct.DistributedDataFrame.html#method.from_sor
+    /// [`LocalDataFrame::from_parquet`]: struct.LocalDataFrame.html#method.from_parquet
+    #[cfg(feature = "parquet")]
+    pub(crate) async fn from_parquet(
+        server_addr: &str,
+        my_ip: &str,
+        file_name: &str,
+        kv: Arc<KVStore<LocalDataFrame>>,
+        df_name: &str,
+        num_nodes: usize,
+    ) -> Result<Arc<Self>, LiquidError> {
+        let chunks = if kv.id == 1 {
+            let (mut sender, receiver) = mpsc::channel(num_nodes);
+            let file_name = file_name.to_string();
+            task::spawn_blocking(move || {
+                let sizes =
+                    match LocalDataFrame::parquet_row_group_sizes(&file_name) {
+                        Ok(sizes) => sizes,
+                        Err(e) => {
+                            // the data frame fails whether this is sent or not
+                            let _ = executor::block_on(sender.send(Err(e)));
+                            return;
+                        }
+                    };
+                info!("Parquet file with row groups of sizes {:?}", &sizes);
+                for row_groups in row_group_ranges(&sizes, num_nodes) {
+                    let chunk =
+                        LocalDataFrame::from_parquet(&file_name, row_groups);
+                    let is_err = chunk.is_err();
+                    if executor::block_on(sender.send(chunk)).is_err() || is_err
+                    {
+                        // the data frame could not be created
+                        break;
+                    }
+                }
+            });
+            Some(receiver)
+        } else {
+            None
+        };
+        DistributedDataFrame::from_chunks(
+            server_addr,
+            my_ip,
+            chunks,
             kv,
             df_name,
             num_nodes,
@@ -149,20 +310,66 @@ impl DistributedDataFrame {
         kv: Arc<KVStore<LocalDataFrame>>,
         df_name: &str,
         num_nodes: usize,
+    ) -> Result<Arc<Self>, LiquidError> {
+        let (mut sender, receiver) = mpsc::channel(num_nodes);
+        let chunks = if iter.is_some() { Some(receiver) } else { None };
+        // feed the chunks of `iter` to the data frame from this task, since
+        // `iter` may not be `Send`
+        let feed_chunks = async move {
+            for chunk in iter.into_iter().flatten() {
+                if sender.send(Ok(LocalDataFrame::from(chunk))).await.is_err() {
+                    // the data frame could not be created
+                    break;
+                }
+            }
+        };
+        let (_, ddf) = tokio::join!(
+            feed_chunks,
+            DistributedDataFrame::from_chunks(
+                server_addr,
+                my_ip,
+                chunks,
+                kv,
+                df_name,
+                num_nodes,
+            )
+        );
+        ddf
+    }
+
+    /// Creates a new `DataFrame` from the chunks received from `chunks`,
+    /// which is only `Some` on node 1. Node 1 receives chunks until the
+    /// sending half is dropped and distributes them concurrently.
+    ///
+    /// If node 1 receives an error instead of a chunk, it stops, tells every
+    /// other node that the `DistributedDataFrame` could not be created and
+    /// returns the error, while the other nodes return a
+    /// `LiquidError::RemoteError`. Chunks that were already distributed are
+    /// not removed.
+    async fn from_chunks(
+        server_addr: &str,
+        my_ip: &str,
+        chunks: Option<Receiver<Result<LocalDataFrame, LiquidError>>>,
+        kv: Arc<KVStore<LocalDataFrame>>,
+        df_name: &str,
+        num_nodes: usize,
     ) -> Result<Arc<Self>, LiquidError> {
         // Figure out what node we are supposed to be
         let node_id = kv.id;
         // initialize some other required fields of self so as not to duplicate
         // code in if branches
-        let (blob_sender, blob_receiver) = mpsc::channel(2);
+        // every other node may send us a blob at the same time, so make sure
+        // there is room for all of them
+        let (blob_sender, blob_receiver) = mpsc::channel(num_nodes);
         // used for internal messaging processing so that the asynchronous
         // messaging task can notify other tasks when `self.row` is ready
         let internal_notifier = Arc::new(Notify::new());
         // so that our network client can notify us when they get a Kill
         // signal
         let kill_notifier = Arc::new(Notify::new());
-        // so that our client only connects to clients for this dataframe
-        let df_network_name = format!("ddf-{}", df_name);
+        // so that our client only connects to clients for this dataframe,
+        // and not to a dataframe with the same name in another network
+        let df_network_name = format!("{}-ddf-{}", kv.network_name(), df_name);
         // for processing results when distributed filtering is performed
         // on this `DistributedDataFrame`
         let (filter_results_sender, filter_results) = mpsc::channel(num_nodes);
@@ -185,12 +392,26 @@ impl DistributedDataFrame {
             {
                 // in each iteration, create a future sends a chunk to a node
                 let mut chunk_idx = 0;
-                for chunk in iter.unwrap().into_iter() {
+                let mut chunks = chunks.unwrap();
+                while let Some(ldf) = chunks.recv().await {
+                    let ldf = match ldf {
+                        Ok(ldf) => ldf,
+                        Err(e) => {
+                            // the other nodes are waiting for the
+                            // `Initialization` message
+                            let msg = DistributedDFMsg::InitializationFailed(
+                                e.to_string(),
+                            );
+                            let mut network = network.lock().await;
+                            network.broadcast(msg).await?;
+                            network.close().await?;
+                            return Err(e);
+                        }
+                    };
                     if chunk_idx == 0 {
-                        schema = Some(Schema::from(&chunk));
+                        schema = Some(ldf.get_schema().clone());
                     }
 
-                    let ldf = LocalDataFrame::from(chunk);
                     if chunk_idx > 0 {
                         // assert all chunks have the same schema
                         assert_eq!(schema.as_ref(), Some(ldf.get_schema()));
@@ -257,6 +478,7 @@ impl DistributedDataFrame {
                 kill_notifier,
                 blob_receiver: Mutex::new(blob_receiver),
                 filter_results,
+                n_joins: AtomicUsize::new(0),
             });
 
             // spawn a tokio task to process messages
@@ -282,6 +504,10 @@ impl DistributedDataFrame {
                     schema,
                     df_chunk_map,
                 } => (schema, df_chunk_map),
+                DistributedDFMsg::InitializationFailed(e) => {
+                    network.lock().await.close().await?;
+                    return Err(LiquidError::RemoteError(e));
+                }
                 _ => return Err(LiquidError::UnexpectedMessage),
             };
             debug!("Got the Initialization message from Node 1");
@@ -312,6 +538,7 @@ impl DistributedDataFrame {
                 kill_notifier,
                 blob_receiver: Mutex::new(blob_receiver),
                 filter_results,
+                n_joins: AtomicUsize::new(0),
             });
 
             // spawn a tokio task to process messages
@@ -431,7 +658,12 @@ impl DistributedDataFrame {
     ///
     /// A local `pmap` is used on each node to map over that nodes' chunk.
     /// By default, each node will use the number of threads available on that
-    /// machine.
+    /// machine. `finalize` is only called on node 1, once the results of all
+    /// nodes have been joined.
+    ///
+    /// If the connection to the node that sends us its results fails, so
+    /// that its results can never arrive, a `LiquidError::BlobChannelClosed`
+    /// is returned.
     ///
     ///
     /// NOTE:
@@ -448,7 +680,7 @@ impl DistributedDataFrame {
     /// This implementation went with option 1 for simplicity reasons
     pub async fn map<T: Rower + Clone + Send + Serialize + DeserializeOwned>(
         &self,
-        mut rower: T,
+        rower: T,
     ) -> Result<Option<T>, LiquidError> {
         // get the keys for our locally owned chunks
         let my_keys: Vec<&Key> = self
@@ -457,20 +689,27 @@ impl DistributedDataFrame {
             .filter(|(_, key)| key.home == self.node_id)
             .map(|(_, v)| v)
             .collect();
-        // map over our chunks
+        // map over our chunks, each one starts from a copy of the given
+        // `rower` so that the results of earlier chunks aren't copied into
+        // every thread of the `pmap`
+        let mut joined: Option<T> = None;
         for key in my_keys {
             // TODO: shouldn't need wait_and_get here since we own that chunk..
             let ldf = self.kv.wait_and_get(key).await?;
-            rower = ldf.pmap(rower);
+            let result = ldf.pmap_without_finalize(rower.clone());
+            joined = Some(match joined {
+                Some(joined) => joined.join(result),
+                None => result,
+            });
         }
+        let mut rower = joined.unwrap_or(rower);
         if self.node_id == self.num_nodes {
             // we are the last node
             self.send_blob(self.node_id - 1, &rower).await?;
             debug!("Last node sent its results");
             Ok(None)
         } else {
-            let blob =
-                { self.blob_receiver.lock().await.recv().await.unwrap() };
+            let blob = recv_blob(&self.blob_receiver).await?;
             let external_rower: T = deserialize(&blob[..])?;
             rower = rower.join(external_rower);
             debug!("Received a resulting rower and joined it with local rower");
@@ -480,7 +719,7 @@ impl DistributedDataFrame {
                 Ok(None)
             } else {
                 debug!("Final node completed map");
-                Ok(Some(rower))
+                Ok(Some(rower.finalize()))
             }
         }
     }
@@ -518,7 +757,8 @@ impl DistributedDataFrame {
         let mut rng = rand::thread_rng();
         let r = rng.gen::<i16>();
         let new_name = format!("{}-filtered-{}", &self.df_name, r);
-        let df_network_name = format!("ddf-{}", new_name);
+        let df_network_name =
+            format!("{}-ddf-{}", self.kv.network_name(), new_name);
         let (network, mut read_streams, _kill_notifier) =
             Client::register_network(
                 self.kv.network.clone(),
@@ -546,7 +786,7 @@ impl DistributedDataFrame {
 
         // initialize some other required fields of self so as not to duplicate
         // code in if branches
-        let (blob_sender, blob_receiver) = mpsc::channel(2);
+        let (blob_sender, blob_receiver) = mpsc::channel(self.num_nodes);
         // used for internal messaging processing so that the asynnchronous
         // messaging task can notify other tasks when `self.row` is ready
         let internal_notifier = Arc::new(Notify::new());
@@ -662,6 +902,7 @@ impl DistributedDataFrame {
                 kill_notifier,
                 blob_receiver: Mutex::new(blob_receiver),
                 filter_results,
+                n_joins: AtomicUsize::new(0),
             });
 
             // spawn a tokio task to process messages
@@ -724,6 +965,7 @@ impl DistributedDataFrame {
                 kill_notifier,
                 blob_receiver: Mutex::new(blob_receiver),
                 filter_results,
+                n_joins: AtomicUsize::new(0),
             });
 
             // spawn a tokio task to process messages
@@ -743,6 +985,927 @@ impl DistributedDataFrame {
         }
     }
 
+    /// Creates a new `DistributedDataFrame` with the same rows, in the same
+    /// order, as this one, but with the rows split evenly between the nodes
+    /// so that the number of rows on any two nodes differs by at most one.
+    /// This improves the load balance of `map`s after e.g. a `filter` left
+    /// some nodes with many more rows than others.
+    ///
+    /// Every node knows the size of every chunk, so they all compute the same
+    /// balanced layout where each node gets one chunk of consecutive rows.
+    /// Each node then slices the chunks it owns into the parts that belong on
+    /// each node and sends every part straight to its node as a blob, so only
+    /// the rows that move are sent. Chunks are not owned in row order, e.g.
+    /// after `from_iter` they are spread round-robin, so a part may go to
+    /// any node, not just an adjacent one.
+    ///
+    /// Once the moves are done, every node removes the chunks of this
+    /// `DistributedDataFrame` from its `KVStore` and closes its network, so
+    /// this `DistributedDataFrame` can no longer be used. Every node must
+    /// call `rebalance` for it to complete.
+    pub async fn rebalance(&self) -> Result<Arc<Self>, LiquidError> {
+        // the name must be the same on every node
+        let new_name = format!("{}-rebalanced", &self.df_name);
+        let df_network_name =
+            format!("{}-ddf-{}", self.kv.network_name(), new_name);
+        let (network, read_streams, _kill_notifier) = Client::register_network(
+            self.kv.network.clone(),
+            df_network_name.to_string(),
+        )
+        .await?;
+        assert_eq!(self.node_id, { network.lock().await.id });
+
+        // every node knows the size of every chunk, so they all compute the
+        // same balanced layout without having to exchange it
+        let targets = balanced_ranges(self.num_rows, self.num_nodes);
+        let target = targets[self.node_id - 1].clone();
+        let mut parts = Vec::new();
+        let mut n_incoming = 0;
+        for (range, key) in &self.df_chunk_map {
+            let overlaps = targets.iter().enumerate().filter_map(|(idx, t)| {
+                let start = cmp::max(range.start, t.start);
+                let end = cmp::min(range.end, t.end);
+                if start < end {
+                    Some((idx + 1, start..end))
+                } else {
+                    None
+                }
+            });
+            if key.home != self.node_id {
+                n_incoming += overlaps
+                    .filter(|(node_id, _)| *node_id == self.node_id)
+                    .count();
+                continue;
+            }
+            let chunk = self.kv.get(key).await?;
+            for (node_id, overlap) in overlaps {
+                let mut part = LocalDataFrame::new(self.get_schema());
+                let rows =
+                    overlap.start - range.start..overlap.end - range.start;
+                part.append_rows_from(&chunk, rows)?;
+                if node_id == self.node_id {
+                    parts.push((overlap.start, part));
+                } else {
+                    self.send_blob(node_id, &(overlap.start, part)).await?;
+                }
+            }
+        }
+        for _ in 0..n_incoming {
+            let blob = recv_blob(&self.blob_receiver).await?;
+            parts.push(deserialize(&blob[..])?);
+        }
+        parts.sort_by_key(|(start, _)| *start);
+        let mut balanced = LocalDataFrame::new(self.get_schema());
+        for (_, part) in parts {
+            balanced.append_rows_from(&part, 0..part.n_rows())?;
+        }
+        info!(
+            "Rebalanced {} to have {} rows on this node",
+            &self.df_name,
+            balanced.n_rows()
+        );
+
+        // every node only reads the chunks it owns, and has sent all of
+        // their parts by now, so the old chunks and network are not needed
+        for key in self.df_chunk_map.values() {
+            self.kv.remove(key).await;
+        }
+        self.network.lock().await.close().await?;
+
+        let df_chunk_map: HashMap<Range<usize>, Key> = targets
+            .into_iter()
+            .enumerate()
+            .filter(|(_, range)| range.start < range.end)
+            .map(|(idx, range)| (range, Key::new(&new_name, idx + 1)))
+            .collect();
+        if target.start < target.end {
+            let key = Key::new(&new_name, self.node_id);
+            self.kv.put(key, balanced).await?;
+        }
+
+        let (blob_sender, blob_receiver) = mpsc::channel(self.num_nodes);
+        let (filter_results_sender, filter_results) =
+            mpsc::channel(self.num_nodes);
+        let ddf = Arc::new(DistributedDataFrame {
+            schema: self.get_schema().clone(),
+            df_name: new_name,
+            df_chunk_map,
+            num_rows: self.num_rows,
+            network,
+            node_id: self.node_id,
+            num_nodes: self.num_nodes,
+            server_addr: self.server_addr.clone(),
+            my_ip: self.my_ip.clone(),
+            kv: self.kv.clone(),
+            internal_notifier: Arc::new(Notify::new()),
+            row: Arc::new(RwLock::new(Row::new(self.get_schema()))),
+            kill_notifier: Arc::new(Notify::new()),
+            blob_receiver: Mutex::new(blob_receiver),
+            filter_results: Mutex::new(filter_results),
+            n_joins: AtomicUsize::new(0),
+        });
+
+        // spawn a tokio task to process messages
+        let ddf_clone = ddf.clone();
+        tokio::spawn(async move {
+            DistributedDataFrame::process_messages(
+                ddf_clone,
+                read_streams,
+                blob_sender,
+                filter_results_sender,
+            )
+            .await
+            .unwrap();
+        });
+
+        Ok(ddf)
+    }
+
+    /// Creates a new `DistributedDataFrame` where the column at `col_idx` is
+    /// replaced by the result of applying `f` to each of its values, as in
+    /// [`LocalDataFrame::map_column`]. The new `DistributedDataFrame` has the
+    /// name `"<df_name>-map-<col_idx>"`.
+    ///
+    /// Each node maps the chunks it owns, so no rows are sent between nodes.
+    /// Every node must call `map_column` with the same arguments for it to
+    /// complete.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned on every node. If `f` returns a value that is not of type
+    /// `data_type`, `LiquidError::TypeMismatch` is returned on the nodes
+    /// that own such a value.
+    ///
+    /// [`LocalDataFrame::map_column`]: struct.LocalDataFrame.html#method.map_column
+    pub async fn map_column<F>(
+        &self,
+        col_idx: usize,
+        data_type: &DataType,
+        f: F,
+    ) -> Result<Arc<Self>, LiquidError>
+    where
+        F: Fn(&Data) -> Data,
+    {
+        let new_name = format!("{}-map-{}", &self.df_name, col_idx);
+        self.map_chunks(new_name, |chunk| {
+            chunk.map_column(col_idx, data_type, &f)
+        })
+        .await
+    }
+
+    /// Creates a new `DistributedDataFrame` with a column named `name` of
+    /// type `data_type`, whose value in each row is the result of applying
+    /// `f` to that row, as in [`LocalDataFrame::with_column`]. The new
+    /// `DistributedDataFrame` has the name `"<df_name>-with-<name>"`.
+    ///
+    /// Each node computes the column for the chunks it owns, so no rows are
+    /// sent between nodes. Every node must call `with_column` with the same
+    /// arguments for it to complete.
+    ///
+    /// # Errors
+    /// If `f` returns a value that is not of type `data_type`,
+    /// `LiquidError::TypeMismatch` is returned on the nodes that own such a
+    /// row.
+    ///
+    /// [`LocalDataFrame::with_column`]: struct.LocalDataFrame.html#method.with_column
+    pub async fn with_column<F>(
+        &self,
+        name: &str,
+        data_type: &DataType,
+        f: F,
+    ) -> Result<Arc<Self>, LiquidError>
+    where
+        F: Fn(&Row) -> Data,
+    {
+        let new_name = format!("{}-with-{}", &self.df_name, name);
+        self.map_chunks(new_name, |chunk| {
+            chunk.with_column(name, data_type, &f)
+        })
+        .await
+    }
+
+    /// Creates a new `DistributedDataFrame` with the name `new_name` by
+    /// applying `f` to every chunk of this `DistributedDataFrame`, where `f`
+    /// must not change the number of rows of a chunk. Each node applies `f`
+    /// to the chunks it owns, and since the chunks keep their rows every node
+    /// builds the same chunk map without having to exchange it. `f` is first
+    /// applied to an empty chunk to find the new `Schema`, so that errors
+    /// caused by the `Schema` happen on every node before anything is sent.
+    async fn map_chunks<F>(
+        &self,
+        new_name: String,
+        f: F,
+    ) -> Result<Arc<Self>, LiquidError>
+    where
+        F: Fn(&LocalDataFrame) -> Result<LocalDataFrame, LiquidError>,
+    {
+        let schema = f(&LocalDataFrame::new(self.get_schema()))?
+            .get_schema()
+            .clone();
+        let df_network_name =
+            format!("{}-ddf-{}", self.kv.network_name(), new_name);
+        let (network, read_streams, _kill_notifier) = Client::register_network(
+            self.kv.network.clone(),
+            df_network_name.to_string(),
+        )
+        .await?;
+        assert_eq!(self.node_id, { network.lock().await.id });
+
+        let df_chunk_map: HashMap<Range<usize>, Key> = self
+            .df_chunk_map
+            .iter()
+            .map(|(range, key)| {
+                let name = format!("{}-{}", &new_name, range.start);
+                (range.clone(), Key::new(&name, key.home))
+            })
+            .collect();
+        for (range, key) in &self.df_chunk_map {
+            if key.home == self.node_id {
+                let chunk = self.kv.wait_and_get(key).await?;
+                let mapped = f(&chunk)?;
+                self.kv.put(df_chunk_map[range].clone(), mapped).await?;
+            }
+        }
+        debug!("Mapped the chunks of {} on this node", &self.df_name);
+
+        Ok(self.build_from_chunk_map(
+            new_name,
+            schema,
+            df_chunk_map,
+            self.num_rows,
+            network,
+            read_streams,
+        ))
+    }
+
+    /// Creates a new `DistributedDataFrame` with the name `new_name` that
+    /// uses the given `network` and has the given chunks, which must be the
+    /// same on every node, and spawns the task that processes its messages.
+    fn build_from_chunk_map(
+        &self,
+        new_name: String,
+        schema: Schema,
+        df_chunk_map: HashMap<Range<usize>, Key>,
+        num_rows: usize,
+        network: Arc<Mutex<Client<DistributedDFMsg>>>,
+        read_streams: SelectAll<FramedStream<DistributedDFMsg>>,
+    ) -> Arc<Self> {
+        let (blob_sender, blob_receiver) = mpsc::channel(self.num_nodes);
+        let (filter_results_sender, filter_results) =
+            mpsc::channel(self.num_nodes);
+        let ddf = Arc::new(DistributedDataFrame {
+            row: Arc::new(RwLock::new(Row::new(&schema))),
+            schema,
+            df_name: new_name,
+            df_chunk_map,
+            num_rows,
+            network,
+            node_id: self.node_id,
+            num_nodes: self.num_nodes,
+            server_addr: self.server_addr.clone(),
+            my_ip: self.my_ip.clone(),
+            kv: self.kv.clone(),
+            internal_notifier: Arc::new(Notify::new()),
+            kill_notifier: Arc::new(Notify::new()),
+            blob_receiver: Mutex::new(blob_receiver),
+            filter_results: Mutex::new(filter_results),
+            n_joins: AtomicUsize::new(0),
+        });
+
+        // spawn a tokio task to process messages
+        let ddf_clone = ddf.clone();
+        tokio::spawn(async move {
+            DistributedDataFrame::process_messages(
+                ddf_clone,
+                read_streams,
+                blob_sender,
+                filter_results_sender,
+            )
+            .await
+            .unwrap();
+        });
+
+        ddf
+    }
+
+    /// Returns the rows of this `DistributedDataFrame` in the given `target`
+    /// range of row indices, in order, to compute a `Window` for the rows of
+    /// the chunk at `range`, which this node owns. The rows of that chunk
+    /// are read locally, while the rows of the chunks before and after it
+    /// are read from the boundaries of those chunks, see `with_window`.
+    async fn window_rows(
+        &self,
+        range: &Range<usize>,
+        target: &Range<usize>,
+        new_name: &str,
+        before: usize,
+        after: usize,
+    ) -> Result<LocalDataFrame, LiquidError> {
+        let mut overlapping: Vec<(&Range<usize>, &Key)> = self
+            .df_chunk_map
+            .iter()
+            .filter(|(other, _)| {
+                other.start < target.end && other.end > target.start
+            })
+            .collect();
+        overlapping.sort_by_key(|(other, _)| other.start);
+        let mut rows = LocalDataFrame::new(self.get_schema());
+        for (other, key) in overlapping {
+            let from = cmp::max(other.start, target.start) - other.start;
+            let to = cmp::min(other.end, target.end) - other.start;
+            if other == range {
+                let chunk = self.kv.get(key).await?;
+                rows.append_rows_from(&chunk, from..to)?;
+                continue;
+            }
+            let key = boundary_key(new_name, other, key.home);
+            let boundary = self.kv.wait_and_get(&key).await?;
+            let (head_len, tail_len) = boundary_lens(other, before, after);
+            if other.start < range.start {
+                // the rows are at the end of the chunk, so in its tail
+                let tail_start = other.end - other.start - tail_len;
+                let from = head_len + from - tail_start;
+                let to = head_len + to - tail_start;
+                rows.append_rows_from(&boundary, from..to)?;
+            } else {
+                // the rows are at the start of the chunk, so in its head
+                rows.append_rows_from(&boundary, from..to)?;
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// Waits until every node has called `barrier`, by sending an empty blob
+    /// to every other node and waiting for one from each of them.
+    async fn barrier(&self) -> Result<(), LiquidError> {
+        for node_id in 1..=self.num_nodes {
+            if node_id != self.node_id {
+                self.send_blob(node_id, &()).await?;
+            }
+        }
+        for _ in 1..self.num_nodes {
+            recv_blob(&self.blob_receiver).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new `DistributedDataFrame` with a column named `name` that
+    /// has the value of the given `window` function over the column at
+    /// `col_idx` for every row, as in [`LocalDataFrame::window`]. The new
+    /// `DistributedDataFrame` has the name `"<df_name>-with-<name>"`.
+    ///
+    /// The window of a row may reach into the chunks before or after the
+    /// chunk it is in, so for every chunk it owns each node puts the
+    /// boundary of the chunk into the `KVStore`: its first rows that the
+    /// windows of the rows before it reach, followed by its last rows that
+    /// the windows of the rows after it reach. Each node then reads the
+    /// boundaries of the chunks around each chunk it owns, so only those
+    /// rows are sent between nodes. For `Window::CumSum`, each node instead
+    /// shares the total of every chunk it owns, and starts the running sum
+    /// of each chunk from the totals of the chunks before it. The boundaries
+    /// and totals are removed once every node is done with them. Every node
+    /// must call `with_window` for it to complete.
+    ///
+    /// # Errors
+    /// The same errors as [`LocalDataFrame::window`] are returned on every
+    /// node, and if a column is already named `name`,
+    /// `LiquidError::NameAlreadyExists` is returned.
+    ///
+    /// [`LocalDataFrame::window`]: struct.LocalDataFrame.html#method.window
+    pub async fn with_window(
+        &self,
+        col_idx: usize,
+        window: Window,
+        name: &str,
+    ) -> Result<Arc<Self>, LiquidError> {
+        // checked on an empty chunk before anything is sent, so that every
+        // node fails
+        let mut empty = LocalDataFrame::new(self.get_schema());
+        let col = empty.window(col_idx, window)?;
+        empty.add_column(col, Some(name.to_string()))?;
+        let schema = empty.get_schema().clone();
+        let new_name = format!("{}-with-{}", &self.df_name, name);
+        let df_network_name =
+            format!("{}-ddf-{}", self.kv.network_name(), new_name);
+        let (network, read_streams, _kill_notifier) = Client::register_network(
+            self.kv.network.clone(),
+            df_network_name.to_string(),
+        )
+        .await?;
+        assert_eq!(self.node_id, { network.lock().await.id });
+
+        let mut my_chunks: Vec<(&Range<usize>, &Key)> = self
+            .df_chunk_map
+            .iter()
+            .filter(|(_, key)| key.home == self.node_id)
+            .collect();
+        my_chunks.sort_by_key(|(range, _)| range.start);
+        let total_key = |range: &Range<usize>, home: usize| {
+            let key_name = format!("{}-total-{}", &new_name, range.start);
+            Key::new(&key_name, home)
+        };
+        let (before, after) = window.context();
+        let zero = match self.get_schema().col_type(col_idx)? {
+            DataType::Int => Data::Int(0),
+            _ => Data::Float(0.0),
+        };
+        if window == Window::CumSum {
+            for (range, key) in &my_chunks {
+                let chunk = self.kv.wait_and_get(key).await?;
+                // the total of a chunk is the last value of its running sum
+                let total = match chunk.cumsum_from(col_idx, &zero)? {
+                    Column::Int(c) => {
+                        c.into_iter().flatten().last().map(Data::Int)
+                    }
+                    Column::Float(c) => {
+                        c.into_iter().flatten().last().map(Data::Float)
+                    }
+                    _ => None,
+                }
+                .unwrap_or_else(|| zero.clone());
+                let total_key = total_key(range, self.node_id);
+                self.kv.put(total_key, LocalDataFrame::from(total)).await?;
+            }
+        } else {
+            let before = before.unwrap_or(0);
+            for (range, key) in &my_chunks {
+                let chunk = self.kv.get(key).await?;
+                let (head_len, tail_len) = boundary_lens(range, before, after);
+                let mut boundary = LocalDataFrame::new(self.get_schema());
+                boundary.append_rows_from(&chunk, 0..head_len)?;
+                let n_rows = chunk.n_rows();
+                boundary.append_rows_from(&chunk, n_rows - tail_len..n_rows)?;
+                let key = boundary_key(&new_name, range, self.node_id);
+                self.kv.put(key, boundary).await?;
+            }
+        }
+
+        let df_chunk_map: HashMap<Range<usize>, Key> = self
+            .df_chunk_map
+            .iter()
+            .map(|(range, key)| {
+                let name = format!("{}-{}", &new_name, range.start);
+                (range.clone(), Key::new(&name, key.home))
+            })
+            .collect();
+        for (range, _) in my_chunks {
+            let start =
+                range.start - cmp::min(before.unwrap_or(0), range.start);
+            let end = cmp::min(range.end + after, self.num_rows);
+            let mut rows = self
+                .window_rows(
+                    range,
+                    &(start..end),
+                    &new_name,
+                    before.unwrap_or(0),
+                    after,
+                )
+                .await?;
+            let col = match before {
+                Some(_) => rows.window(col_idx, window)?,
+                None => {
+                    let mut offset = zero.clone();
+                    for (other, key) in &self.df_chunk_map {
+                        if other.start < range.start {
+                            let total = self
+                                .kv
+                                .wait_and_get(&total_key(other, key.home))
+                                .await?
+                                .get(0, 0)?;
+                            offset = match (offset, total) {
+                                (Data::Int(a), Data::Int(b)) => {
+                                    Data::Int(a.wrapping_add(b))
+                                }
+                                (Data::Float(a), Data::Float(b)) => {
+                                    Data::Float(a + b)
+                                }
+                                _ => return Err(LiquidError::TypeMismatch),
+                            };
+                        }
+                    }
+                    rows.cumsum_from(col_idx, &offset)?
+                }
+            };
+            rows.add_column(col, Some(name.to_string()))?;
+            let from = range.start - start;
+            let chunk = rows.slice(from, from + range.end - range.start)?;
+            self.kv
+                .put(df_chunk_map[range].clone(), chunk.to_local())
+                .await?;
+        }
+        debug!("Computed {} for the chunks on this node", name);
+
+        // the other nodes may still be reading our boundaries or totals
+        self.barrier().await?;
+        for (range, key) in &self.df_chunk_map {
+            self.kv.remove(&total_key(range, key.home)).await;
+            self.kv
+                .remove(&boundary_key(&new_name, range, key.home))
+                .await;
+        }
+
+        Ok(self.build_from_chunk_map(
+            new_name,
+            schema,
+            df_chunk_map,
+            self.num_rows,
+            network,
+            read_streams,
+        ))
+    }
+
+    /// Creates a new `DistributedDataFrame` of a `fraction` of the rows of
+    /// this `DistributedDataFrame` (rounded to the nearest row), sampled
+    /// uniformly without replacement. The new `DistributedDataFrame` has the
+    /// name `"<df_name>-sample-<seed>"` and the sampled rows keep their
+    /// relative order and stay on the node they were on.
+    ///
+    /// Every node draws the same row indices from the whole
+    /// `DistributedDataFrame` using the given `seed`, rather than sampling a
+    /// `fraction` of each chunk, so every row is equally likely to be
+    /// selected no matter how the chunks are sized and no rows or counts
+    /// have to be exchanged. Every node must call `sample` with the same
+    /// arguments for it to complete.
+    pub async fn sample(
+        &self,
+        fraction: f64,
+        seed: u64,
+    ) -> Result<Arc<Self>, LiquidError> {
+        let new_name = format!("{}-sample-{}", &self.df_name, seed);
+        let df_network_name =
+            format!("{}-ddf-{}", self.kv.network_name(), new_name);
+        let (network, read_streams, _kill_notifier) = Client::register_network(
+            self.kv.network.clone(),
+            df_network_name.to_string(),
+        )
+        .await?;
+        assert_eq!(self.node_id, { network.lock().await.id });
+
+        let n = sample_size(fraction, self.num_rows);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut indices =
+            rand::seq::index::sample(&mut rng, self.num_rows, n).into_vec();
+        indices.sort_unstable();
+        let mut chunks: Vec<(&Range<usize>, &Key)> =
+            self.df_chunk_map.iter().collect();
+        chunks.sort_by_key(|(range, _)| range.start);
+
+        // every node knows how many rows are picked from each chunk, so they
+        // all build the same map, leaving out chunks with no rows picked
+        let mut df_chunk_map = HashMap::new();
+        let mut picked = indices.into_iter().peekable();
+        let mut num_rows = 0;
+        for (range, key) in chunks {
+            let mut rows = Vec::new();
+            while let Some(&row_idx) = picked.peek() {
+                if row_idx >= range.end {
+                    break;
+                }
+                rows.push(row_idx - range.start);
+                picked.next();
+            }
+            if rows.is_empty() {
+                continue;
+            }
+            let new_range = num_rows..num_rows + rows.len();
+            let name = format!("{}-{}", &new_name, num_rows);
+            let new_key = Key::new(&name, key.home);
+            if key.home == self.node_id {
+                let chunk = self.kv.wait_and_get(key).await?;
+                self.kv.put(new_key.clone(), chunk.take_rows(&rows)).await?;
+            }
+            num_rows = new_range.end;
+            df_chunk_map.insert(new_range, new_key);
+        }
+        debug!("Sampled {} rows of {}", num_rows, &self.df_name);
+
+        Ok(self.build_from_chunk_map(
+            new_name,
+            self.get_schema().clone(),
+            df_chunk_map,
+            num_rows,
+            network,
+            read_streams,
+        ))
+    }
+
+    /// Randomly splits the rows of this `DistributedDataFrame` into two new
+    /// `DistributedDataFrame`s, a training set named `"<df_name>-train"` and
+    /// a test set named `"<df_name>-test"` that has `test_fraction` of the
+    /// rows of each chunk (rounded to the nearest row). Both keep the
+    /// `Schema` of this `DistributedDataFrame`, and the rows of each keep
+    /// their relative order and stay on the node they were on.
+    ///
+    /// Each chunk is split with [`LocalDataFrame::train_test_split`] using a
+    /// seed derived from `seed` and the start of the chunk, so the split is
+    /// deterministic and every node knows how many rows of each chunk end up
+    /// in each set without exchanging any rows or counts. Every node must
+    /// call `train_test_split` with the same arguments for it to complete.
+    ///
+    /// [`LocalDataFrame::train_test_split`]: struct.LocalDataFrame.html#method.train_test_split
+    pub async fn train_test_split(
+        &self,
+        test_fraction: f64,
+        seed: u64,
+    ) -> Result<(Arc<Self>, Arc<Self>), LiquidError> {
+        let train_name = format!("{}-train", &self.df_name);
+        let test_name = format!("{}-test", &self.df_name);
+        let mut networks = Vec::new();
+        for new_name in &[&train_name, &test_name] {
+            let df_network_name =
+                format!("{}-ddf-{}", self.kv.network_name(), new_name);
+            let (network, read_streams, _kill_notifier) =
+                Client::register_network(
+                    self.kv.network.clone(),
+                    df_network_name.to_string(),
+                )
+                .await?;
+            assert_eq!(self.node_id, { network.lock().await.id });
+            networks.push((network, read_streams));
+        }
+
+        let mut chunks: Vec<(&Range<usize>, &Key)> =
+            self.df_chunk_map.iter().collect();
+        chunks.sort_by_key(|(range, _)| range.start);
+        let train_fraction = 1.0 - test_fraction;
+        let mut train_map = HashMap::new();
+        let mut test_map = HashMap::new();
+        let (mut train_rows, mut test_rows) = (0, 0);
+        for (range, key) in chunks {
+            let n_train = sample_size(train_fraction, range.end - range.start);
+            let n_test = range.end - range.start - n_train;
+            let train_key =
+                Key::new(&format!("{}-{}", &train_name, train_rows), key.home);
+            let test_key =
+                Key::new(&format!("{}-{}", &test_name, test_rows), key.home);
+            if key.home == self.node_id {
+                let chunk = self.kv.wait_and_get(key).await?;
+                let chunk_seed = seed.wrapping_add(range.start as u64);
+                let (train, test) =
+                    chunk.train_test_split(train_fraction, chunk_seed);
+                if n_train > 0 {
+                    self.kv.put(train_key.clone(), train).await?;
+                }
+                if n_test > 0 {
+                    self.kv.put(test_key.clone(), test).await?;
+                }
+            }
+            // chunks with no rows in a set are left out of its map
+            if n_train > 0 {
+                train_map.insert(train_rows..train_rows + n_train, train_key);
+                train_rows += n_train;
+            }
+            if n_test > 0 {
+                test_map.insert(test_rows..test_rows + n_test, test_key);
+                test_rows += n_test;
+            }
+        }
+        debug!(
+            "Split {} into {} training and {} test rows",
+            &self.df_name, train_rows, test_rows
+        );
+
+        let (test_network, test_streams) = networks.pop().unwrap();
+        let (train_network, train_streams) = networks.pop().unwrap();
+        let train = self.build_from_chunk_map(
+            train_name,
+            self.get_schema().clone(),
+            train_map,
+            train_rows,
+            train_network,
+            train_streams,
+        );
+        let test = self.build_from_chunk_map(
+            test_name,
+            self.get_schema().clone(),
+            test_map,
+            test_rows,
+            test_network,
+            test_streams,
+        );
+
+        Ok((train, test))
+    }
+
+    /// Performs a distributed join of this `DistributedDataFrame` with
+    /// `other`, matching rows where the value in the `left_key` column of
+    /// this `DistributedDataFrame` equals the value in the `right_key` column
+    /// of `other`. The given `kind` determines whether rows without a match
+    /// are kept, as in [`LocalDataFrame::join`].
+    ///
+    /// The rows of both `DistributedDataFrame`s are first shuffled through
+    /// the `KVStore` so that rows with equal keys end up on the same node,
+    /// by hash partitioning their key columns. Each node then joins the rows
+    /// it received locally, and the nodes exchange the sizes of their
+    /// results so that every node builds the same joined
+    /// `DistributedDataFrame`. The rows of the result are in no particular
+    /// order, and every node must call `join` for it to complete.
+    ///
+    /// The joined `DistributedDataFrame` is named
+    /// `"<left_df_name>-<right_df_name>-joined"`, followed by `-<n>` if this
+    /// `DistributedDataFrame` has already been joined `n` times.
+    ///
+    /// # Errors
+    /// - `LiquidError::ColIndexOutOfBounds` if either key column doesn't exist
+    /// - `LiquidError::TypeMismatch` if the key columns have different types
+    ///
+    /// [`LocalDataFrame::join`]: struct.LocalDataFrame.html#method.join
+    pub async fn join(
+        &self,
+        other: &Self,
+        left_key: usize,
+        right_key: usize,
+        kind: JoinKind,
+    ) -> Result<Arc<Self>, LiquidError> {
+        // checked before any rows are shuffled, so that every node fails
+        if self.get_schema().col_type(left_key)?
+            != other.get_schema().col_type(right_key)?
+        {
+            return Err(LiquidError::TypeMismatch);
+        }
+        // the name must be the same on every node, and repeated joins get a
+        // suffix so they don't register a network that already exists
+        let new_name = match self.n_joins.fetch_add(1, Ordering::SeqCst) {
+            0 => format!("{}-{}-joined", &self.df_name, &other.df_name),
+            n => format!("{}-{}-joined-{}", &self.df_name, &other.df_name, n),
+        };
+        let left = self
+            .shuffle(&[left_key], &format!("{}-left", &new_name))
+            .await?;
+        let right = other
+            .shuffle(&[right_key], &format!("{}-right", &new_name))
+            .await?;
+        let joined = left.join(&right, left_key, right_key, kind)?;
+        info!(
+            "Joined {} rows with {} rows, have {} rows after join",
+            left.n_rows(),
+            right.n_rows(),
+            joined.n_rows()
+        );
+
+        self.collect_node_results(new_name, joined).await
+    }
+
+    /// Removes duplicate rows from this `DistributedDataFrame`, keeping one
+    /// row for each distinct combination of values in the `subset` columns,
+    /// or in all columns if `subset` is `None`, as in
+    /// [`LocalDataFrame::unique`]. Returns a new `DistributedDataFrame` with
+    /// the name `"<df_name>-unique"`.
+    ///
+    /// The rows are first shuffled through the `KVStore` by hash
+    /// partitioning the `subset` columns, so that duplicate rows end up on
+    /// the same node, and each node then deduplicates the rows it received
+    /// locally. Which of the duplicate rows is kept and the order of the rows
+    /// of the result are unspecified. Every node must call `unique` for it to
+    /// complete.
+    ///
+    /// # Errors
+    /// If any of the `subset` columns are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned.
+    ///
+    /// [`LocalDataFrame::unique`]: struct.LocalDataFrame.html#method.unique
+    pub async fn unique(
+        &self,
+        subset: Option<&[usize]>,
+    ) -> Result<Arc<Self>, LiquidError> {
+        let all_cols: Vec<usize> = (0..self.n_cols()).collect();
+        let subset = subset.unwrap_or(&all_cols);
+        // checked before any rows are shuffled, so that every node fails
+        for &col_idx in subset {
+            self.get_schema().col_type(col_idx)?;
+        }
+        // the name must be the same on every node
+        let new_name = format!("{}-unique", &self.df_name);
+        let shuffled = self.shuffle(subset, &new_name).await?;
+        let unique = shuffled.unique_by(subset)?;
+        info!(
+            "Removed {} duplicate rows, have {} rows left",
+            shuffled.n_rows() - unique.n_rows(),
+            unique.n_rows()
+        );
+
+        self.collect_node_results(new_name, unique).await
+    }
+
+    /// Creates a new `DistributedDataFrame` with the name `new_name` from
+    /// the `result` computed by every node, e.g. the rows joined by each node
+    /// in a distributed `join`. Each node keeps its own `result` as a single
+    /// chunk, and the nodes exchange the sizes of their results so that
+    /// every node builds the same `DistributedDataFrame`. Every node must
+    /// call `collect_node_results` for it to complete.
+    async fn collect_node_results(
+        &self,
+        new_name: String,
+        result: LocalDataFrame,
+    ) -> Result<Arc<Self>, LiquidError> {
+        let df_network_name =
+            format!("{}-ddf-{}", self.kv.network_name(), new_name);
+        let (network, mut read_streams, _kill_notifier) =
+            Client::register_network(
+                self.kv.network.clone(),
+                df_network_name.to_string(),
+            )
+            .await?;
+        assert_eq!(self.node_id, { network.lock().await.id });
+        let schema = result.get_schema().clone();
+        let num_rows_result = result.n_rows();
+
+        // put our result in our KVStore only if its not empty
+        let mut key = None;
+        if num_rows_result > 0 {
+            let k = Key::new(&new_name, self.node_id);
+            key = Some(k.clone());
+            self.kv.put(k, result).await?;
+        }
+
+        // every node tells every other node the size of its result, so they
+        // all build the same chunk map
+        let results = DistributedDFMsg::FilterResult {
+            num_rows: num_rows_result,
+            filtered_df_key: key.clone(),
+        };
+        network.lock().await.broadcast(results).await?;
+        let mut results = vec![(self.node_id, num_rows_result, key)];
+        while results.len() < self.num_nodes {
+            let msg = read_streams.next().await.unwrap()?;
+            match msg.msg {
+                DistributedDFMsg::FilterResult {
+                    num_rows,
+                    filtered_df_key,
+                } => results.push((msg.sender_id, num_rows, filtered_df_key)),
+                _ => return Err(LiquidError::UnexpectedMessage),
+            }
+        }
+        results.sort_by_key(|(node_id, _, _)| *node_id);
+        let mut df_chunk_map = HashMap::new();
+        let mut num_rows = 0;
+        for (_, n, key) in results {
+            if let Some(key) = key {
+                df_chunk_map.insert(num_rows..num_rows + n, key);
+                num_rows += n;
+            }
+        }
+        debug!("Got the results of all nodes");
+
+        Ok(self.build_from_chunk_map(
+            new_name,
+            schema,
+            df_chunk_map,
+            num_rows,
+            network,
+            read_streams,
+        ))
+    }
+
+    /// Hash partitions the rows this node owns by the `key_cols` columns
+    /// into one partition per node, and sends each partition to its node
+    /// through the `KVStore` under a `Key` derived from `shuffle_name`.
+    /// Returns all the rows sent to this node, so that rows with equal keys
+    /// in the `key_cols` columns end up on the same node, and removes the
+    /// partitions it received from the `KVStore`. Every node must call
+    /// `shuffle` for it to complete.
+    async fn shuffle(
+        &self,
+        key_cols: &[usize],
+        shuffle_name: &str,
+    ) -> Result<LocalDataFrame, LiquidError> {
+        let mut my_chunks: Vec<(&Range<usize>, &Key)> = self
+            .df_chunk_map
+            .iter()
+            .filter(|(_, key)| key.home == self.node_id)
+            .collect();
+        my_chunks.sort_by_key(|(range, _)| range.start);
+        let mut local = LocalDataFrame::new(self.get_schema());
+        for (_, key) in my_chunks {
+            let chunk = self.kv.wait_and_get(key).await?;
+            local.append_rows_from(&chunk, 0..chunk.n_rows())?;
+        }
+
+        let partitions = local.hash_partition_by(key_cols, self.num_nodes)?;
+        let sent_name = format!("{}-from-{}", shuffle_name, self.node_id);
+        for (idx, partition) in partitions.into_iter().enumerate() {
+            self.kv
+                .put(Key::new(&sent_name, idx + 1), partition)
+                .await?;
+        }
+
+        let mut shuffled = LocalDataFrame::new(self.get_schema());
+        for from in 1..=self.num_nodes {
+            let received_name = format!("{}-from-{}", shuffle_name, from);
+            let key = Key::new(&received_name, self.node_id);
+            let partition = self.kv.wait_and_get(&key).await?;
+            shuffled.append_rows_from(&partition, 0..partition.n_rows())?;
+            // this node owns the partitions sent to it, so it is the only one
+            // that can remove them once they are read
+            self.kv.remove(&key).await;
+        }
+        debug!("Received {} shuffled rows", shuffled.n_rows());
+
+        Ok(shuffled)
+    }
+
     /// Return the (total) number of rows across all nodes for this
     /// `DistributedDataFrame`
     pub fn n_rows(&self) -> usize {
@@ -874,6 +2037,134 @@ impl Iterator for DataChunkerator {
     }
 }
 
+/// The `Key` of the boundary of the chunk at `range` that is put by the node
+/// `home` when computing a `Window` for the `DistributedDataFrame` named
+/// `new_name`, see `DistributedDataFrame::with_window`.
+fn boundary_key(new_name: &str, range: &Range<usize>, home: usize) -> Key {
+    Key::new(&format!("{}-boundary-{}", new_name, range.start), home)
+}
+
+/// Returns the number of rows at the start and at the end of the chunk at
+/// `range` that are in its boundary, which are the rows the windows of the
+/// rows `after` them and `before` them reach, up to the whole chunk.
+fn boundary_lens(
+    range: &Range<usize>,
+    before: usize,
+    after: usize,
+) -> (usize, usize) {
+    let len = range.end - range.start;
+    (cmp::min(after, len), cmp::min(before, len))
+}
+
+/// Splits `num_rows` rows into `num_nodes` consecutive ranges whose lengths
+/// differ by at most one, with the longer ranges first.
+fn balanced_ranges(num_rows: usize, num_nodes: usize) -> Vec<Range<usize>> {
+    let per_node = num_rows / num_nodes;
+    let extra = num_rows % num_nodes;
+    let mut start = 0;
+    (0..num_nodes)
+        .map(|idx| {
+            let len = per_node + if idx < extra { 1 } else { 0 };
+            let range = start..start + len;
+            start += len;
+            range
+        })
+        .collect()
+}
+
+/// Groups consecutive row groups with the given `sizes` into at most
+/// `num_nodes` ranges of row group indices so that every range has about the
+/// same number of rows. A range is closed as soon as it reaches its share of
+/// the rows that are left. Always returns at least one range, which is empty
+/// if there are no row groups, so that the `Schema` can still be read.
+#[cfg(feature = "parquet")]
+fn row_group_ranges(sizes: &[usize], num_nodes: usize) -> Vec<Range<usize>> {
+    let mut rows_left: usize = sizes.iter().sum();
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut rows = 0;
+    for (idx, size) in sizes.iter().enumerate() {
+        rows += size;
+        let nodes_left = num_nodes - ranges.len();
+        if nodes_left > 1 && rows * nodes_left >= rows_left {
+            ranges.push(start..idx + 1);
+            rows_left -= rows;
+            start = idx + 1;
+            rows = 0;
+        }
+    }
+    if start < sizes.len() || ranges.is_empty() {
+        ranges.push(start..sizes.len());
+    }
+
+    ranges
+}
+
+/// Waits for the next blob from the given `blob_receiver`. Returns a
+/// `LiquidError::BlobChannelClosed` if the sending half was dropped, e.g.
+/// because the connection to another node failed, instead of panicking in the
+/// middle of a distributed operation.
+async fn recv_blob(
+    blob_receiver: &Mutex<Receiver<Vec<u8>>>,
+) -> Result<Vec<u8>, LiquidError> {
+    let blob = { blob_receiver.lock().await.recv().await };
+    blob.ok_or(LiquidError::BlobChannelClosed)
+}
+
+/// Infers the `Schema` of the CSV file at `file_name`, read with `options`,
+/// counts its records, then parses it in chunks of at most the number of
+/// records divided by `num_nodes` and sends them over `sender`. Stops early
+/// if `sender` is closed, since the data frame could not be created. Logs
+/// the number of discarded malformed records once the whole file is sent.
+///
+/// Blocks, so it must be called from a thread where blocking is acceptable.
+fn send_csv_chunks(
+    file_name: &str,
+    options: &CsvOptions,
+    num_nodes: usize,
+    sender: &mut Sender<Result<LocalDataFrame, LiquidError>>,
+) -> Result<(), LiquidError> {
+    let schema = csv_io::infer_schema(file_name, options)?;
+    let mut counter = options.reader(file_name)?;
+    let mut total_records = 0;
+    for record in csv_io::data_records(&mut counter, options)? {
+        record?;
+        total_records += 1;
+    }
+    let max_rows_per_node =
+        cmp::max(1, (total_records + num_nodes - 1) / num_nodes);
+    info!(
+        "Total records: {} max rows per node: {}",
+        total_records, max_rows_per_node
+    );
+    info!("Inferred schema: {:?}", &schema);
+
+    let mut reader = options.reader(file_name)?;
+    let mut records = csv_io::data_records(&mut reader, options)?;
+    let mut discarded = 0;
+    let mut is_first = true;
+    loop {
+        let chunk = records
+            .by_ref()
+            .take(max_rows_per_node)
+            .collect::<Result<Vec<_>, _>>()?;
+        // an empty file still needs a chunk for its schema
+        if chunk.is_empty() && !is_first {
+            break;
+        }
+        is_first = false;
+        let (chunk, n_discarded) =
+            LocalDataFrame::from_csv_records(schema.clone(), &chunk);
+        discarded += n_discarded;
+        if executor::block_on(sender.send(Ok(chunk))).is_err() {
+            // the data frame could not be created
+            return Ok(());
+        }
+    }
+    csv_io::warn_discarded(discarded + records.n_malformed);
+    Ok(())
+}
+
 fn n_rows(data: &[Column]) -> usize {
     match data.get(0) {
         None => 0,
@@ -900,3 +2191,40 @@ fn count_new_lines(file_name: &str) -> usize {
         buf_reader.consume(len);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balanced_ranges() {
+        assert_eq!(balanced_ranges(12, 3), vec![0..4, 4..8, 8..12]);
+        assert_eq!(balanced_ranges(5, 3), vec![0..2, 2..4, 4..5]);
+        assert_eq!(balanced_ranges(1, 3), vec![0..1, 1..1, 1..1]);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_row_group_ranges() {
+        assert_eq!(row_group_ranges(&[5, 5, 5, 5], 2), vec![0..2, 2..4]);
+        assert_eq!(row_group_ranges(&[10, 1, 1, 1, 1], 2), vec![0..1, 1..5]);
+        assert_eq!(row_group_ranges(&[3, 3], 4), vec![0..1, 1..2]);
+        assert_eq!(row_group_ranges(&[4, 4, 4], 1), vec![0..3]);
+        assert_eq!(row_group_ranges(&[], 3), vec![0..0]);
+    }
+
+    #[tokio::test]
+    async fn test_recv_blob_after_sender_dropped() {
+        let (mut blob_sender, blob_receiver) = mpsc::channel(2);
+        let blob_receiver = Mutex::new(blob_receiver);
+        blob_sender.send(vec![1, 2, 3]).await.unwrap();
+        drop(blob_sender);
+
+        // blobs that were already sent are still received
+        assert_eq!(recv_blob(&blob_receiver).await.unwrap(), vec![1, 2, 3]);
+        assert!(matches!(
+            recv_blob(&blob_receiver).await,
+            Err(LiquidError::BlobChannelClosed)
+        ));
+    }
+}
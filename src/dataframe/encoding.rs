@@ -0,0 +1,147 @@
+//! The serialized form of the columns of a `LocalDataFrame`, which dictionary
+//! encodes `String` columns with few distinct values so that sending them to
+//! other nodes, e.g. through the `KVStore`, is cheaper. It is used with
+//! `#[serde(with = "crate::dataframe::encoding")]` on the columns of a
+//! `LocalDataFrame`, so the encoding is transparent to everything else.
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use sorer::dataframe::Column;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A `String` column is dictionary encoded if it has at most this many
+/// distinct values per row
+const MAX_DICTIONARY_RATIO: f64 = 0.5;
+
+/// A borrowed `Column` in the form it is serialized in
+#[derive(Serialize)]
+enum EncodedColumn<'a> {
+    /// A column that is serialized as is
+    Plain(&'a Column),
+    /// A `String` column stored as the distinct values in `pool` and, for
+    /// every row, the index of its value in `pool`, or `None` if it is null
+    Dictionary {
+        pool: Vec<&'a str>,
+        codes: Vec<Option<u32>>,
+    },
+}
+
+/// An owned `Column` in the form it is serialized in. Must have the same
+/// variants in the same order as `EncodedColumn`
+#[derive(Deserialize)]
+enum DecodedColumn {
+    Plain(Column),
+    Dictionary {
+        pool: Vec<String>,
+        codes: Vec<Option<u32>>,
+    },
+}
+
+/// Serializes the given `columns`, dictionary encoding the `String` columns
+/// where that is smaller.
+pub(crate) fn serialize<S: Serializer>(
+    columns: &[Arc<Column>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(columns.iter().map(|col| encode(col)))
+}
+
+/// Deserializes columns that were serialized with `serialize`.
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Arc<Column>>, D::Error> {
+    Vec::<DecodedColumn>::deserialize(deserializer)?
+        .into_iter()
+        .map(|col| match col {
+            DecodedColumn::Plain(col) => Ok(Arc::new(col)),
+            DecodedColumn::Dictionary { pool, codes } => codes
+                .into_iter()
+                .map(|code| match code {
+                    Some(code) => match pool.get(code as usize) {
+                        Some(value) => Ok(Some(value.clone())),
+                        None => Err(D::Error::custom(format!(
+                            "dictionary code {} is out of bounds",
+                            code
+                        ))),
+                    },
+                    None => Ok(None),
+                })
+                .collect::<Result<_, _>>()
+                .map(|values| Arc::new(Column::String(values))),
+        })
+        .collect()
+}
+
+/// Returns the form `col` is serialized in, which is `Dictionary` for a
+/// `String` column with few enough distinct values.
+fn encode(col: &Column) -> EncodedColumn<'_> {
+    let values = match col {
+        Column::String(values) => values,
+        _ => return EncodedColumn::Plain(col),
+    };
+    let max_distinct = (values.len() as f64 * MAX_DICTIONARY_RATIO) as usize;
+    let mut pool = Vec::new();
+    let mut code_of: HashMap<&str, u32> = HashMap::new();
+    let mut codes = Vec::with_capacity(values.len());
+    for value in values {
+        match value {
+            Some(value) => {
+                let next = pool.len() as u32;
+                let code = *code_of.entry(value.as_str()).or_insert(next);
+                if code == next {
+                    if pool.len() == max_distinct {
+                        return EncodedColumn::Plain(col);
+                    }
+                    pool.push(value.as_str());
+                }
+                codes.push(Some(code));
+            }
+            None => codes.push(None),
+        }
+    }
+
+    EncodedColumn::Dictionary { pool, codes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::LocalDataFrame;
+    use bincode::{deserialize, serialize};
+
+    #[test]
+    fn test_dictionary_encoding() {
+        let labels: Vec<Option<String>> = (0..1000)
+            .map(|i| match i % 3 {
+                0 => None,
+                1 => Some("some fairly long label".to_string()),
+                _ => Some("another fairly long label".to_string()),
+            })
+            .collect();
+        let ids: Vec<Option<String>> =
+            (0..1000).map(|i| Some(i.to_string())).collect();
+        let mut df = LocalDataFrame::from(Column::String(labels.clone()));
+        df.add_column(Column::String(ids), None).unwrap();
+        df.add_column(Column::Int((0..1000).map(Some).collect()), None)
+            .unwrap();
+
+        let encoded = serialize(&df).unwrap();
+        assert_eq!(deserialize::<LocalDataFrame>(&encoded).unwrap(), df);
+        let label_df = LocalDataFrame::from(Column::String(labels.clone()));
+        let plain = serialize(&Column::String(labels)).unwrap();
+        assert!(serialize(&label_df).unwrap().len() < plain.len() / 2);
+
+        assert!(matches!(
+            encode(&df.data[0]),
+            EncodedColumn::Dictionary { .. }
+        ));
+        assert!(matches!(encode(&df.data[1]), EncodedColumn::Plain(_)));
+        assert!(matches!(encode(&df.data[2]), EncodedColumn::Plain(_)));
+        let empty = LocalDataFrame::from(Column::String(Vec::new()));
+        assert_eq!(
+            deserialize::<LocalDataFrame>(&serialize(&empty).unwrap()).unwrap(),
+            empty
+        );
+    }
+}
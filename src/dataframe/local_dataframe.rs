@@ -1,33 +1,90 @@
 //! Defines functionality for a `LocalDataFrame`
-use crate::dataframe::{Row, Rower, Schema};
+#[cfg(feature = "arrow")]
+use crate::dataframe::arrow_io;
+use crate::dataframe::{
+    csv_io, records, schema::no_column_named, Agg, ArithOp, CmpOp,
+    ColumnSummary, CsvOptions, DataFrameSlice, JoinKind, MapRower, Row, RowRef,
+    Rower, Schema, Window,
+};
 use crate::error::LiquidError;
+use crate::{
+    DISPLAY_MAX_COL_WIDTH, DISPLAY_MAX_ROWS, MIN_COLS_FOR_PARALLEL_PARSE,
+    SOR_SCHEMA_INFERENCE_ROWS,
+};
+#[cfg(feature = "arrow")]
+use arrow::datatypes::{Field, Schema as ArrowSchema};
+#[cfg(feature = "arrow")]
+use arrow::ipc::reader::FileReader;
+#[cfg(feature = "arrow")]
+use arrow::ipc::writer::FileWriter;
+#[cfg(feature = "arrow")]
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
 use crossbeam_utils::thread;
+use csv::StringRecord;
 use deepsize::DeepSizeOf;
-use serde::{Deserialize, Serialize};
+use log::warn;
+#[cfg(feature = "parquet")]
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sorer::dataframe::{from_file, Column, Data};
+use sorer::parsers::{parse_line, parse_line_with_schema};
 use sorer::schema::{infer_schema, DataType};
-use std::cmp::Ordering;
-use std::convert::TryInto;
+use std::borrow::Cow;
+use std::cmp::{self, Ordering};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Seek, SeekFrom};
+use std::iter::FromIterator;
+use std::ops::{Index, Range};
+use std::sync::Arc;
 
 /// Represents a local data frame which contains data stored in a columnar
 /// format and a well-defined `Schema`. Is useful for data sets that fit into
 /// memory or for testing/debugging purposes.
-#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, DeepSizeOf)]
+#[derive(Serialize, Deserialize, Clone, Debug, DeepSizeOf)]
 pub struct LocalDataFrame {
     /// The `Schema` of this data frame
     pub schema: Schema,
-    /// The data of this data frame, in columnar format
-    pub data: Vec<Column>,
+    /// The data of this data frame, in columnar format.
+    ///
+    /// Each `Column` is reference counted so that data frames created from
+    /// whole columns of another data frame (e.g. by [`clone`]) share the
+    /// storage of those columns instead of copying them. Shared columns are
+    /// copy-on-write: mutating a `Column` through a `LocalDataFrame`, e.g.
+    /// with `set_int` or `add_row`, first clones it if any other data frame
+    /// still holds a reference to it, so mutations are never visible to the
+    /// other data frames.
+    ///
+    /// When serialized, e.g. to be sent to another node, `String` columns
+    /// with few distinct values are dictionary encoded to save space.
+    ///
+    /// [`clone`]: #impl-Clone
+    #[serde(with = "crate::dataframe::encoding")]
+    pub data: Vec<Arc<Column>>,
     /// Number of threads for this computer
     pub n_threads: usize,
     /// Current row index for implementing the `Iterator` trait
     cur_row_idx: usize,
+    /// A reverse row name to row index map for all the named rows.
+    row_names: HashMap<String, usize>,
 }
 
 macro_rules! setter {
     ($func_name:ident, $type:ty, $sorer_type:ident) => {
         /// Mutates the value in this `DataFrame` at the given `col_idx, row_idx`
         /// to be changed to the given `data`.
+        ///
+        /// # Errors
+        /// - `LiquidError::ColIndexOutOfBounds` if `col_idx` is out of bounds
+        /// - `LiquidError::RowIndexOutOfBounds` if `row_idx` is out of bounds
+        /// - `LiquidError::TypeMismatch` if the column is of a different type
         pub fn $func_name(
             &mut self,
             col_idx: usize,
@@ -35,22 +92,22 @@ macro_rules! setter {
             data: $type,
         ) -> Result<(), LiquidError> {
             match self.schema.schema.get(col_idx) {
-                Some(DataType::$sorer_type) => {
-                    match self.data.get_mut(col_idx) {
-                        Some(Column::$sorer_type(col)) => {
-                            match col.get_mut(row_idx) {
-                                Some(d) => {
-                                    *d = Some(data);
-                                    Ok(())
-                                }
-                                None => Err(LiquidError::RowIndexOutOfBounds),
+                Some(DataType::$sorer_type) => match self.column_mut(col_idx) {
+                    Some(Column::$sorer_type(col)) => {
+                        match col.get_mut(row_idx) {
+                            Some(d) => {
+                                *d = Some(data);
+                                Ok(())
                             }
+                            None => Err(LiquidError::RowIndexOutOfBounds),
                         }
-                        None => Err(LiquidError::ColIndexOutOfBounds),
-                        _ => panic!("Something is horribly wrong"),
                     }
-                }
-                _ => Err(LiquidError::TypeMismatch),
+                    None => Err(LiquidError::ColIndexOutOfBounds),
+                    // the column was replaced with one of a different type
+                    _ => Err(LiquidError::TypeMismatch),
+                },
+                Some(_) => Err(LiquidError::TypeMismatch),
+                None => Err(LiquidError::ColIndexOutOfBounds),
             }
         }
     };
@@ -67,13 +124,100 @@ impl LocalDataFrame {
         let schema = Schema::from(infer_schema(file_name));
         let n_threads = num_cpus::get();
         let data =
-            from_file(file_name, schema.schema.clone(), from, len, n_threads);
+            from_file(file_name, schema.schema.clone(), from, len, n_threads)
+                .into_iter()
+                .map(Arc::new)
+                .collect();
         LocalDataFrame {
             schema,
             data,
             n_threads,
             cur_row_idx: 0,
+            row_names: HashMap::new(),
+        }
+    }
+
+    /// Creates a new `LocalDataFrame` from the given file in the same way as
+    /// [`from_sor`], but reads the whole range into memory first and then
+    /// parses its lines with `sorer`'s parser on the number of cores
+    /// available on this machine, each building the columns of a block of
+    /// lines. The columns of the blocks are joined in order, so the rows are
+    /// in the same order as in the file and the columns in the same order as
+    /// the inferred `Schema`.
+    ///
+    /// Only reads `len` bytes of the file, starting at the given byte offset
+    /// `from`. Partial lines at either end of that range are skipped. Rows
+    /// that are malformed for the `Schema`, including rows with more fields
+    /// than it has columns, are discarded, and a warning with the number of
+    /// discarded rows is logged.
+    ///
+    /// Data frames with fewer than `MIN_COLS_FOR_PARALLEL_PARSE` columns gain
+    /// nothing from this, so they are parsed with [`from_sor`].
+    ///
+    /// # Errors
+    /// If the file can't be read, a `LiquidError::NetworkError` with the
+    /// underlying `io::Error` is returned.
+    ///
+    /// [`from_sor`]: struct.LocalDataFrame.html#method.from_sor
+    pub fn from_sor_parallel(
+        file_name: &str,
+        from: usize,
+        len: usize,
+    ) -> Result<Self, LiquidError> {
+        // open the file first, since `sorer` panics if it can't be read
+        let mut file = File::open(file_name)?;
+        let schema = Schema::from(infer_schema(file_name));
+        if schema.width() < MIN_COLS_FOR_PARALLEL_PARSE {
+            return Ok(LocalDataFrame::from_sor(file_name, from, len));
+        }
+        let file_len = file.metadata()?.len();
+        // the range starts in the middle of a line unless the byte before it
+        // ends a line
+        let mut mid_line = false;
+        if from > 0 {
+            file.seek(SeekFrom::Start(from as u64 - 1))?;
+            let mut before = [0; 1];
+            mid_line = file.read(&mut before)? == 1 && before[0] != b'\n';
         }
+        let mut contents = Vec::new();
+        file.take(len as u64).read_to_end(&mut contents)?;
+        let at_eof = from as u64 + contents.len() as u64 >= file_len;
+        let lines = sor_lines(&contents, mid_line, at_eof);
+
+        LocalDataFrame::from_sor_lines(schema, &lines, num_cpus::get())
+    }
+
+    /// Creates a new `LocalDataFrame` by reading each of the given `SoR`
+    /// files in full with [`from_sor`] and stacking their rows in the order
+    /// the files are given, e.g. for data that is split across daily files.
+    /// All the files must have the same inferred `Schema`, and the column
+    /// names are those of the first file.
+    ///
+    /// # Errors
+    /// If the `Schema` of a file does not match the `Schema` of the first
+    /// file, a `LiquidError::SchemaMismatch` with the name of that file is
+    /// returned. If a file can not be read, a `LiquidError::NetworkError`
+    /// with the underlying `io::Error` is returned.
+    ///
+    /// [`from_sor`]: struct.LocalDataFrame.html#method.from_sor
+    pub fn from_sor_many(paths: &[&str]) -> Result<Self, LiquidError> {
+        let mut combined: Option<LocalDataFrame> = None;
+        for path in paths {
+            let len = fs::metadata(path)?.len() as usize;
+            let df = LocalDataFrame::from_sor(path, 0, len);
+            combined = Some(match combined {
+                None => df,
+                Some(acc) => {
+                    if acc.get_schema().schema != df.get_schema().schema {
+                        return Err(LiquidError::SchemaMismatch(
+                            path.to_string(),
+                        ));
+                    }
+                    acc.combine(df)?
+                }
+            });
+        }
+        Ok(combined.unwrap_or_else(|| LocalDataFrame::new(&Schema::new())))
     }
 
     /// Creates an empty `LocalDataFrame` from the given `Schema`. The
@@ -83,10 +227,14 @@ impl LocalDataFrame {
         let mut data = Vec::new();
         for data_type in &schema.schema {
             match data_type {
-                DataType::Bool => data.push(Column::Bool(Vec::new())),
-                DataType::Int => data.push(Column::Int(Vec::new())),
-                DataType::Float => data.push(Column::Float(Vec::new())),
-                DataType::String => data.push(Column::String(Vec::new())),
+                DataType::Bool => data.push(Arc::new(Column::Bool(Vec::new()))),
+                DataType::Int => data.push(Arc::new(Column::Int(Vec::new()))),
+                DataType::Float => {
+                    data.push(Arc::new(Column::Float(Vec::new())))
+                }
+                DataType::String => {
+                    data.push(Arc::new(Column::String(Vec::new())))
+                }
             }
         }
         let schema = Schema {
@@ -99,6 +247,7 @@ impl LocalDataFrame {
             data,
             n_threads: num_cpus::get(),
             cur_row_idx: 0,
+            row_names: HashMap::new(),
         }
     }
 
@@ -109,25 +258,31 @@ impl LocalDataFrame {
 
     /// Adds a `Column` to this `LocalDataFrame` with an optional name. Returns
     /// a `LiquidError::NameAlreadyExists` if the given `name` is not unique.
+    ///
+    /// If `col` is shorter than the other `Column`s in this `LocalDataFrame`,
+    /// it is padded with nulls, and if it is longer, all the other `Column`s
+    /// are padded with nulls. Use [`append_column_checked`] to instead get an
+    /// error when the lengths don't match.
+    ///
+    /// [`append_column_checked`]: struct.LocalDataFrame.html#method.append_column_checked
     pub fn add_column(
         &mut self,
         col: Column,
         name: Option<String>,
     ) -> Result<(), LiquidError> {
-        match &col {
-            Column::Int(_) => self.schema.add_column(DataType::Int, name),
-            Column::Bool(_) => self.schema.add_column(DataType::Bool, name),
-            Column::Float(_) => self.schema.add_column(DataType::Float, name),
-            Column::String(_) => self.schema.add_column(DataType::String, name),
-        }?;
+        if let Some(name) = &name {
+            if self.schema.col_idx(name).is_some() {
+                return Err(LiquidError::NameAlreadyExists);
+            }
+        }
 
-        match self.n_rows().cmp(&col.len()) {
-            Ordering::Equal => self.data.push(col),
+        let col = match self.n_rows().cmp(&col.len()) {
+            Ordering::Equal => col,
             Ordering::Less => {
                 // our data is shorter than `col`, must add Data::Null to
                 // all of our columns until they are equal length w/`col`
-                for j in 0..self.n_cols() - 1 {
-                    let c = self.data.get_mut(j).unwrap();
+                for c in self.data.iter_mut() {
+                    let c = Arc::make_mut(c);
                     for _ in 0..col.len() - c.len() {
                         match c {
                             Column::Bool(x) => x.push(None),
@@ -137,7 +292,7 @@ impl LocalDataFrame {
                         }
                     }
                 }
-                self.data.push(col)
+                col
             }
             Ordering::Greater => {
                 // our data is longer than `col`, we must add Data::Null to
@@ -151,26 +306,55 @@ impl LocalDataFrame {
                     Column::Bool(mut x) => {
                         let nones = vec![None; diff];
                         x.extend(nones.into_iter());
-                        self.data.push(Column::Bool(x))
+                        Column::Bool(x)
                     }
                     Column::Int(mut x) => {
                         let nones = vec![None; diff];
                         x.extend(nones.into_iter());
-                        self.data.push(Column::Int(x))
+                        Column::Int(x)
                     }
                     Column::Float(mut x) => {
                         let nones = vec![None; diff];
                         x.extend(nones.into_iter());
-                        self.data.push(Column::Float(x))
+                        Column::Float(x)
                     }
                     Column::String(mut x) => {
                         let nones = vec![None; diff];
                         x.extend(nones.into_iter());
-                        self.data.push(Column::String(x))
+                        Column::String(x)
                     }
                 }
             }
+        };
+
+        self.append_column_checked(col, name)
+    }
+
+    /// Adds a `Column` to this `LocalDataFrame` with an optional name, only
+    /// if it has the same length as the other `Column`s in this
+    /// `LocalDataFrame`. Any `Column` may be added to a `LocalDataFrame` with
+    /// no `Column`s.
+    ///
+    /// # Errors
+    /// If the length of `col` is not equal to `n_rows`, a
+    /// `LiquidError::RowIndexOutOfBounds` is returned. If the given `name` is
+    /// not unique, a `LiquidError::NameAlreadyExists` is returned. In both
+    /// cases this `LocalDataFrame` is left unchanged.
+    pub fn append_column_checked(
+        &mut self,
+        col: Column,
+        name: Option<String>,
+    ) -> Result<(), LiquidError> {
+        if self.n_cols() != 0 && col.len() != self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
         }
+        match &col {
+            Column::Int(_) => self.schema.add_column(DataType::Int, name),
+            Column::Bool(_) => self.schema.add_column(DataType::Bool, name),
+            Column::Float(_) => self.schema.add_column(DataType::Float, name),
+            Column::String(_) => self.schema.add_column(DataType::String, name),
+        }?;
+        self.data.push(Arc::new(col));
 
         Ok(())
     }
@@ -185,7 +369,7 @@ impl LocalDataFrame {
         // without macros (must match on the types) and it is for performance
         // so that we don't have to box/unbox values when constructing the
         // DataFrame and mapping over it
-        match self.data.get(col_idx) {
+        match self.column(col_idx) {
             Some(Column::Int(col)) => match col.get(row_idx) {
                 Some(optional_data) => match optional_data {
                     Some(data) => Ok(Data::Int(*data)),
@@ -232,6 +416,67 @@ impl LocalDataFrame {
         self.schema.col_name(col_idx)
     }
 
+    /// Replaces the names of all the `Column`s in this `LocalDataFrame` with
+    /// the given `names`, e.g. to apply the header of a `SoR` file. The name
+    /// at index `i` is given to the `Column` at index `i`.
+    ///
+    /// # Errors
+    /// If the number of `names` does not match the number of `Column`s, a
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If the `names` are not
+    /// unique, a `LiquidError::NameAlreadyExists` is returned. In both cases
+    /// the existing names are left unchanged.
+    pub fn set_col_names(
+        &mut self,
+        names: Vec<String>,
+    ) -> Result<(), LiquidError> {
+        self.schema.set_col_names(names)
+    }
+
+    /// Renames the `Column` named `old` to `new`.
+    ///
+    /// # Errors
+    /// If there is no `Column` named `old`, a `LiquidError::InvalidArgument`
+    /// is returned, and if another `Column` is already named `new` a
+    /// `LiquidError::NameAlreadyExists` is returned.
+    pub fn rename_col(
+        &mut self,
+        old: &str,
+        new: String,
+    ) -> Result<(), LiquidError> {
+        self.schema.rename_col(old, new)
+    }
+
+    /// Reorders the `Column`s of this `LocalDataFrame` so that the `Column`
+    /// at index `order[i]` is moved to index `i`, keeping their names.
+    /// `order` must contain every column index exactly once; use
+    /// [`select_idx`] to also drop or duplicate columns.
+    ///
+    /// # Errors
+    /// If `order` doesn't have one index per `Column` or contains an index
+    /// that is out of bounds, a `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if it contains an index more than once a
+    /// `LiquidError::InvalidArgument` is returned. In both cases this
+    /// `LocalDataFrame` is left unchanged.
+    ///
+    /// [`select_idx`]: struct.LocalDataFrame.html#method.select_idx
+    pub fn reorder_cols(&mut self, order: &[usize]) -> Result<(), LiquidError> {
+        self.schema.reorder_cols(order)?;
+        self.data = order.iter().map(|&i| self.data[i].clone()).collect();
+        Ok(())
+    }
+
+    /// Removes the `Column` named `name` from this `LocalDataFrame`, shifting
+    /// the `Column`s after it one index to the left.
+    ///
+    /// # Errors
+    /// If there is no `Column` named `name`, a `LiquidError::InvalidArgument`
+    /// is returned.
+    pub fn drop_col(&mut self, name: &str) -> Result<(), LiquidError> {
+        let col_idx = self.schema.drop_col(name)?;
+        self.data.remove(col_idx);
+        Ok(())
+    }
+
     setter!(set_string, String, String);
     setter!(set_bool, bool, Bool);
     setter!(set_float, f64, Float);
@@ -240,28 +485,33 @@ impl LocalDataFrame {
     /// Set the fields of the given `Row` struct with values from this
     /// `DataFrame` at the given `row_index`.
     ///
-    /// If the `row` does not have the same schema as this `DataFrame`, a
+    /// If `row_index` is out of bounds, a `LiquidError::RowIndexOutOfBounds`
+    /// error will be returned and the `row` is left unchanged. If the `row`
+    /// does not have the same schema as this `DataFrame`, a
     /// `LiquidError::TypeMismatch` error will be returned.
     pub fn fill_row(
         &self,
         row_index: usize,
         row: &mut Row,
     ) -> Result<(), LiquidError> {
+        if row_index >= self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
         for (c_idx, col) in self.data.iter().enumerate() {
-            match col {
-                Column::Int(c) => match c.get(row_index).unwrap() {
+            match col.as_ref() {
+                Column::Int(c) => match &c[row_index] {
                     Some(x) => row.set_int(c_idx, *x)?,
                     None => row.set_null(c_idx)?,
                 },
-                Column::Float(c) => match c.get(row_index).unwrap() {
+                Column::Float(c) => match &c[row_index] {
                     Some(x) => row.set_float(c_idx, *x)?,
                     None => row.set_null(c_idx)?,
                 },
-                Column::Bool(c) => match c.get(row_index).unwrap() {
+                Column::Bool(c) => match &c[row_index] {
                     Some(x) => row.set_bool(c_idx, *x)?,
                     None => row.set_null(c_idx)?,
                 },
-                Column::String(c) => match c.get(row_index).unwrap() {
+                Column::String(c) => match &c[row_index] {
                     Some(x) => row.set_string(c_idx, x.clone())?,
                     None => row.set_null(c_idx)?,
                 },
@@ -280,8 +530,26 @@ impl LocalDataFrame {
             return Err(LiquidError::TypeMismatch);
         }
 
+        self.push_row_unchecked(row);
+        Ok(())
+    }
+
+    /// Add a `Row` at the end of this `DataFrame` without checking that the
+    /// `row` has the same schema as this `DataFrame`. This is faster than
+    /// [`add_row`] and is meant for hot paths where the caller guarantees the
+    /// `row` is compatible, e.g. a `Row` filled from this `DataFrame`.
+    ///
+    /// The `row` must have exactly as many fields as this `DataFrame` has
+    /// columns, otherwise the columns end up with different lengths and
+    /// later reads panic. This is only checked in debug builds. Will panic
+    /// if the `row` has data that doesn't match the type of the
+    /// corresponding column.
+    ///
+    /// [`add_row`]: struct.LocalDataFrame.html#method.add_row
+    pub fn push_row_unchecked(&mut self, row: &Row) {
+        debug_assert_eq!(row.width(), self.n_cols());
         for (data, column) in row.data.iter().zip(self.data.iter_mut()) {
-            match (data, column) {
+            match (data, Arc::make_mut(column)) {
                 (Data::Int(n), Column::Int(l)) => l.push(Some(*n)),
                 (Data::Float(n), Column::Float(l)) => l.push(Some(*n)),
                 (Data::Bool(n), Column::Bool(l)) => l.push(Some(*n)),
@@ -290,10 +558,48 @@ impl LocalDataFrame {
                 (Data::Null, Column::Float(l)) => l.push(None),
                 (Data::Null, Column::Bool(l)) => l.push(None),
                 (Data::Null, Column::String(l)) => l.push(None),
-                (_, _) => unreachable!("Something is horribly wrong"),
+                (_, _) => panic!("Err incompatible row"),
             };
         }
+    }
 
+    /// Overwrites every value of the row at `row_idx` in this `DataFrame`
+    /// with the values of the given `row`, the inverse of [`fill_row`]. Null
+    /// fields of the `row` set the values to null.
+    ///
+    /// # Errors
+    /// If the `row` does not have the same schema as this `DataFrame`, a
+    /// `LiquidError::TypeMismatch` is returned, and if `row_idx` is out of
+    /// bounds a `LiquidError::RowIndexOutOfBounds` is returned. In both cases
+    /// this `DataFrame` is left unchanged.
+    ///
+    /// [`fill_row`]: struct.LocalDataFrame.html#method.fill_row
+    pub fn set_row(
+        &mut self,
+        row_idx: usize,
+        row: &Row,
+    ) -> Result<(), LiquidError> {
+        if row.schema != self.schema {
+            return Err(LiquidError::TypeMismatch);
+        }
+        if row_idx >= self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        for (data, column) in row.data.iter().zip(self.data.iter_mut()) {
+            match (data, Arc::make_mut(column)) {
+                (Data::Int(n), Column::Int(l)) => l[row_idx] = Some(*n),
+                (Data::Float(n), Column::Float(l)) => l[row_idx] = Some(*n),
+                (Data::Bool(n), Column::Bool(l)) => l[row_idx] = Some(*n),
+                (Data::String(n), Column::String(l)) => {
+                    l[row_idx] = Some(n.clone())
+                }
+                (Data::Null, Column::Int(l)) => l[row_idx] = None,
+                (Data::Null, Column::Float(l)) => l[row_idx] = None,
+                (Data::Null, Column::Bool(l)) => l[row_idx] = None,
+                (Data::Null, Column::String(l)) => l[row_idx] = None,
+                (_, _) => unreachable!("the schemas are the same"),
+            };
+        }
         Ok(())
     }
 
@@ -305,8 +611,10 @@ impl LocalDataFrame {
     /// create its own `DataFrame` internally, clone each `Row` from this
     /// `DataFrame` as it visits them, and mutate the cloned row during each
     /// visit.
+    ///
+    /// `finalize` is called on the `rower` after all rows have been visited.
     pub fn map<T: Rower>(&self, rower: T) -> T {
-        map_helper(self, rower, 0, self.n_rows())
+        map_helper(self, rower, 0, self.n_rows()).finalize()
     }
 
     /// Applies the given `rower` to every row sequentially in this `DataFrame`
@@ -320,17 +628,38 @@ impl LocalDataFrame {
     /// as it visit rows, and mutates that.
     ///
     /// `n_threads` defaults to the number of cores available on this machine.
+    ///
+    /// `finalize` is called on the joined `rower` after all the `rower`s have
+    /// been joined.
     pub fn pmap<T: Rower + Clone + Send>(&self, rower: T) -> T {
-        let rowers = vec![rower; self.n_threads];
+        self.pmap_without_finalize(rower).finalize()
+    }
+
+    /// Performs a `pmap` without calling `finalize` on the result, for when
+    /// the result will be joined with further `Rower`s, e.g. the results of
+    /// other chunks of a `DistributedDataFrame`.
+    ///
+    /// At most one thread is used per row, and if this `LocalDataFrame` has
+    /// no rows the `rower` is returned unchanged, so that a `rower` is never
+    /// joined with copies of itself that didn't visit any rows.
+    pub(crate) fn pmap_without_finalize<T: Rower + Clone + Send>(
+        &self,
+        rower: T,
+    ) -> T {
+        if self.n_rows() == 0 {
+            return rower;
+        }
+        let n_threads = cmp::min(self.n_threads, self.n_rows());
+        let rowers = vec![rower; n_threads];
         let mut new_rowers = Vec::new();
-        let step = self.n_rows() / self.n_threads;
+        let step = self.n_rows() / n_threads;
         let mut from = 0;
         thread::scope(|s| {
             let mut threads = Vec::new();
             let mut i = 0;
             for r in rowers {
                 i += 1;
-                let to = if i == self.n_threads {
+                let to = if i == n_threads {
                     self.n_rows()
                 } else {
                     from + step
@@ -420,7 +749,8 @@ impl LocalDataFrame {
         }
 
         for (col_idx, col) in other.data.into_iter().enumerate() {
-            match self.data.get_mut(col_idx).unwrap() {
+            let col = Arc::try_unwrap(col).unwrap_or_else(|col| (*col).clone());
+            match self.column_mut(col_idx).unwrap() {
                 Column::Bool(result_col) => {
                     let x: Vec<Option<bool>> = col.try_into().unwrap();
                     result_col.extend(x.into_iter())
@@ -456,230 +786,6203 @@ impl LocalDataFrame {
     pub fn n_cols(&self) -> usize {
         self.schema.width()
     }
-}
 
-fn filter_helper<T: Rower>(
-    df: &LocalDataFrame,
-    r: &mut T,
-    start: usize,
-    end: usize,
-) -> LocalDataFrame {
-    let mut df2 = LocalDataFrame::new(&df.schema);
-    let mut row = Row::new(&df.schema);
+    /// Creates a new `LocalDataFrame` of `n` randomly selected rows from this
+    /// `LocalDataFrame`, sampled without replacement. If `n` is larger than
+    /// the number of rows, every row is returned. The selected rows keep the
+    /// relative order they had in this `LocalDataFrame`.
+    ///
+    /// The same `seed` always selects the same rows, so samples are
+    /// reproducible across nodes and runs.
+    pub fn sample(&self, n: usize, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let n = cmp::min(n, self.n_rows());
+        let mut indices =
+            rand::seq::index::sample(&mut rng, self.n_rows(), n).into_vec();
+        indices.sort_unstable();
+        self.take_rows(&indices)
+    }
 
-    for i in start..end {
-        df.fill_row(i, &mut row).unwrap();
-        if r.visit(&row) {
-            df2.add_row(&row).unwrap();
-        }
+    /// Creates a new `LocalDataFrame` of a `fraction` of the rows of this
+    /// `LocalDataFrame` (rounded to the nearest row), sampled without
+    /// replacement like [`sample`]. A `fraction` outside of `[0, 1]` is
+    /// clamped to it.
+    ///
+    /// [`sample`]: struct.LocalDataFrame.html#method.sample
+    pub fn sample_frac(&self, fraction: f64, seed: u64) -> Self {
+        self.sample(sample_size(fraction, self.n_rows()), seed)
     }
 
-    df2
-}
+    /// Creates a new `LocalDataFrame` with a `fraction` of the rows of each
+    /// distinct value of the column at `col_idx` (rounded to the nearest
+    /// row), so that every value keeps its share of the rows. Nulls are
+    /// sampled as their own value. The selected rows keep the relative order
+    /// they had in this `LocalDataFrame`, and the same `seed` always selects
+    /// the same rows.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    pub fn sample_stratified(
+        &self,
+        col_idx: usize,
+        fraction: f64,
+        seed: u64,
+    ) -> Result<Self, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        // strata are visited in the order their values first appear, so the
+        // same `seed` always selects the same rows
+        let mut strata: Vec<Vec<usize>> = Vec::new();
+        let mut stratum_of: HashMap<DataKey, usize> = HashMap::new();
+        for row_idx in 0..self.n_rows() {
+            let next = strata.len();
+            let stratum =
+                *stratum_of.entry(data_key(col, row_idx)).or_insert(next);
+            if stratum == next {
+                strata.push(Vec::new());
+            }
+            strata[stratum].push(row_idx);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut indices = Vec::new();
+        for rows in strata {
+            let n = sample_size(fraction, rows.len());
+            indices.extend(
+                rand::seq::index::sample(&mut rng, rows.len(), n)
+                    .into_iter()
+                    .map(|i| rows[i]),
+            );
+        }
+        indices.sort_unstable();
 
-fn map_helper<T: Rower>(
-    df: &LocalDataFrame,
-    mut rower: T,
-    start: usize,
-    end: usize,
-) -> T {
-    let mut row = Row::new(&df.schema);
-    // NOTE: IS THIS THE ~10% slower way to do counted loop???? @tom
-    for i in start..end {
-        df.fill_row(i, &mut row).unwrap();
-        rower.visit(&row);
+        Ok(self.take_rows(&indices))
     }
-    rower
-}
 
-impl From<Column> for LocalDataFrame {
-    /// Construct a new `DataFrame` with the given `column`.
-    fn from(column: Column) -> Self {
-        LocalDataFrame::from(vec![column])
+    /// Randomly splits the rows of this `LocalDataFrame` into two new
+    /// `LocalDataFrame`s, a training set containing `frac` of the rows
+    /// (rounded to the nearest row) and a test set containing the rest. Both
+    /// sets keep the relative row order of this `LocalDataFrame`.
+    ///
+    /// The same `seed` always produces the same split.
+    pub fn train_test_split(&self, frac: f64, seed: u64) -> (Self, Self) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut indices: Vec<usize> = (0..self.n_rows()).collect();
+        indices.shuffle(&mut rng);
+        let n_train = sample_size(frac, self.n_rows());
+        let (train, test) = indices.split_at_mut(n_train);
+        train.sort_unstable();
+        test.sort_unstable();
+        (self.take_rows(train), self.take_rows(test))
     }
-}
 
-impl From<Vec<Column>> for LocalDataFrame {
-    /// Construct a new `DataFrame` with the given `columns`.
-    fn from(data: Vec<Column>) -> Self {
-        let mut schema = Schema::new();
-        for column in &data {
-            match &column {
-                Column::Bool(_) => {
-                    schema.add_column(DataType::Bool, None).unwrap()
-                }
-                Column::Int(_) => {
-                    schema.add_column(DataType::Int, None).unwrap()
-                }
-                Column::Float(_) => {
-                    schema.add_column(DataType::Float, None).unwrap()
-                }
-                Column::String(_) => {
-                    schema.add_column(DataType::String, None).unwrap()
+    /// Performs an inner join of this `LocalDataFrame` with `other`, matching
+    /// rows where the value in the `left_key` column of this `LocalDataFrame`
+    /// equals the value in the `right_key` column of `other`. Rows with a
+    /// null key never match.
+    ///
+    /// The resulting `LocalDataFrame` has all the columns of this
+    /// `LocalDataFrame` followed by all the columns of `other`. If a column
+    /// name of `other` is already in use, `_right` is appended to it until it
+    /// is unique. Rows are ordered by their position in this
+    /// `LocalDataFrame`, then by their position in `other`.
+    ///
+    /// A hash table is built on the key column of whichever `LocalDataFrame`
+    /// has fewer rows and probed with the key column of the other one.
+    ///
+    /// # Errors
+    /// - `LiquidError::ColIndexOutOfBounds` if either key column doesn't exist
+    /// - `LiquidError::TypeMismatch` if the key columns have different types
+    pub fn inner_join(
+        &self,
+        other: &Self,
+        left_key: usize,
+        right_key: usize,
+    ) -> Result<Self, LiquidError> {
+        if self.schema.col_type(left_key)?
+            != other.schema.col_type(right_key)?
+        {
+            return Err(LiquidError::TypeMismatch);
+        }
+
+        let left_col = &self.data[left_key];
+        let right_col = &other.data[right_key];
+        let build_is_left = self.n_rows() <= other.n_rows();
+        let (build_col, probe_col) = if build_is_left {
+            (left_col, right_col)
+        } else {
+            (right_col, left_col)
+        };
+        let mut table: HashMap<DataKey, Vec<usize>> = HashMap::new();
+        for i in 0..build_col.len() {
+            match data_key(build_col, i) {
+                DataKey::Null => (),
+                k => table.entry(k).or_insert_with(Vec::new).push(i),
+            }
+        }
+        let mut pairs = Vec::new();
+        for j in 0..probe_col.len() {
+            // null keys are never inserted into the table, so they can't match
+            if let Some(matches) = table.get(&data_key(probe_col, j)) {
+                for &i in matches {
+                    pairs.push(if build_is_left { (i, j) } else { (j, i) });
                 }
-            };
+            }
         }
-        let n_threads = num_cpus::get();
-        LocalDataFrame {
+        pairs.sort_unstable();
+        let left_rows: Vec<usize> = pairs.iter().map(|(l, _)| *l).collect();
+        let right_rows: Vec<usize> = pairs.iter().map(|(_, r)| *r).collect();
+
+        let schema = self.joined_schema(other)?;
+        let mut data = self.take_rows(&left_rows).data;
+        data.extend(other.take_rows(&right_rows).data);
+
+        Ok(LocalDataFrame {
             schema,
-            n_threads,
             data,
+            n_threads: self.n_threads,
             cur_row_idx: 0,
-        }
+            row_names: HashMap::new(),
+        })
     }
-}
 
-impl From<Data> for LocalDataFrame {
-    /// Construct a new `DataFrame` with the given `scalar` value.
-    fn from(scalar: Data) -> Self {
-        let c = match scalar {
-            Data::Bool(x) => Column::Bool(vec![Some(x)]),
-            Data::Int(x) => Column::Int(vec![Some(x)]),
-            Data::Float(x) => Column::Float(vec![Some(x)]),
-            Data::String(x) => Column::String(vec![Some(x)]),
-            Data::Null => panic!("Can't make a DataFrame from a null value"),
-        };
-        LocalDataFrame::from(c)
-    }
-}
+    /// Joins this `LocalDataFrame` with `other` like [`inner_join`], matching
+    /// rows where the value in the `left_key` column of this `LocalDataFrame`
+    /// equals the value in the `right_key` column of `other`. The given
+    /// `kind` determines whether rows without a match are kept, in which case
+    /// the columns from the other `LocalDataFrame` are null. Rows with a null
+    /// key never match.
+    ///
+    /// The columns are named as in [`inner_join`]. Rows are ordered by their
+    /// position in this `LocalDataFrame`, then by their position in `other`,
+    /// followed by the unmatched rows of `other` for `JoinKind::Right` and
+    /// `JoinKind::Outer`.
+    ///
+    /// # Errors
+    /// - `LiquidError::ColIndexOutOfBounds` if either key column doesn't exist
+    /// - `LiquidError::TypeMismatch` if the key columns have different types
+    ///
+    /// [`inner_join`]: struct.LocalDataFrame.html#method.inner_join
+    pub fn join(
+        &self,
+        other: &Self,
+        left_key: usize,
+        right_key: usize,
+        kind: JoinKind,
+    ) -> Result<Self, LiquidError> {
+        if kind == JoinKind::Inner {
+            return self.inner_join(other, left_key, right_key);
+        }
+        if self.schema.col_type(left_key)?
+            != other.schema.col_type(right_key)?
+        {
+            return Err(LiquidError::TypeMismatch);
+        }
 
-impl std::fmt::Display for LocalDataFrame {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for i in 0..self.n_rows() {
-            for j in 0..self.n_cols() {
-                write!(f, "<{}>", self.get(j, i).unwrap())?;
+        let left_col = &self.data[left_key];
+        let right_col = &other.data[right_key];
+        let mut table: HashMap<DataKey, Vec<usize>> = HashMap::new();
+        for j in 0..right_col.len() {
+            match data_key(right_col, j) {
+                DataKey::Null => (),
+                k => table.entry(k).or_insert_with(Vec::new).push(j),
             }
-            writeln!(f)?;
         }
-        Ok(())
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::dataframe::{Row, Rower};
-
-    #[derive(Clone)]
-    struct PosIntSummer {
-        sum: i64,
-    }
-
-    impl Rower for PosIntSummer {
-        fn visit(&mut self, r: &Row) -> bool {
-            let i = r.get(0).unwrap();
-            match i {
-                Data::Int(val) => {
-                    if *val < 0 {
-                        return false;
+        let keep_left = kind == JoinKind::Left || kind == JoinKind::Outer;
+        let mut right_matched = vec![false; right_col.len()];
+        let mut left_rows = Vec::new();
+        let mut right_rows = Vec::new();
+        for i in 0..left_col.len() {
+            match table.get(&data_key(left_col, i)) {
+                Some(matches) => {
+                    for &j in matches {
+                        left_rows.push(Some(i));
+                        right_rows.push(Some(j));
+                        right_matched[j] = true;
                     }
-                    self.sum += *val;
-                    true
                 }
-                _ => panic!(),
+                None if keep_left => {
+                    left_rows.push(Some(i));
+                    right_rows.push(None);
+                }
+                None => (),
             }
         }
-
-        fn join(mut self, other: Self) -> Self {
-            self.sum += other.sum;
-            self
+        if kind == JoinKind::Right || kind == JoinKind::Outer {
+            for (j, _) in right_matched.iter().enumerate().filter(|(_, m)| !**m)
+            {
+                left_rows.push(None);
+                right_rows.push(Some(j));
+            }
         }
-    }
 
-    fn init() -> LocalDataFrame {
-        let s = Schema::from(vec![DataType::Int]);
-        let mut r = Row::new(&s);
-        let mut df = LocalDataFrame::new(&s);
+        let schema = self.joined_schema(other)?;
+        let data = self
+            .data
+            .iter()
+            .map(|col| Arc::new(gather_rows(col, &left_rows)))
+            .chain(
+                other
+                    .data
+                    .iter()
+                    .map(|col| Arc::new(gather_rows(col, &right_rows))),
+            )
+            .collect();
 
-        for i in 0..1000 {
-            if i % 2 == 0 {
-                r.set_int(0, i * -1).unwrap();
-            } else {
-                r.set_int(0, i).unwrap();
-            }
-            df.add_row(&r).unwrap();
+        Ok(LocalDataFrame {
+            schema,
+            data,
+            n_threads: self.n_threads,
+            cur_row_idx: 0,
+            row_names: HashMap::new(),
+        })
+    }
+
+    /// Returns the number of null values in the column at `col_idx`.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    pub fn null_count(&self, col_idx: usize) -> Result<usize, LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::Bool(c)) => {
+                Ok(c.iter().filter(|x| x.is_none()).count())
+            }
+            Some(Column::Int(c)) => {
+                Ok(c.iter().filter(|x| x.is_none()).count())
+            }
+            Some(Column::Float(c)) => {
+                Ok(c.iter().filter(|x| x.is_none()).count())
+            }
+            Some(Column::String(c)) => {
+                Ok(c.iter().filter(|x| x.is_none()).count())
+            }
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Returns the total number of null values in this `LocalDataFrame`,
+    /// across all columns.
+    pub fn total_nulls(&self) -> usize {
+        (0..self.n_cols())
+            .map(|col_idx| self.null_count(col_idx).unwrap())
+            .sum()
+    }
+
+    /// Creates a new `LocalDataFrame` with only the rows of this
+    /// `LocalDataFrame` that have no null values in any column, i.e. the
+    /// complete cases. Useful for cleaning data before training a model.
+    pub fn drop_nulls(&self) -> Self {
+        let cols: Vec<usize> = (0..self.n_cols()).collect();
+        self.drop_nulls_subset(&cols).unwrap()
+    }
+
+    /// Creates a new `LocalDataFrame` with only the rows of this
+    /// `LocalDataFrame` that have no null values in any of the columns at the
+    /// given `cols`. Nulls in other columns are kept.
+    ///
+    /// # Errors
+    /// If any of the `cols` are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned.
+    pub fn drop_nulls_subset(
+        &self,
+        cols: &[usize],
+    ) -> Result<Self, LiquidError> {
+        let cols = cols
+            .iter()
+            .map(|&col_idx| self.column(col_idx))
+            .collect::<Option<Vec<&Column>>>()
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let indices: Vec<usize> = (0..self.n_rows())
+            .filter(|&row_idx| !cols.iter().any(|col| is_null(col, row_idx)))
+            .collect();
+        Ok(self.take_rows(&indices))
+    }
+
+    /// Creates a new `LocalDataFrame` where every null in the column at
+    /// `col_idx` is replaced with the given `value`, e.g. to impute missing
+    /// features with a default or the mean of the column. The other columns
+    /// are shared with this `LocalDataFrame`, so they are not copied.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if `value` is null or not of the type of the column,
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn fill_null(
+        &self,
+        col_idx: usize,
+        value: &Data,
+    ) -> Result<Self, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let filled = match (col, value) {
+            (Column::Bool(c), Data::Bool(b)) => {
+                Column::Bool(c.iter().map(|x| Some(x.unwrap_or(*b))).collect())
+            }
+            (Column::Int(c), Data::Int(i)) => {
+                Column::Int(c.iter().map(|x| Some(x.unwrap_or(*i))).collect())
+            }
+            (Column::Float(c), Data::Float(f)) => {
+                Column::Float(c.iter().map(|x| Some(x.unwrap_or(*f))).collect())
+            }
+            (Column::String(c), Data::String(s)) => Column::String(
+                c.iter()
+                    .map(|x| Some(x.as_ref().unwrap_or(s).clone()))
+                    .collect(),
+            ),
+            _ => return Err(LiquidError::TypeMismatch),
+        };
+        let mut result = self.clone();
+        result.data[col_idx] = Arc::new(filled);
+
+        Ok(result)
+    }
+
+    /// Returns a `Bool` column that is `true` in every row where the column
+    /// at `col_idx` is null and `false` everywhere else, e.g. to keep track
+    /// of which values were imputed with [`fill_null`] or to use as a
+    /// feature. The mask itself never contains nulls.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    ///
+    /// [`fill_null`]: struct.LocalDataFrame.html#method.fill_null
+    pub fn null_mask(&self, col_idx: usize) -> Result<Column, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        Ok(Column::Bool(
+            (0..self.n_rows())
+                .map(|row_idx| Some(is_null(col, row_idx)))
+                .collect(),
+        ))
+    }
+
+    /// Returns the number of unique values in the column at `col_idx`,
+    /// which is useful as a cardinality check before grouping by a column.
+    /// Nulls are not counted as a distinct value, so a column of only nulls
+    /// has `0` distinct values. `Float`s are compared by their bits, so
+    /// `0.0` and `-0.0` are counted separately.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    pub fn count_distinct(&self, col_idx: usize) -> Result<usize, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let distinct: HashSet<DataKey> = (0..self.n_rows())
+            .map(|row_idx| data_key(col, row_idx))
+            .filter(|key| *key != DataKey::Null)
+            .collect();
+        Ok(distinct.len())
+    }
+
+    /// Creates a new `LocalDataFrame` by applying the given `rower` to every
+    /// row sequentially in this `LocalDataFrame` and collecting the rows it
+    /// emits. Rows for which the `rower` returns `None` are dropped. The
+    /// `Schema` of the new `LocalDataFrame` is the `Schema` of the first
+    /// emitted row, and if no rows are emitted the new `LocalDataFrame` is
+    /// empty with no columns.
+    ///
+    /// # Errors
+    /// If the emitted rows don't all have the same `Schema`,
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn map_to_frame<T: MapRower>(
+        &self,
+        rower: &T,
+    ) -> Result<Self, LiquidError> {
+        let mut row = Row::new(&self.schema);
+        let mut new_df: Option<LocalDataFrame> = None;
+        for i in 0..self.n_rows() {
+            self.fill_row(i, &mut row)?;
+            if let Some(new_row) = rower.map_row(&row) {
+                new_df
+                    .get_or_insert_with(|| LocalDataFrame::new(&new_row.schema))
+                    .add_row(&new_row)?;
+            }
+        }
+        Ok(new_df.unwrap_or_else(|| LocalDataFrame::new(&Schema::new())))
+    }
+
+    /// Gives the row at `row_idx` the given `name`, replacing any name it
+    /// previously had. Named rows can later be found with [`get_row_idx`] and
+    /// [`get_named_row`]. Row names are not kept by operations that create a
+    /// new `LocalDataFrame` with different rows.
+    ///
+    /// # Errors
+    /// - `LiquidError::RowIndexOutOfBounds` if `row_idx` is out of bounds
+    /// - `LiquidError::NameAlreadyExists` if another row already has `name`
+    ///
+    /// [`get_row_idx`]: struct.LocalDataFrame.html#method.get_row_idx
+    /// [`get_named_row`]: struct.LocalDataFrame.html#method.get_named_row
+    pub fn set_row_name(
+        &mut self,
+        row_idx: usize,
+        name: String,
+    ) -> Result<(), LiquidError> {
+        if row_idx >= self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        match self.row_names.get(&name) {
+            Some(idx) if *idx == row_idx => return Ok(()),
+            Some(_) => return Err(LiquidError::NameAlreadyExists),
+            None => (),
+        }
+        self.row_names.retain(|_, idx| *idx != row_idx);
+        self.row_names.insert(name, row_idx);
+        Ok(())
+    }
+
+    /// Get the index of the row with the given `row_name`. Returns `Some` if
+    /// a row with the given name exists, or `None` otherwise.
+    pub fn get_row_idx(&self, row_name: &str) -> Option<usize> {
+        self.row_names.get(row_name).copied()
+    }
+
+    /// Given a row index, returns its name if it has one
+    pub fn row_name(&self, row_idx: usize) -> Option<&str> {
+        self.row_names
+            .iter()
+            .find(|(_, &idx)| idx == row_idx)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns a clone of the row with the given `row_name`, or `None` if no
+    /// row has that name.
+    pub fn get_named_row(&self, row_name: &str) -> Option<Row> {
+        let row_idx = self.get_row_idx(row_name)?;
+        let mut row = Row::new(&self.schema);
+        self.fill_row(row_idx, &mut row).ok()?;
+        Some(row)
+    }
+
+    /// Reshapes this `LocalDataFrame` from long format into wide format.
+    /// The first `Column` of the new `LocalDataFrame` has every distinct
+    /// value in the `Column` at `index_col`, and it is followed by one
+    /// `Column` for every distinct value in the `Column` at `column_col`,
+    /// named by that value. The cell for an index value and a column value is
+    /// the value in the `Column` at `value_col` of the row that has both of
+    /// them. Index and column values are ordered by where they first appear.
+    ///
+    /// Combinations of an index value and a column value that are missing
+    /// are filled with nulls. Rows where the index or column value is null
+    /// are skipped.
+    ///
+    /// # Errors
+    /// If any of the given columns are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If more than one row
+    /// has the same index value and column value, there is no single value to
+    /// put in that cell and `LiquidError::DuplicateEntry` is returned.
+    pub fn pivot(
+        &self,
+        index_col: usize,
+        column_col: usize,
+        value_col: usize,
+    ) -> Result<Self, LiquidError> {
+        let (index, columns, values) = match (
+            self.column(index_col),
+            self.column(column_col),
+            self.column(value_col),
+        ) {
+            (Some(i), Some(c), Some(v)) => (i, c, v),
+            _ => return Err(LiquidError::ColIndexOutOfBounds),
+        };
+
+        // the positions of index and column values in the pivoted frame, and
+        // the row that has the value for each cell
+        let mut index_pos: HashMap<DataKey, usize> = HashMap::new();
+        let mut index_rows = Vec::new();
+        let mut column_pos: HashMap<DataKey, usize> = HashMap::new();
+        let mut column_keys = Vec::new();
+        let mut cells: HashMap<(usize, usize), usize> = HashMap::new();
+        for row_idx in 0..self.n_rows() {
+            let index_key = data_key(index, row_idx);
+            let column_key = data_key(columns, row_idx);
+            if index_key == DataKey::Null || column_key == DataKey::Null {
+                continue;
+            }
+            let n = index_rows.len();
+            let i = *index_pos.entry(index_key).or_insert(n);
+            if i == n {
+                index_rows.push(Some(row_idx));
+            }
+            let n = column_keys.len();
+            let c = *column_pos.entry(column_key.clone()).or_insert(n);
+            if c == n {
+                column_keys.push(column_key);
+            }
+            if cells.insert((i, c), row_idx).is_some() {
+                return Err(LiquidError::DuplicateEntry);
+            }
+        }
+
+        let mut pivoted = LocalDataFrame::new(&Schema::new());
+        pivoted.n_threads = self.n_threads;
+        let index_name = self.col_name(index_col)?.map(|n| n.to_string());
+        pivoted.append_column_checked(
+            gather_rows(index, &index_rows),
+            index_name,
+        )?;
+        for (c, column_key) in column_keys.iter().enumerate() {
+            let rows: Vec<Option<usize>> = (0..index_rows.len())
+                .map(|i| cells.get(&(i, c)).copied())
+                .collect();
+            pivoted.append_column_checked(
+                gather_rows(values, &rows),
+                Some(column_key.to_string()),
+            )?;
+        }
+
+        Ok(pivoted)
+    }
+
+    /// Reshapes this `LocalDataFrame` from long format into wide format like
+    /// [`pivot`], but combines the values of all the rows that have the
+    /// same index value and column value with the given `agg` instead of
+    /// returning an error, e.g. to sum the sales of every store per month.
+    /// The cells are computed as in [`group_by`], so they have the same types
+    /// and cells without any non-null values are null.
+    ///
+    /// # Errors
+    /// If any of the given columns are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If `agg` is not
+    /// `Agg::Count` and the `value_col` is not an `Int` or `Float` column,
+    /// `LiquidError::TypeMismatch` is returned.
+    ///
+    /// [`pivot`]: struct.LocalDataFrame.html#method.pivot
+    /// [`group_by`]: struct.LocalDataFrame.html#method.group_by
+    pub fn pivot_agg(
+        &self,
+        index_col: usize,
+        column_col: usize,
+        value_col: usize,
+        agg: Agg,
+    ) -> Result<Self, LiquidError> {
+        // every index and column value has exactly one row once grouped
+        self.group_by(&[index_col, column_col], &[(value_col, agg)])?
+            .pivot(0, 1, 2)
+    }
+
+    /// Reshapes this `LocalDataFrame` from wide format into long format, the
+    /// inverse of [`pivot`]. Every row is turned into one row per column in
+    /// `value_vars`, which has the values of the `id_vars` columns of that
+    /// row, followed by a `String` column named `variable` with the name of
+    /// the value column (or its index if it has no name) and a column named
+    /// `value` with its value. The rows for the first value column come
+    /// first, then those for the second, and so on.
+    ///
+    /// # Errors
+    /// - `LiquidError::ColIndexOutOfBounds` if any of the columns are out of
+    ///   bounds
+    /// - `LiquidError::InvalidArgument` if `value_vars` is empty
+    /// - `LiquidError::TypeMismatch` if the `value_vars` columns are not all
+    ///   of the same type
+    /// - `LiquidError::NameAlreadyExists` if one of the `id_vars` columns is
+    ///   named `variable` or `value`
+    ///
+    /// [`pivot`]: struct.LocalDataFrame.html#method.pivot
+    pub fn melt(
+        &self,
+        id_vars: &[usize],
+        value_vars: &[usize],
+    ) -> Result<Self, LiquidError> {
+        let values = value_vars
+            .iter()
+            .map(|&col_idx| self.column(col_idx))
+            .collect::<Option<Vec<&Column>>>()
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let first = match values.first() {
+            Some(first) => first,
+            None => {
+                return Err(LiquidError::InvalidArgument(
+                    "there must be at least one value column".to_string(),
+                ))
+            }
+        };
+        let mut value_col = gather_rows(first, &[]);
+        for col in &values {
+            if std::mem::discriminant(*col) != std::mem::discriminant(*first) {
+                return Err(LiquidError::TypeMismatch);
+            }
+            extend_column(&mut value_col, col);
+        }
+
+        let mut melted = LocalDataFrame::new(&Schema::new());
+        melted.n_threads = self.n_threads;
+        let rows: Vec<Option<usize>> = value_vars
+            .iter()
+            .flat_map(|_| (0..self.n_rows()).map(Some))
+            .collect();
+        for &col_idx in id_vars {
+            let col = self
+                .column(col_idx)
+                .ok_or(LiquidError::ColIndexOutOfBounds)?;
+            let name = self.col_name(col_idx)?.map(String::from);
+            melted.append_column_checked(gather_rows(col, &rows), name)?;
+        }
+        let mut variables = Vec::with_capacity(rows.len());
+        for &col_idx in value_vars {
+            let name = match self.col_name(col_idx)? {
+                Some(name) => name.to_string(),
+                None => col_idx.to_string(),
+            };
+            variables.extend(vec![Some(name); self.n_rows()]);
+        }
+        melted.append_column_checked(
+            Column::String(variables),
+            Some("variable".to_string()),
+        )?;
+        melted.append_column_checked(value_col, Some("value".to_string()))?;
+
+        Ok(melted)
+    }
+
+    /// Creates a new `Column` by casting every value of the `Column` at
+    /// `col_idx` to the given `data_type`. Nulls stay null. Casts between
+    /// any two types are supported as long as every value can be converted:
+    /// - `Bool`s become `0` and `1` when cast to a number
+    /// - numbers can only be cast to a `Bool` if they are `0` or `1`
+    /// - `Float`s can only be cast to an `Int` if they have no fractional part
+    ///   and fit in an `i64`
+    /// - `String`s are parsed when cast to any other type
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned. If any value can't be cast, `LiquidError::InvalidCast` with
+    /// the `col_idx` is returned.
+    pub fn cast_column(
+        &self,
+        col_idx: usize,
+        data_type: &DataType,
+    ) -> Result<Column, LiquidError> {
+        match self.column(col_idx) {
+            Some(col) => {
+                cast(col, data_type).ok_or(LiquidError::InvalidCast(col_idx))
+            }
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Creates a new `LocalDataFrame` by casting every `Column` to the type
+    /// of the corresponding column in the `target` `Schema` with
+    /// [`cast_column`], e.g. so that data frames parsed with slightly
+    /// different inferred types can be combined. The column names of this
+    /// `LocalDataFrame` are kept.
+    ///
+    /// # Errors
+    /// If the `target` does not have the same number of columns as this
+    /// `LocalDataFrame`, `LiquidError::ColIndexOutOfBounds` is returned. If a
+    /// `Column` can't be cast, `LiquidError::InvalidCast` with the index of
+    /// the first such `Column` is returned.
+    ///
+    /// [`cast_column`]: struct.LocalDataFrame.html#method.cast_column
+    pub fn cast_to_schema(&self, target: &Schema) -> Result<Self, LiquidError> {
+        if target.width() != self.n_cols() {
+            return Err(LiquidError::ColIndexOutOfBounds);
+        }
+        let data = target
+            .schema
+            .iter()
+            .enumerate()
+            .map(|(col_idx, data_type)| {
+                if *data_type == self.schema.schema[col_idx] {
+                    // nothing to cast, so share the column
+                    Ok(self.data[col_idx].clone())
+                } else {
+                    self.cast_column(col_idx, data_type).map(Arc::new)
+                }
+            })
+            .collect::<Result<Vec<Arc<Column>>, LiquidError>>()?;
+        let schema = Schema {
+            schema: target.schema.clone(),
+            col_names: self.schema.col_names.clone(),
+        };
+
+        Ok(LocalDataFrame {
+            schema,
+            data,
+            n_threads: self.n_threads,
+            cur_row_idx: 0,
+            row_names: self.row_names.clone(),
+        })
+    }
+
+    /// Returns the Pearson correlation coefficient between the numeric
+    /// columns at `col_a` and `col_b`, computed over the rows where neither
+    /// value is null. `Int`s are converted to `f64`s.
+    ///
+    /// If either column has zero variance over those rows (e.g. it is
+    /// constant, or there are fewer than two such rows), the correlation is
+    /// undefined and `f64::NAN` is returned.
+    ///
+    /// # Errors
+    /// If either column index is out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If either column is
+    /// not an `Int` or `Float` column, `LiquidError::TypeMismatch` is returned.
+    pub fn corr(&self, col_a: usize, col_b: usize) -> Result<f64, LiquidError> {
+        let a = numeric_values(self.column(col_a))?;
+        let b = numeric_values(self.column(col_b))?;
+        let pairs: Vec<(f64, f64)> = a
+            .iter()
+            .zip(b.iter())
+            .filter_map(|pair| match pair {
+                (Some(x), Some(y)) => Some((*x, *y)),
+                _ => None,
+            })
+            .collect();
+        let n = pairs.len() as f64;
+        let mean_a = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_b = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+        for (x, y) in pairs {
+            cov += (x - mean_a) * (y - mean_b);
+            var_a += (x - mean_a) * (x - mean_a);
+            var_b += (y - mean_b) * (y - mean_b);
+        }
+        if var_a == 0.0 || var_b == 0.0 {
+            return Ok(f64::NAN);
+        }
+
+        Ok(cov / (var_a * var_b).sqrt())
+    }
+
+    /// Computes the given `agg`regation over a trailing window of `window`
+    /// rows for every row of the numeric column at `col_idx`, returning the
+    /// results as a new `Column` with one value per row. Nulls inside a
+    /// window are skipped, so a window of only nulls has a null result
+    /// (except for `Agg::Count`, which is `0`). The first `window - 1` values
+    /// are null since their window is not full.
+    ///
+    /// `Agg::Sum`, `Agg::Min`, and `Agg::Max` return a column of the same
+    /// type as the one at `col_idx`, `Agg::Mean` returns a `Float` column,
+    /// and `Agg::Count` returns an `Int` column. Sums of an `Int` column wrap
+    /// around on overflow, as in [`cumsum`].
+    ///
+    /// # Errors
+    /// If `window` is `0`, `LiquidError::InvalidArgument` is returned. If
+    /// `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned. If the column is not an `Int` or `Float` column,
+    /// `LiquidError::TypeMismatch` is returned.
+    ///
+    /// [`cumsum`]: struct.LocalDataFrame.html#method.cumsum
+    pub fn rolling(
+        &self,
+        col_idx: usize,
+        window: usize,
+        agg: Agg,
+    ) -> Result<Column, LiquidError> {
+        if window == 0 {
+            return Err(LiquidError::InvalidArgument(
+                "the window must have at least one row".to_string(),
+            ));
+        }
+        let col = self.column(col_idx);
+        let values = numeric_values(col)?;
+        match (col.unwrap(), agg) {
+            (_, Agg::Count) => {
+                Ok(Column::Int(rolling_window(&values, window, |w| {
+                    Some(w.iter().flatten().count() as i64)
+                })))
+            }
+            (_, Agg::Mean) => {
+                Ok(Column::Float(rolling_window(&values, window, |w| {
+                    let (sum, n) = w
+                        .iter()
+                        .flatten()
+                        .fold((0.0, 0), |(sum, n), x| (sum + x, n + 1));
+                    if n == 0 {
+                        None
+                    } else {
+                        Some(sum / n as f64)
+                    }
+                })))
+            }
+            (Column::Int(c), agg) => {
+                let f: fn(i64, i64) -> i64 = match agg {
+                    Agg::Sum => i64::wrapping_add,
+                    Agg::Min => cmp::min,
+                    _ => cmp::max,
+                };
+                Ok(Column::Int(rolling_window(c, window, |w| {
+                    fold_window(w, f)
+                })))
+            }
+            (_, agg) => {
+                let f: fn(f64, f64) -> f64 = match agg {
+                    Agg::Sum => |a, b| a + b,
+                    Agg::Min => f64::min,
+                    _ => f64::max,
+                };
+                Ok(Column::Float(rolling_window(&values, window, |w| {
+                    fold_window(w, f)
+                })))
+            }
+        }
+    }
+
+    /// Computes the sum of a trailing window of `window` rows for every row
+    /// of the numeric column at `col_idx`, the same as [`rolling`] with
+    /// `Agg::Sum`.
+    ///
+    /// [`rolling`]: struct.LocalDataFrame.html#method.rolling
+    pub fn rolling_sum(
+        &self,
+        col_idx: usize,
+        window: usize,
+    ) -> Result<Column, LiquidError> {
+        self.rolling(col_idx, window, Agg::Sum)
+    }
+
+    /// Computes the mean of a trailing window of `window` rows for every row
+    /// of the numeric column at `col_idx`, the same as [`rolling`] with
+    /// `Agg::Mean`.
+    ///
+    /// [`rolling`]: struct.LocalDataFrame.html#method.rolling
+    pub fn rolling_mean(
+        &self,
+        col_idx: usize,
+        window: usize,
+    ) -> Result<Column, LiquidError> {
+        self.rolling(col_idx, window, Agg::Mean)
+    }
+
+    /// Returns a `Column` of the same type as the one at `col_idx` where the
+    /// value of every row is the value `n` rows before it, e.g. to compare a
+    /// time series with its previous value. The first `n` values are null.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    pub fn lag(&self, col_idx: usize, n: usize) -> Result<Column, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let rows: Vec<Option<usize>> =
+            (0..self.n_rows()).map(|i| i.checked_sub(n)).collect();
+        Ok(gather_rows(col, &rows))
+    }
+
+    /// Returns a `Column` of the same type as the one at `col_idx` where the
+    /// value of every row is the value `n` rows after it. The last `n` values
+    /// are null.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    pub fn lead(
+        &self,
+        col_idx: usize,
+        n: usize,
+    ) -> Result<Column, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let n_rows = self.n_rows();
+        let rows: Vec<Option<usize>> = (0..n_rows)
+            .map(|i| i.checked_add(n).filter(|&j| j < n_rows))
+            .collect();
+        Ok(gather_rows(col, &rows))
+    }
+
+    /// Returns the running sum of the numeric column at `col_idx`, as a
+    /// `Column` of the same type. Nulls are skipped and stay null, so the
+    /// value of a non-null row is the sum of all the non-null values up to
+    /// and including it. Sums of an `Int` column wrap around on overflow.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if the column is not an `Int` or `Float` column,
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn cumsum(&self, col_idx: usize) -> Result<Column, LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::Int(_)) => self.cumsum_from(col_idx, &Data::Int(0)),
+            Some(Column::Float(_)) => {
+                self.cumsum_from(col_idx, &Data::Float(0.0))
+            }
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Returns the running sum of the numeric column at `col_idx` like
+    /// [`cumsum`], but starting from `offset` instead of `0`, e.g. the sum
+    /// of the chunks before this one in a `DistributedDataFrame`. `offset`
+    /// must be of the same type as the column.
+    ///
+    /// [`cumsum`]: struct.LocalDataFrame.html#method.cumsum
+    pub(crate) fn cumsum_from(
+        &self,
+        col_idx: usize,
+        offset: &Data,
+    ) -> Result<Column, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        cumulative_sum(col, offset)
+    }
+
+    /// Computes the given `window` function over the column at `col_idx`,
+    /// dispatching to [`rolling_sum`], [`rolling_mean`], [`lag`], [`lead`]
+    /// or [`cumsum`], and errors in the same way.
+    ///
+    /// [`rolling_sum`]: struct.LocalDataFrame.html#method.rolling_sum
+    /// [`rolling_mean`]: struct.LocalDataFrame.html#method.rolling_mean
+    /// [`lag`]: struct.LocalDataFrame.html#method.lag
+    /// [`lead`]: struct.LocalDataFrame.html#method.lead
+    /// [`cumsum`]: struct.LocalDataFrame.html#method.cumsum
+    pub fn window(
+        &self,
+        col_idx: usize,
+        window: Window,
+    ) -> Result<Column, LiquidError> {
+        match window {
+            Window::RollingSum(n) => self.rolling_sum(col_idx, n),
+            Window::RollingMean(n) => self.rolling_mean(col_idx, n),
+            Window::Lag(n) => self.lag(col_idx, n),
+            Window::Lead(n) => self.lead(col_idx, n),
+            Window::CumSum => self.cumsum(col_idx),
+        }
+    }
+
+    /// Returns the name of every column, or its index if it has no name.
+    fn col_labels(&self) -> Vec<String> {
+        (0..self.n_cols())
+            .map(|col_idx| match self.schema.col_name(col_idx).unwrap() {
+                Some(name) => name.to_string(),
+                None => col_idx.to_string(),
+            })
+            .collect()
+    }
+
+    /// Returns a reference to the `Column` at `col_idx`, if there is one.
+    fn column(&self, col_idx: usize) -> Option<&Column> {
+        self.data.get(col_idx).map(|col| col.as_ref())
+    }
+
+    /// Returns a mutable reference to the `Column` at `col_idx`, if there is
+    /// one. If the `Column` is shared with another `LocalDataFrame`, it is
+    /// cloned first so the mutation is only visible to this one.
+    fn column_mut(&mut self, col_idx: usize) -> Option<&mut Column> {
+        self.data.get_mut(col_idx).map(Arc::make_mut)
+    }
+
+    /// Combines the non-null values of the numeric column at `col_idx` with
+    /// `int_f` or `float_f`, depending on its type, or returns `None` if they
+    /// are all null. See `summary` for errors.
+    fn fold_numeric(
+        &self,
+        col_idx: usize,
+        int_f: fn(i64, i64) -> i64,
+        float_f: fn(f64, f64) -> f64,
+    ) -> Result<Option<Data>, LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::Int(c)) => Ok(fold_window(c, int_f).map(Data::Int)),
+            Some(Column::Float(c)) => {
+                Ok(fold_window(c, float_f).map(Data::Float))
+            }
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Splits this `LocalDataFrame` into `n` `LocalDataFrame`s by hashing the
+    /// value of each row in the column at `key_col`, so that all rows with
+    /// the same key end up in the same partition. Rows with a null key are
+    /// put in partition `0`. Rows keep their relative order within each
+    /// partition.
+    ///
+    /// Keys are hashed the same way by every copy of the same binary, so
+    /// partitioning the data on each node of a distributed system that runs
+    /// the same build with the same `n` co-locates rows with equal keys in the
+    /// same partition on every node, e.g. to prepare for a distributed join.
+    /// The hash is not guaranteed to be stable across Rust versions or
+    /// builds, so nodes running different builds may disagree.
+    ///
+    /// # Errors
+    /// If `key_col` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned. If `n` is `0`, `LiquidError::InvalidArgument` is returned.
+    pub fn hash_partition(
+        &self,
+        key_col: usize,
+        n: usize,
+    ) -> Result<Vec<Self>, LiquidError> {
+        self.hash_partition_by(&[key_col], n)
+    }
+
+    /// Like [`hash_partition`], but hashes the combination of the values of
+    /// each row in all of the `key_cols` columns, so that all rows with the
+    /// same values in those columns end up in the same partition. Rows where
+    /// every key is null are put in partition `0`. Partitioning by a single
+    /// column is the same as [`hash_partition`].
+    ///
+    /// # Errors
+    /// If any of the `key_cols` are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If `n` is `0`,
+    /// `LiquidError::InvalidArgument` is returned.
+    ///
+    /// [`hash_partition`]: struct.LocalDataFrame.html#method.hash_partition
+    pub fn hash_partition_by(
+        &self,
+        key_cols: &[usize],
+        n: usize,
+    ) -> Result<Vec<Self>, LiquidError> {
+        if n == 0 {
+            return Err(LiquidError::InvalidArgument(
+                "there must be at least one partition".to_string(),
+            ));
+        }
+        let cols = key_cols
+            .iter()
+            .map(|&col_idx| self.column(col_idx))
+            .collect::<Option<Vec<&Column>>>()
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let mut partitions = vec![Vec::new(); n];
+        for row_idx in 0..self.n_rows() {
+            let keys: Vec<DataKey> =
+                cols.iter().map(|col| data_key(col, row_idx)).collect();
+            let partition = if keys.iter().all(|key| *key == DataKey::Null) {
+                0
+            } else {
+                // the keys are hashed one after another rather than as a
+                // `Vec`, so a single key hashes the same as in older versions
+                let mut hasher = DefaultHasher::new();
+                keys.iter().for_each(|key| key.hash(&mut hasher));
+                (hasher.finish() % n as u64) as usize
+            };
+            partitions[partition].push(row_idx);
+        }
+
+        Ok(partitions
+            .iter()
+            .map(|indices| self.take_rows(indices))
+            .collect())
+    }
+
+    /// Checks that this `LocalDataFrame` has the `expected` `Schema`, e.g. to
+    /// catch schema drift between the stages of a pipeline before any rows
+    /// are added. Every column must have the same type as in `expected`, and
+    /// columns that are named in `expected` must have the same name. Columns
+    /// that are not named in `expected` may have any name.
+    ///
+    /// # Errors
+    /// If `expected` doesn't have the same number of columns,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. Otherwise, if a column
+    /// doesn't match, `LiquidError::ColumnMismatch` with the index of the
+    /// first such column is returned.
+    pub fn assert_schema(&self, expected: &Schema) -> Result<(), LiquidError> {
+        if expected.width() != self.n_cols() {
+            return Err(LiquidError::ColIndexOutOfBounds);
+        }
+        for (col_idx, data_type) in expected.schema.iter().enumerate() {
+            let name = expected.col_name(col_idx)?;
+            if *data_type != self.schema.schema[col_idx]
+                || (name.is_some() && name != self.col_name(col_idx)?)
+            {
+                return Err(LiquidError::ColumnMismatch(col_idx));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new `LocalDataFrame` that keeps only the first row for each
+    /// distinct combination of values in the columns at the given `cols`,
+    /// e.g. to deduplicate records by an id column. Rows keep their original
+    /// order. Nulls are treated as a value like any other, so two rows that
+    /// both have a null key are duplicates.
+    ///
+    /// # Errors
+    /// If any of the `cols` are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned.
+    pub fn unique_by(&self, cols: &[usize]) -> Result<Self, LiquidError> {
+        let cols = cols
+            .iter()
+            .map(|&col_idx| self.column(col_idx))
+            .collect::<Option<Vec<&Column>>>()
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let mut seen: HashSet<Vec<DataKey>> = HashSet::new();
+        let indices: Vec<usize> = (0..self.n_rows())
+            .filter(|&row_idx| {
+                seen.insert(
+                    cols.iter().map(|col| data_key(col, row_idx)).collect(),
+                )
+            })
+            .collect();
+
+        Ok(self.take_rows(&indices))
+    }
+
+    /// Creates a new `LocalDataFrame` without duplicate rows, keeping only
+    /// the first row for each distinct combination of values in the `subset`
+    /// columns, or in all columns if `subset` is `None`. Rows keep their
+    /// original order. This is the same as [`unique_by`] with all columns
+    /// when no `subset` is given.
+    ///
+    /// # Errors
+    /// If any of the `subset` columns are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned.
+    ///
+    /// [`unique_by`]: struct.LocalDataFrame.html#method.unique_by
+    pub fn unique(
+        &self,
+        subset: Option<&[usize]>,
+    ) -> Result<Self, LiquidError> {
+        match subset {
+            Some(cols) => self.unique_by(cols),
+            None => self.unique_by(&(0..self.n_cols()).collect::<Vec<_>>()),
+        }
+    }
+
+    /// Appends the rows in the given `range` of `other` to the end of this
+    /// `LocalDataFrame`. Values are copied column-to-column without creating
+    /// intermediate `Row`s, so this is much faster than calling `add_row`
+    /// for every row, e.g. when gathering or concatenating data frames.
+    ///
+    /// # Errors
+    /// If `other` doesn't have the same column types as this
+    /// `LocalDataFrame`, `LiquidError::TypeMismatch` is returned. If the
+    /// `range` is not within the rows of `other`,
+    /// `LiquidError::RowIndexOutOfBounds` is returned. In both cases this
+    /// `LocalDataFrame` is left unchanged.
+    pub fn append_rows_from(
+        &mut self,
+        other: &LocalDataFrame,
+        range: Range<usize>,
+    ) -> Result<(), LiquidError> {
+        if self.schema.schema != other.schema.schema {
+            return Err(LiquidError::TypeMismatch);
+        }
+        if range.start > range.end || range.end > other.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        for (col_idx, col) in other.data.iter().enumerate() {
+            match (self.column_mut(col_idx).unwrap(), col.as_ref()) {
+                (Column::Bool(dst), Column::Bool(src)) => {
+                    dst.extend_from_slice(&src[range.clone()])
+                }
+                (Column::Int(dst), Column::Int(src)) => {
+                    dst.extend_from_slice(&src[range.clone()])
+                }
+                (Column::Float(dst), Column::Float(src)) => {
+                    dst.extend_from_slice(&src[range.clone()])
+                }
+                (Column::String(dst), Column::String(src)) => {
+                    dst.extend_from_slice(&src[range.clone()])
+                }
+                _ => unreachable!("the schemas are the same"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counts how many times each distinct value occurs in the column at
+    /// `col_idx`. Returns a new `LocalDataFrame` with two columns: `value`,
+    /// of the same type as the column at `col_idx`, and `count`, an `Int`
+    /// column. Rows are sorted by count in descending order, and values with
+    /// the same count are in the order they first appear in the column.
+    ///
+    /// Nulls are counted too and are reported as a row with a null `value`.
+    /// `Float`s are compared by their bits, so `0.0` and `-0.0` are counted
+    /// separately.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    pub fn value_counts(&self, col_idx: usize) -> Result<Self, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        // the first row each value appears in, and how many times it appears
+        let mut positions: HashMap<DataKey, usize> = HashMap::new();
+        let mut counts: Vec<(usize, i64)> = Vec::new();
+        for row_idx in 0..self.n_rows() {
+            let pos =
+                *positions.entry(data_key(col, row_idx)).or_insert_with(|| {
+                    counts.push((row_idx, 0));
+                    counts.len() - 1
+                });
+            counts[pos].1 += 1;
+        }
+        counts.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let rows: Vec<Option<usize>> =
+            counts.iter().map(|(row_idx, _)| Some(*row_idx)).collect();
+        let mut result = LocalDataFrame::new(&Schema::new());
+        result.n_threads = self.n_threads;
+        result.append_column_checked(
+            gather_rows(col, &rows),
+            Some("value".to_string()),
+        )?;
+        result.append_column_checked(
+            Column::Int(counts.iter().map(|(_, count)| Some(*count)).collect()),
+            Some("count".to_string()),
+        )?;
+
+        Ok(result)
+    }
+
+    /// Returns an estimate of the number of bytes of memory used by each
+    /// `Column` of this `LocalDataFrame`. The estimate includes the space
+    /// reserved for every `Option` in the `Column` and, for `String` columns,
+    /// the memory allocated for every `String`. `Column`s that are shared with
+    /// other data frames are counted in full.
+    pub fn memory_usage(&self) -> Vec<usize> {
+        self.data
+            .iter()
+            .map(|col| col.as_ref().deep_size_of())
+            .collect()
+    }
+
+    /// Returns an estimate of the total number of bytes of memory used by
+    /// the `Column`s of this `LocalDataFrame`, i.e. the sum of
+    /// [`memory_usage`].
+    ///
+    /// [`memory_usage`]: struct.LocalDataFrame.html#method.memory_usage
+    pub fn total_memory_usage(&self) -> usize {
+        self.memory_usage().iter().sum()
+    }
+
+    /// Creates a new `LocalDataFrame` by applying `f` to every column of this
+    /// `LocalDataFrame` in parallel, where `f` is given the index of each
+    /// column and a reference to it. This is the column-oriented analog to
+    /// [`pmap`], and the columns are split between up to `n_threads` threads.
+    ///
+    /// The new `LocalDataFrame` has the same `Schema` and row names as this
+    /// one, so `f` must return a `Column` of the same type and length as the
+    /// one it was given.
+    ///
+    /// # Errors
+    /// If `f` returns a `Column` of a different type for any column, a
+    /// `LiquidError::ColumnMismatch` with the index of that column is
+    /// returned. If `f` returns a `Column` of a different length, a
+    /// `LiquidError::RowIndexOutOfBounds` is returned.
+    ///
+    /// [`pmap`]: struct.LocalDataFrame.html#method.pmap
+    pub fn map_columns_parallel<F>(&self, f: F) -> Result<Self, LiquidError>
+    where
+        F: Fn(usize, &Column) -> Column + Sync,
+    {
+        let n_threads = cmp::max(cmp::min(self.n_threads, self.n_cols()), 1);
+        let mut mapped = Vec::with_capacity(self.n_cols());
+        thread::scope(|s| {
+            let mut threads = Vec::new();
+            for t in 0..n_threads {
+                let f = &f;
+                threads.push(s.spawn(move |_| {
+                    (t..self.n_cols())
+                        .step_by(n_threads)
+                        .map(|col_idx| {
+                            (col_idx, f(col_idx, &self.data[col_idx]))
+                        })
+                        .collect::<Vec<_>>()
+                }));
+            }
+            for thread in threads {
+                mapped.extend(thread.join().unwrap());
+            }
+        })
+        .unwrap();
+        mapped.sort_by_key(|(col_idx, _)| *col_idx);
+
+        let mut data = Vec::with_capacity(self.n_cols());
+        for (col_idx, col) in mapped {
+            let same_type = match (&col, &self.schema.schema[col_idx]) {
+                (Column::Bool(_), DataType::Bool)
+                | (Column::Int(_), DataType::Int)
+                | (Column::Float(_), DataType::Float)
+                | (Column::String(_), DataType::String) => true,
+                _ => false,
+            };
+            if !same_type {
+                return Err(LiquidError::ColumnMismatch(col_idx));
+            }
+            if col.len() != self.n_rows() {
+                return Err(LiquidError::RowIndexOutOfBounds);
+            }
+            data.push(Arc::new(col));
+        }
+
+        Ok(LocalDataFrame {
+            schema: self.schema.clone(),
+            data,
+            n_threads: self.n_threads,
+            cur_row_idx: 0,
+            row_names: self.row_names.clone(),
+        })
+    }
+
+    /// Creates a new `LocalDataFrame` by splitting each `String` in the column
+    /// at `col_idx` on `delim`, with one row for every token. The values of
+    /// the other columns are duplicated across the rows produced from the
+    /// same row of this `LocalDataFrame`. A null cell produces a single row
+    /// that keeps the null.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, a `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if the column is not a `String` column a
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn explode(
+        &self,
+        col_idx: usize,
+        delim: char,
+    ) -> Result<Self, LiquidError> {
+        let col = match self.column(col_idx) {
+            Some(Column::String(col)) => col,
+            Some(_) => return Err(LiquidError::TypeMismatch),
+            None => return Err(LiquidError::ColIndexOutOfBounds),
+        };
+        let mut indices = Vec::with_capacity(col.len());
+        let mut tokens = Vec::with_capacity(col.len());
+        for (row_idx, cell) in col.iter().enumerate() {
+            match cell {
+                Some(s) => {
+                    for token in s.split(delim) {
+                        indices.push(row_idx);
+                        tokens.push(Some(token.to_string()));
+                    }
+                }
+                None => {
+                    indices.push(row_idx);
+                    tokens.push(None);
+                }
+            }
+        }
+        let mut result = self.take_rows(&indices);
+        result.data[col_idx] = Arc::new(Column::String(tokens));
+
+        Ok(result)
+    }
+
+    /// Concatenates the rows of `other` after the rows of this
+    /// `LocalDataFrame`, aligning columns by name. This is an outer union:
+    /// the result has every column of this `LocalDataFrame`, followed by the
+    /// columns of `other` whose names are not in this `LocalDataFrame`, and
+    /// rows from a `LocalDataFrame` that is missing a column are null in that
+    /// column. Columns without a name can't be aligned, so they are never
+    /// shared between the two.
+    ///
+    /// # Errors
+    /// If a column name is in both `LocalDataFrame`s but the columns have
+    /// different types, a `LiquidError::ColumnMismatch` with the index of the
+    /// column in this `LocalDataFrame` is returned.
+    pub fn concat_union(
+        &self,
+        other: &LocalDataFrame,
+    ) -> Result<Self, LiquidError> {
+        // pairs of (index in `self`, index in `other`) for each output column
+        let mut pairs = Vec::new();
+        let mut shared = HashSet::new();
+        for col_idx in 0..self.n_cols() {
+            let other_idx = self
+                .schema
+                .col_name(col_idx)?
+                .and_then(|name| other.schema.col_idx(name));
+            if let Some(other_idx) = other_idx {
+                if self.schema.schema[col_idx] != other.schema.schema[other_idx]
+                {
+                    return Err(LiquidError::ColumnMismatch(col_idx));
+                }
+                shared.insert(other_idx);
+            }
+            pairs.push((Some(col_idx), other_idx));
+        }
+        pairs.extend(
+            (0..other.n_cols())
+                .filter(|idx| !shared.contains(idx))
+                .map(|idx| (None, Some(idx))),
+        );
+
+        let mut result = LocalDataFrame::new(&Schema::new());
+        result.n_threads = self.n_threads;
+        for (self_idx, other_idx) in pairs {
+            let (mut col, name) = match self_idx {
+                Some(idx) => (
+                    self.data[idx].as_ref().clone(),
+                    self.schema.col_name(idx)?,
+                ),
+                None => {
+                    let idx = other_idx.unwrap();
+                    let nulls = vec![None; self.n_rows()];
+                    (
+                        gather_rows(&other.data[idx], &nulls),
+                        other.schema.col_name(idx)?,
+                    )
+                }
+            };
+            match other_idx {
+                Some(idx) => extend_column(&mut col, &other.data[idx]),
+                None => {
+                    let nulls = vec![None; other.n_rows()];
+                    let nulls = gather_rows(&col, &nulls);
+                    extend_column(&mut col, &nulls)
+                }
+            }
+            result.append_column_checked(col, name.map(str::to_string))?;
+        }
+
+        Ok(result)
+    }
+
+    /// Returns an iterator over the values of the `Int` column at `col_idx`,
+    /// which avoids wrapping each value in a `Data`. Nulls are `None`.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, a `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if the column is not an `Int` column a
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn iter_int_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<impl Iterator<Item = Option<i64>> + '_, LiquidError> {
+        self.get_int_col(col_idx).map(|c| c.iter().copied())
+    }
+
+    /// Returns an iterator over the values of the `Float` column at `col_idx`.
+    /// Nulls are `None`. Errors in the same way as [`iter_int_col`].
+    ///
+    /// [`iter_int_col`]: struct.LocalDataFrame.html#method.iter_int_col
+    pub fn iter_float_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<impl Iterator<Item = Option<f64>> + '_, LiquidError> {
+        self.get_float_col(col_idx).map(|c| c.iter().copied())
+    }
+
+    /// Returns an iterator over the values of the `Bool` column at `col_idx`.
+    /// Nulls are `None`. Errors in the same way as [`iter_int_col`].
+    ///
+    /// [`iter_int_col`]: struct.LocalDataFrame.html#method.iter_int_col
+    pub fn iter_bool_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<impl Iterator<Item = Option<bool>> + '_, LiquidError> {
+        self.get_bool_col(col_idx).map(|c| c.iter().copied())
+    }
+
+    /// Returns an iterator over the values of the `String` column at
+    /// `col_idx`, which borrows each `String` instead of cloning it into a
+    /// `Data`. Nulls are `None`. Errors in the same way as [`iter_int_col`].
+    ///
+    /// [`iter_int_col`]: struct.LocalDataFrame.html#method.iter_int_col
+    pub fn iter_string_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<impl Iterator<Item = Option<&str>> + '_, LiquidError> {
+        self.get_string_col(col_idx)
+            .map(|c| c.iter().map(Option::as_deref))
+    }
+
+    /// Returns the values of the `Int` column at `col_idx` as a slice, for
+    /// performance sensitive code that indexes or iterates over a column
+    /// directly. Nulls are `None`. Errors in the same way as
+    /// [`iter_int_col`].
+    ///
+    /// [`iter_int_col`]: struct.LocalDataFrame.html#method.iter_int_col
+    pub fn get_int_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<&[Option<i64>], LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::Int(c)) => Ok(c),
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Returns the values of the `Float` column at `col_idx` as a slice.
+    /// Nulls are `None`. Errors in the same way as [`iter_int_col`].
+    ///
+    /// [`iter_int_col`]: struct.LocalDataFrame.html#method.iter_int_col
+    pub fn get_float_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<&[Option<f64>], LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::Float(c)) => Ok(c),
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Returns the values of the `Bool` column at `col_idx` as a slice.
+    /// Nulls are `None`. Errors in the same way as [`iter_int_col`].
+    ///
+    /// [`iter_int_col`]: struct.LocalDataFrame.html#method.iter_int_col
+    pub fn get_bool_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<&[Option<bool>], LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::Bool(c)) => Ok(c),
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Returns the values of the `String` column at `col_idx` as a slice.
+    /// Nulls are `None`. Errors in the same way as [`iter_int_col`].
+    ///
+    /// [`iter_int_col`]: struct.LocalDataFrame.html#method.iter_int_col
+    pub fn get_string_col(
+        &self,
+        col_idx: usize,
+    ) -> Result<&[Option<String>], LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::String(c)) => Ok(c),
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Creates a new `LocalDataFrame` by parsing `SoR` data that is already
+    /// in memory, e.g. a chunk of a file that was received over the network,
+    /// in the same way as [`from_sor_parallel`]. If `schema` is `None` it is
+    /// inferred from the first `SOR_SCHEMA_INFERENCE_ROWS` rows: the width is
+    /// that of the longest row, and each column gets the most general of the
+    /// types `sorer`'s parser gives its values (`Bool` < `Int` < `Float` <
+    /// `String`).
+    ///
+    /// Rows with more fields than the `Schema`, and rows that are malformed
+    /// for any column, are discarded.
+    ///
+    /// # Errors
+    /// If `bytes` is not valid UTF-8, a `LiquidError::InvalidArgument` is
+    /// returned.
+    ///
+    /// [`from_sor_parallel`]: struct.LocalDataFrame.html#method.from_sor_parallel
+    pub fn from_sor_bytes(
+        bytes: &[u8],
+        schema: Option<Schema>,
+    ) -> Result<Self, LiquidError> {
+        if std::str::from_utf8(bytes).is_err() {
+            return Err(LiquidError::InvalidArgument(
+                "SoR data is not valid UTF-8".to_string(),
+            ));
+        }
+        let lines = sor_lines(bytes, false, true);
+        let schema = schema.unwrap_or_else(|| infer_sor_schema(&lines));
+
+        LocalDataFrame::from_sor_lines(schema, &lines, num_cpus::get())
+    }
+
+    /// Creates a new `LocalDataFrame` with the given `schema` by parsing the
+    /// `lines` of a `SoR` file with `sorer`'s parser, with the lines split
+    /// into `n_threads` blocks that are parsed in parallel. Rows that are
+    /// malformed for the `schema` are discarded, and a warning with the
+    /// number of discarded rows is logged.
+    fn from_sor_lines(
+        schema: Schema,
+        lines: &[&[u8]],
+        n_threads: usize,
+    ) -> Result<Self, LiquidError> {
+        let block_size = cmp::max(1, (lines.len() + n_threads - 1) / n_threads);
+        let mut blocks = Vec::new();
+        thread::scope(|s| {
+            let threads: Vec<_> = lines
+                .chunks(block_size)
+                .map(|block| {
+                    let schema = &schema;
+                    s.spawn(move |_| parse_sor_lines(&schema.schema, block))
+                })
+                .collect();
+            for thread in threads {
+                blocks.push(thread.join().unwrap());
+            }
+        })
+        .unwrap();
+
+        let mut df = LocalDataFrame::new(&schema);
+        df.n_threads = n_threads;
+        let (mut wider, mut malformed) = (0, 0);
+        for block in blocks {
+            let (columns, block_wider, block_malformed) = block?;
+            for (col_idx, col) in columns.iter().enumerate() {
+                extend_column(df.column_mut(col_idx).unwrap(), col);
+            }
+            wider += block_wider;
+            malformed += block_malformed;
+        }
+        if wider > 0 {
+            warn!(
+                "Discarded {} SoR rows with more than {} fields",
+                wider,
+                schema.width()
+            );
+        }
+        if malformed > 0 {
+            warn!("Discarded {} malformed SoR rows", malformed);
+        }
+
+        Ok(df)
+    }
+
+    /// Returns every row of this `LocalDataFrame` as an owned `Row`, in order,
+    /// where each `Row` has a clone of this `LocalDataFrame`'s `Schema`. This
+    /// is convenient for passing the rows of a small `LocalDataFrame` to
+    /// row-oriented code, and the `Row`s can be collected back into a
+    /// `LocalDataFrame` with `FromIterator`.
+    ///
+    /// Every value and the `Schema` are cloned for each row, so this is
+    /// expensive for large `LocalDataFrame`s. Prefer reusing a single `Row`
+    /// with [`fill_row`], or visiting rows with a [`Rower`] using [`map`] or
+    /// [`pmap`].
+    ///
+    /// [`fill_row`]: struct.LocalDataFrame.html#method.fill_row
+    /// [`map`]: struct.LocalDataFrame.html#method.map
+    /// [`pmap`]: struct.LocalDataFrame.html#method.pmap
+    /// [`Rower`]: trait.Rower.html
+    pub fn to_rows(&self) -> Vec<Row> {
+        self.iter_rows().collect()
+    }
+
+    /// Returns a copy of the numeric column at `col_idx` where values below
+    /// `lo` are replaced with `lo` and values above `hi` are replaced with
+    /// `hi`, e.g. to limit the effect of outliers. Nulls stay null. An `Int`
+    /// column stays an `Int` column and is clipped to the bounds rounded to
+    /// the nearest integers.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, a `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if the column is not an `Int` or `Float` column a
+    /// `LiquidError::TypeMismatch` is returned. If `lo` is greater than `hi`
+    /// or either bound is `NaN`, a `LiquidError::InvalidArgument` is
+    /// returned.
+    pub fn clip(
+        &self,
+        col_idx: usize,
+        lo: f64,
+        hi: f64,
+    ) -> Result<Column, LiquidError> {
+        if lo.is_nan() || hi.is_nan() || lo > hi {
+            return Err(LiquidError::InvalidArgument(format!(
+                "clip bounds {} and {} are not ordered",
+                lo, hi
+            )));
+        }
+        match self.column(col_idx) {
+            Some(Column::Int(c)) => {
+                let (lo, hi) = (lo.round() as i64, hi.round() as i64);
+                Ok(Column::Int(
+                    c.iter().map(|x| x.map(|x| x.max(lo).min(hi))).collect(),
+                ))
+            }
+            Some(Column::Float(c)) => Ok(Column::Float(
+                c.iter().map(|x| x.map(|x| x.max(lo).min(hi))).collect(),
+            )),
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Creates a new `LocalDataFrame` with the same `Schema` as this one that
+    /// contains only the rows for which the given `predicate` returns true,
+    /// in their original order. This is a lighter-weight alternative to
+    /// [`filter`] for one-off predicates that don't need a [`Rower`].
+    ///
+    /// [`filter`]: struct.LocalDataFrame.html#method.filter
+    /// [`Rower`]: trait.Rower.html
+    pub fn filter_rows<F: FnMut(&Row) -> bool>(
+        &self,
+        mut predicate: F,
+    ) -> Self {
+        let mut row = Row::new(&self.schema);
+        let indices: Vec<usize> = (0..self.n_rows())
+            .filter(|&row_idx| {
+                self.fill_row(row_idx, &mut row).unwrap();
+                predicate(&row)
+            })
+            .collect();
+        self.take_rows(&indices)
+    }
+
+    /// Returns the `Schema` of this `LocalDataFrame` joined with `other`: all
+    /// the columns of this `LocalDataFrame` followed by all the columns of
+    /// `other`, where `_right` is appended to the names of columns of `other`
+    /// until they are unique.
+    fn joined_schema(&self, other: &Self) -> Result<Schema, LiquidError> {
+        let mut schema = Schema::new();
+        for (idx, data_type) in self.schema.schema.iter().enumerate() {
+            let name = self.col_name(idx)?.map(String::from);
+            schema.add_column(data_type.clone(), name)?;
+        }
+        for (idx, data_type) in other.schema.schema.iter().enumerate() {
+            let name = other.col_name(idx)?.map(|name| {
+                let mut name = name.to_string();
+                while schema.col_idx(&name).is_some() {
+                    name.push_str("_right");
+                }
+                name
+            });
+            schema.add_column(data_type.clone(), name)?;
+        }
+        Ok(schema)
+    }
+
+    /// Groups the rows of this `LocalDataFrame` by the values in the `keys`
+    /// columns and computes the given `aggs` over every group, where each
+    /// aggregation is a column index and the `Agg` to compute over that
+    /// column. Null keys are grouped together, and null values are skipped
+    /// by the aggregations.
+    ///
+    /// Returns a `LocalDataFrame` with one row per group, in the order the
+    /// groups first appear. It has the `keys` columns followed by one column
+    /// per aggregation, named `<column name>_<aggregation>`, e.g. `price_sum`,
+    /// if the aggregated column is named. `Agg::Sum`, `Agg::Min`, and
+    /// `Agg::Max` return a column of the same type as the aggregated one,
+    /// `Agg::Mean` returns a `Float` column, and `Agg::Count` returns an
+    /// `Int` column. Sums of an `Int` column wrap around on overflow, as in
+    /// [`cumsum`].
+    ///
+    /// The rows are visited in parallel with [`pmap`].
+    ///
+    /// # Errors
+    /// If any of the columns are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If any aggregation
+    /// other than `Agg::Count` is of a column that is not an `Int` or `Float`
+    /// column, `LiquidError::TypeMismatch` is returned. If two aggregations
+    /// would have the same name, `LiquidError::NameAlreadyExists` is
+    /// returned.
+    ///
+    /// [`cumsum`]: struct.LocalDataFrame.html#method.cumsum
+    /// [`pmap`]: struct.LocalDataFrame.html#method.pmap
+    pub fn group_by(
+        &self,
+        keys: &[usize],
+        aggs: &[(usize, Agg)],
+    ) -> Result<Self, LiquidError> {
+        let rower = GroupByRower::new(&self.schema, keys, aggs)?;
+        self.pmap(rower).into_frame(&self.schema)
+    }
+
+    /// Creates a new `LocalDataFrame` with only the columns at the given
+    /// `col_idxs`, in the order given, keeping their names. The columns are
+    /// shared with this `LocalDataFrame` rather than copied, so selecting
+    /// columns is cheap, e.g. so that a [`Rower`] only visits the columns it
+    /// needs.
+    ///
+    /// # Errors
+    /// If any of the `col_idxs` are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If a named column is
+    /// selected more than once, `LiquidError::NameAlreadyExists` is returned.
+    ///
+    /// [`Rower`]: trait.Rower.html
+    pub fn select_idx(&self, col_idxs: &[usize]) -> Result<Self, LiquidError> {
+        let mut schema = Schema::new();
+        let mut data = Vec::with_capacity(col_idxs.len());
+        for &col_idx in col_idxs {
+            let data_type = self.schema.col_type(col_idx)?.clone();
+            let name = self.col_name(col_idx)?.map(String::from);
+            schema.add_column(data_type, name)?;
+            data.push(self.data[col_idx].clone());
+        }
+
+        Ok(LocalDataFrame {
+            schema,
+            data,
+            n_threads: self.n_threads,
+            cur_row_idx: self.cur_row_idx,
+            row_names: self.row_names.clone(),
+        })
+    }
+
+    /// Creates a new `LocalDataFrame` with only the columns with the given
+    /// `names`, in the order given. See [`select_idx`] for details.
+    ///
+    /// # Errors
+    /// If there is no column with one of the `names`,
+    /// `LiquidError::InvalidArgument` is returned. If a column is selected
+    /// more than once, `LiquidError::NameAlreadyExists` is returned.
+    ///
+    /// [`select_idx`]: struct.LocalDataFrame.html#method.select_idx
+    pub fn select(&self, names: &[&str]) -> Result<Self, LiquidError> {
+        let col_idxs = names
+            .iter()
+            .map(|name| {
+                self.get_col_idx(name).ok_or_else(|| {
+                    LiquidError::InvalidArgument(format!(
+                        "there is no column named {}",
+                        name
+                    ))
+                })
+            })
+            .collect::<Result<Vec<usize>, LiquidError>>()?;
+        self.select_idx(&col_idxs)
+    }
+
+    /// Creates a [`DataFrameSlice`] view of the rows from `start` up to, but
+    /// not including, `end` of this `LocalDataFrame`. The view shares the
+    /// columns of this `LocalDataFrame` instead of copying them, so it is
+    /// cheap to create, e.g. for processing windows of rows or for splitting
+    /// a chunk before redistributing it.
+    ///
+    /// # Errors
+    /// If `start` is greater than `end` or `end` is greater than the number
+    /// of rows, `LiquidError::RowIndexOutOfBounds` is returned.
+    ///
+    /// [`DataFrameSlice`]: struct.DataFrameSlice.html
+    pub fn slice(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<DataFrameSlice, LiquidError> {
+        if start > end || end > self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        Ok(DataFrameSlice::new(self.clone(), start..end))
+    }
+
+    /// Creates a new `LocalDataFrame` with the rows of `other` appended to
+    /// the rows of this `LocalDataFrame`. The column names of the result are
+    /// those of this `LocalDataFrame`. Unlike [`combine`], neither
+    /// `LocalDataFrame` is consumed.
+    ///
+    /// # Errors
+    /// If `other` doesn't have the same column types as this
+    /// `LocalDataFrame`, `LiquidError::TypeMismatch` is returned.
+    ///
+    /// [`combine`]: struct.LocalDataFrame.html#method.combine
+    pub fn concat(&self, other: &Self) -> Result<Self, LiquidError> {
+        let mut df = self.clone();
+        df.append_rows_from(other, 0..other.n_rows())?;
+        Ok(df)
+    }
+
+    /// Creates a new `LocalDataFrame` with the columns of this
+    /// `LocalDataFrame` followed by the columns of `other`. The columns are
+    /// shared with both `LocalDataFrame`s rather than copied, and the row
+    /// names are those of this `LocalDataFrame`.
+    ///
+    /// # Errors
+    /// If `other` has a different number of rows,
+    /// `LiquidError::RowIndexOutOfBounds` is returned, unless either
+    /// `LocalDataFrame` has no columns. If a column name of `other` is
+    /// already used by this `LocalDataFrame`,
+    /// `LiquidError::NameAlreadyExists` is returned.
+    pub fn hstack(&self, other: &Self) -> Result<Self, LiquidError> {
+        if self.n_cols() > 0
+            && other.n_cols() > 0
+            && self.n_rows() != other.n_rows()
+        {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        let mut df = self.clone();
+        for (col_idx, col) in other.data.iter().enumerate() {
+            let data_type = other.schema.col_type(col_idx)?.clone();
+            let name = other.col_name(col_idx)?.map(String::from);
+            df.schema.add_column(data_type, name)?;
+            df.data.push(col.clone());
+        }
+        Ok(df)
+    }
+
+    /// Applies the given arithmetic `op` element-wise to the numeric columns
+    /// at `lhs` and `rhs`, returning the results as a new `Column`. Two `Int`
+    /// columns give an `Int` column, where overflow and division by zero
+    /// give a null. Otherwise the result is a `Float` column. Nulls stay
+    /// null.
+    ///
+    /// # Errors
+    /// If either column is out of bounds, `LiquidError::ColIndexOutOfBounds`
+    /// is returned, and if either column is not an `Int` or `Float` column,
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn arith(
+        &self,
+        lhs: usize,
+        rhs: usize,
+        op: ArithOp,
+    ) -> Result<Column, LiquidError> {
+        let lhs = self.column(lhs).ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let rhs = self.column(rhs).ok_or(LiquidError::ColIndexOutOfBounds)?;
+        arith_columns(lhs, rhs, op)
+    }
+
+    /// Applies the given arithmetic `op` element-wise to the numeric column
+    /// at `col_idx` and the given `scalar`, which is the right hand side of
+    /// every operation. The type of the result is as in [`arith`].
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if the column is not an `Int` or `Float` column or the
+    /// `scalar` is not a `Data::Int` or `Data::Float`,
+    /// `LiquidError::TypeMismatch` is returned.
+    ///
+    /// [`arith`]: struct.LocalDataFrame.html#method.arith
+    pub fn arith_scalar(
+        &self,
+        col_idx: usize,
+        op: ArithOp,
+        scalar: &Data,
+    ) -> Result<Column, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        arith_scalar_column(col, scalar, op)
+    }
+
+    /// Compares the columns at `lhs` and `rhs` element-wise with the given
+    /// `op`, returning the results as a new `Bool` column. `Int` and `Float`
+    /// columns can be compared with each other, while `Bool` and `String`
+    /// columns can only be compared with columns of the same type. A
+    /// comparison with a null or `NaN` is null.
+    ///
+    /// # Errors
+    /// If either column is out of bounds, `LiquidError::ColIndexOutOfBounds`
+    /// is returned, and if the columns can't be compared,
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn compare(
+        &self,
+        lhs: usize,
+        rhs: usize,
+        op: CmpOp,
+    ) -> Result<Column, LiquidError> {
+        let lhs = self.column(lhs).ok_or(LiquidError::ColIndexOutOfBounds)?;
+        let rhs = self.column(rhs).ok_or(LiquidError::ColIndexOutOfBounds)?;
+        compare_columns(lhs, rhs, op)
+    }
+
+    /// Compares the column at `col_idx` element-wise with the given `scalar`
+    /// using the given `op`, where the `scalar` is the right hand side of
+    /// every comparison, e.g. to build a mask of the rows with a value
+    /// greater than `10`. See [`compare`] for which types can be compared.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if the column can't be compared with the `scalar`, or
+    /// the `scalar` is `Data::Null`, `LiquidError::TypeMismatch` is returned.
+    ///
+    /// [`compare`]: struct.LocalDataFrame.html#method.compare
+    pub fn compare_scalar(
+        &self,
+        col_idx: usize,
+        op: CmpOp,
+        scalar: &Data,
+    ) -> Result<Column, LiquidError> {
+        let col = self
+            .column(col_idx)
+            .ok_or(LiquidError::ColIndexOutOfBounds)?;
+        compare_scalar_column(col, scalar, op)
+    }
+
+    /// Computes the [`ColumnSummary`] of the numeric column at `col_idx`:
+    /// the count, sum, mean, min, max, sample variance and sample standard
+    /// deviation of its non-null values. The sum, min and max have the type
+    /// of the column, see [`sum`], [`min`] and [`max`].
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if the column is not an `Int` or `Float` column,
+    /// `LiquidError::TypeMismatch` is returned.
+    ///
+    /// [`ColumnSummary`]: struct.ColumnSummary.html
+    /// [`sum`]: struct.LocalDataFrame.html#method.sum
+    /// [`min`]: struct.LocalDataFrame.html#method.min
+    /// [`max`]: struct.LocalDataFrame.html#method.max
+    pub fn summary(
+        &self,
+        col_idx: usize,
+    ) -> Result<ColumnSummary, LiquidError> {
+        let values = numeric_values(self.column(col_idx))?;
+        // Welford's algorithm, which is numerically stable
+        let mut count = 0;
+        let mut mean = 0.0;
+        let mut m2 = 0.0;
+        for &x in values.iter().flatten() {
+            count += 1;
+            let delta = x - mean;
+            mean += delta / count as f64;
+            m2 += delta * (x - mean);
+        }
+        let var = if count > 1 {
+            Some(m2 / (count - 1) as f64)
+        } else {
+            None
+        };
+
+        Ok(ColumnSummary {
+            count,
+            sum: self.sum(col_idx)?,
+            mean: if count > 0 { Some(mean) } else { None },
+            min: self.min(col_idx)?,
+            max: self.max(col_idx)?,
+            var,
+            std: var.map(f64::sqrt),
+        })
+    }
+
+    /// Returns the sum of the non-null values of the numeric column at
+    /// `col_idx`, or `0` if there are none, as a `Data::Int` for an `Int`
+    /// column and a `Data::Float` for a `Float` column. Sums of an `Int`
+    /// column wrap around on overflow, as in [`cumsum`]. See [`summary`] for
+    /// errors.
+    ///
+    /// [`cumsum`]: struct.LocalDataFrame.html#method.cumsum
+    /// [`summary`]: struct.LocalDataFrame.html#method.summary
+    pub fn sum(&self, col_idx: usize) -> Result<Data, LiquidError> {
+        match self.column(col_idx) {
+            Some(Column::Int(c)) => Ok(Data::Int(
+                c.iter().flatten().fold(0, |sum, &x| sum.wrapping_add(x)),
+            )),
+            Some(Column::Float(c)) => Ok(Data::Float(c.iter().flatten().sum())),
+            Some(_) => Err(LiquidError::TypeMismatch),
+            None => Err(LiquidError::ColIndexOutOfBounds),
+        }
+    }
+
+    /// Returns the mean of the non-null values of the numeric column at
+    /// `col_idx`, or `None` if there are none. See [`summary`] for errors.
+    ///
+    /// [`summary`]: struct.LocalDataFrame.html#method.summary
+    pub fn mean(&self, col_idx: usize) -> Result<Option<f64>, LiquidError> {
+        Ok(self.summary(col_idx)?.mean)
+    }
+
+    /// Returns the smallest non-null value of the numeric column at
+    /// `col_idx`, or `None` if there are none, as a `Data` of the type of the
+    /// column. See [`summary`] for errors.
+    ///
+    /// [`summary`]: struct.LocalDataFrame.html#method.summary
+    pub fn min(&self, col_idx: usize) -> Result<Option<Data>, LiquidError> {
+        self.fold_numeric(col_idx, cmp::min, f64::min)
+    }
+
+    /// Returns the largest non-null value of the numeric column at
+    /// `col_idx`, or `None` if there are none, as a `Data` of the type of the
+    /// column. See [`summary`] for errors.
+    ///
+    /// [`summary`]: struct.LocalDataFrame.html#method.summary
+    pub fn max(&self, col_idx: usize) -> Result<Option<Data>, LiquidError> {
+        self.fold_numeric(col_idx, cmp::max, f64::max)
+    }
+
+    /// Returns the sample variance of the non-null values of the numeric
+    /// column at `col_idx`, or `None` if there are fewer than two. See
+    /// [`summary`] for errors.
+    ///
+    /// [`summary`]: struct.LocalDataFrame.html#method.summary
+    pub fn var(&self, col_idx: usize) -> Result<Option<f64>, LiquidError> {
+        Ok(self.summary(col_idx)?.var)
+    }
+
+    /// Returns the sample standard deviation of the non-null values of the
+    /// numeric column at `col_idx`, or `None` if there are fewer than two.
+    /// See [`summary`] for errors.
+    ///
+    /// [`summary`]: struct.LocalDataFrame.html#method.summary
+    pub fn std(&self, col_idx: usize) -> Result<Option<f64>, LiquidError> {
+        Ok(self.summary(col_idx)?.std)
+    }
+
+    /// Returns the number of non-null values in the column at `col_idx`,
+    /// which may be of any type.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned.
+    pub fn count(&self, col_idx: usize) -> Result<usize, LiquidError> {
+        Ok(self.n_rows() - self.null_count(col_idx)?)
+    }
+
+    /// Computes summary statistics of every column of this `LocalDataFrame`
+    /// and returns them as a new `LocalDataFrame`. The result has one `Float`
+    /// column per column of this `LocalDataFrame`, with the same name, and
+    /// one row per statistic. The rows are named, so a statistic can be found
+    /// with [`get_row_idx`], and are, in order:
+    ///
+    /// - `count`: the number of non-null values
+    /// - `null_count`: the number of nulls
+    /// - `distinct`: the number of distinct non-null values of `Bool` and
+    ///   `String` columns, null for numeric columns
+    /// - `mean`, `std`: the mean and sample standard deviation of the non-null
+    ///   values of numeric columns
+    /// - `min`, `25%`, `50%`, `75%`, `max`: the minimum, quartiles and maximum
+    ///   of the non-null values of numeric columns
+    ///
+    /// A statistic is null if it doesn't apply to the type of a column or if
+    /// the column doesn't have enough non-null values to compute it.
+    /// Quartiles are computed with the nearest-rank method, and are exact for
+    /// columns with fewer than `8192` non-null values and approximate beyond
+    /// that.
+    ///
+    /// [`get_row_idx`]: struct.LocalDataFrame.html#method.get_row_idx
+    pub fn describe(&self) -> Self {
+        let rower = self.pmap(DescribeRower::new(&self.schema));
+        let mut described = rower.into_frame(&self.schema);
+        described.n_threads = self.n_threads;
+        described
+    }
+
+    /// Creates a new `LocalDataFrame` where the column at `col_idx` is
+    /// replaced by the result of applying `f` to each of its values, e.g. to
+    /// scale or bucket a column without writing a `Rower`. The new column
+    /// keeps the name of the old one and has the type `data_type`, which may
+    /// differ from the type of the old column. `f` may return `Data::Null`
+    /// for any value. The other columns are shared with this
+    /// `LocalDataFrame`, so they are not copied.
+    ///
+    /// # Errors
+    /// If `col_idx` is out of bounds, `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if `f` returns a value that is not of type `data_type`,
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn map_column<F>(
+        &self,
+        col_idx: usize,
+        data_type: &DataType,
+        f: F,
+    ) -> Result<Self, LiquidError>
+    where
+        F: Fn(&Data) -> Data,
+    {
+        if col_idx >= self.n_cols() {
+            return Err(LiquidError::ColIndexOutOfBounds);
+        }
+        let values = (0..self.n_rows())
+            .map(|row_idx| Ok(f(&self.get(col_idx, row_idx)?)));
+        let col = column_from_data(data_type, values)?;
+        let mut mapped = self.clone();
+        mapped.schema.schema[col_idx] = data_type.clone();
+        mapped.data[col_idx] = Arc::new(col);
+
+        Ok(mapped)
+    }
+
+    /// Creates a new `LocalDataFrame` with a column named `name` of type
+    /// `data_type`, whose value in each row is the result of applying `f` to
+    /// that row, e.g. to derive a feature from several columns. If a column
+    /// named `name` already exists it is replaced, otherwise the new column
+    /// is added after the last column. `f` may return `Data::Null` for any
+    /// row.
+    ///
+    /// # Errors
+    /// If `f` returns a value that is not of type `data_type`,
+    /// `LiquidError::TypeMismatch` is returned.
+    pub fn with_column<F>(
+        &self,
+        name: &str,
+        data_type: &DataType,
+        f: F,
+    ) -> Result<Self, LiquidError>
+    where
+        F: Fn(&Row) -> Data,
+    {
+        let mut row = Row::new(&self.schema);
+        let values = (0..self.n_rows()).map(|row_idx| {
+            self.fill_row(row_idx, &mut row)?;
+            Ok(f(&row))
+        });
+        let col = column_from_data(data_type, values)?;
+        let mut result = self.clone();
+        match self.get_col_idx(name) {
+            Some(col_idx) => {
+                result.schema.schema[col_idx] = data_type.clone();
+                result.data[col_idx] = Arc::new(col);
+            }
+            None => result.add_column(col, Some(name.to_string()))?,
+        }
+
+        Ok(result)
+    }
+
+    /// Returns whether this `LocalDataFrame` and `other` have the same
+    /// `Schema`, including column names, and the same values in every cell,
+    /// allowing `Float` values to differ by up to `float_tolerance`. Nulls
+    /// are only equal to nulls, and two `NaN`s are considered equal.
+    ///
+    /// Unlike `==`, this tolerates the rounding differences that come from
+    /// e.g. summing floats in a different order on different nodes, so it is
+    /// meant for tests and for verifying the results of distributed jobs.
+    pub fn approx_eq(&self, other: &Self, float_tolerance: f64) -> bool {
+        if self.schema != other.schema || self.n_rows() != other.n_rows() {
+            return false;
+        }
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .all(|(left, right)| match (left.as_ref(), right.as_ref()) {
+                (Column::Float(l), Column::Float(r)) => {
+                    l.iter().zip(r.iter()).all(|pair| match pair {
+                        (Some(x), Some(y)) => {
+                            (x.is_nan() && y.is_nan())
+                                || (x - y).abs() <= float_tolerance
+                        }
+                        (None, None) => true,
+                        _ => false,
+                    })
+                }
+                (left, right) => left == right,
+            })
+    }
+
+    /// Creates a new `LocalDataFrame` with the first `n` rows of this
+    /// `LocalDataFrame`, or all of them if there are fewer than `n`.
+    pub fn head(&self, n: usize) -> Self {
+        let indices: Vec<usize> = (0..cmp::min(n, self.n_rows())).collect();
+        self.take_rows(&indices)
+    }
+
+    /// Creates a new `LocalDataFrame` with the last `n` rows of this
+    /// `LocalDataFrame`, or all of them if there are fewer than `n`.
+    pub fn tail(&self, n: usize) -> Self {
+        let start = self.n_rows() - cmp::min(n, self.n_rows());
+        let indices: Vec<usize> = (start..self.n_rows()).collect();
+        self.take_rows(&indices)
+    }
+
+    /// Formats this `LocalDataFrame` as an aligned table with a header of
+    /// the column names (or indices, for unnamed columns) and types, and the
+    /// index of each row on the left. Nulls are printed as `null`.
+    ///
+    /// If there are more than `max_rows` rows, only the first and last
+    /// `max_rows / 2` rows are printed, separated by a row of `...`, and
+    /// values wider than `max_col_width` characters are cut short and end in
+    /// `...`. The number of rows and columns is printed below the table.
+    pub fn to_table(&self, max_rows: usize, max_col_width: usize) -> String {
+        let n_rows = self.n_rows();
+        let truncated = n_rows > max_rows;
+        let shown: Vec<usize> = if truncated {
+            let n_head = (max_rows + 1) / 2;
+            (0..n_head)
+                .chain(n_rows - (max_rows - n_head)..n_rows)
+                .collect()
+        } else {
+            (0..n_rows).collect()
+        };
+        let fit = |s: String| {
+            if s.chars().count() > max_col_width {
+                let mut cut: String =
+                    s.chars().take(max_col_width.saturating_sub(3)).collect();
+                cut.push_str("...");
+                cut
+            } else {
+                s
+            }
+        };
+
+        // the cells of each column, including the index column, with the
+        // name and type as the first two cells
+        let mut columns = vec![(
+            true,
+            vec![String::new(), String::new()]
+                .into_iter()
+                .chain(shown.iter().map(|i| i.to_string()))
+                .collect::<Vec<_>>(),
+        )];
+        for (col_idx, col) in self.data.iter().enumerate() {
+            let name = match self.schema.col_name(col_idx).unwrap() {
+                Some(name) => name.to_string(),
+                None => col_idx.to_string(),
+            };
+            let (type_name, numeric) = match col.as_ref() {
+                Column::Bool(_) => ("bool", false),
+                Column::Int(_) => ("int", true),
+                Column::Float(_) => ("float", true),
+                Column::String(_) => ("string", false),
+            };
+            let mut cells = vec![fit(name), format!("<{}>", type_name)];
+            cells.extend(shown.iter().map(|&row_idx| {
+                match self.get(col_idx, row_idx).unwrap() {
+                    Data::Null => "null".to_string(),
+                    data => fit(data.to_string()),
+                }
+            }));
+            columns.push((numeric, cells));
+        }
+        let widths: Vec<usize> = columns
+            .iter()
+            .map(|(_, cells)| {
+                cells.iter().map(|c| c.chars().count()).max().unwrap_or(0)
+            })
+            .collect();
+
+        let format_line = |cells: Vec<(bool, &str)>| {
+            let line: Vec<String> = cells
+                .iter()
+                .zip(widths.iter())
+                .map(|((right, cell), &width)| {
+                    if *right {
+                        format!("{:>width$}", cell, width = width)
+                    } else {
+                        format!("{:<width$}", cell, width = width)
+                    }
+                })
+                .collect();
+            format!("{}\n", line.join(" | ").trim_end())
+        };
+        let mut table = String::new();
+        for line_idx in 0..2 + shown.len() {
+            if truncated && line_idx == 2 + (max_rows + 1) / 2 {
+                let gap = columns.iter().map(|_| (false, "...")).collect();
+                table.push_str(&format_line(gap));
+            }
+            table.push_str(&format_line(
+                columns
+                    .iter()
+                    .map(|(numeric, cells)| {
+                        (*numeric && line_idx >= 2, cells[line_idx].as_str())
+                    })
+                    .collect(),
+            ));
+            if line_idx == 1 {
+                let rule: Vec<String> =
+                    widths.iter().map(|&width| "-".repeat(width)).collect();
+                table.push_str(&rule.join("-+-"));
+                table.push('\n');
+            }
+        }
+        table.push_str(&format!(
+            "[{} rows x {} columns]",
+            n_rows,
+            self.n_cols()
+        ));
+
+        table
+    }
+
+    /// Creates a new `LocalDataFrame` with a row for each of the given
+    /// `records` and a column for each of their fields, named after the
+    /// field, in the order the fields first appear. Each record must
+    /// serialize to a struct or a map with `String` keys whose values are
+    /// primitives, `String`s, unit enum variants (stored as the name of the
+    /// variant) or `Option`s of those, e.g. a struct that derives
+    /// `Serialize`. `None` values and fields missing from a record are null.
+    ///
+    /// # Errors
+    /// - `LiquidError::SerdeError` if a record has any other shape
+    /// - `LiquidError::TypeMismatch` if the values of a field have different
+    ///   types
+    /// - `LiquidError::InvalidArgument` if a field is always null, so that
+    ///   its type is unknown
+    pub fn from_records<T: Serialize>(
+        records: &[T],
+    ) -> Result<Self, LiquidError> {
+        let mut names = Vec::new();
+        let mut seen = HashSet::new();
+        let mut rows = Vec::with_capacity(records.len());
+        for record in records {
+            let mut row = HashMap::new();
+            for (name, value) in records::to_fields(record)? {
+                if seen.insert(name.clone()) {
+                    names.push(name.clone());
+                }
+                row.insert(name, value);
+            }
+            rows.push(row);
+        }
+
+        frame_from_records(&names, &rows)
+    }
+
+    /// Deserializes every row of this `LocalDataFrame` to a record of type
+    /// `T`, the opposite of [`from_records`], where each field of `T` is
+    /// read from the column with the same name. Unnamed columns are named by
+    /// their index, columns that `T` has no field for are ignored, and null
+    /// values can only be read into `Option` fields.
+    ///
+    /// # Errors
+    /// If a row can not be deserialized to `T`, e.g. because a field is
+    /// missing or has the wrong type, `LiquidError::SerdeError` is returned.
+    ///
+    /// [`from_records`]: struct.LocalDataFrame.html#method.from_records
+    pub fn to_records<T: DeserializeOwned>(
+        &self,
+    ) -> Result<Vec<T>, LiquidError> {
+        let names = self.col_labels();
+        (0..self.n_rows())
+            .map(|row_idx| {
+                let fields = names
+                    .iter()
+                    .enumerate()
+                    .map(|(col_idx, name)| {
+                        Ok((name.clone(), self.get(col_idx, row_idx)?))
+                    })
+                    .collect::<Result<Vec<_>, LiquidError>>()?;
+                Ok(records::from_fields(fields)?)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the rows of this `LocalDataFrame`, in order,
+    /// as `Row`s with their index set. Every `Row` is a copy of the values in
+    /// that row, so use [`iter_row_refs`] to scan the rows without copying
+    /// them.
+    ///
+    /// [`iter_row_refs`]: struct.LocalDataFrame.html#method.iter_row_refs
+    pub fn iter_rows(&self) -> impl Iterator<Item = Row> + '_ {
+        (0..self.n_rows()).map(move |row_idx| {
+            let mut row = Row::new(&self.schema);
+            // the row index is in bounds and the row has our schema
+            self.fill_row(row_idx, &mut row).unwrap();
+            row
+        })
+    }
+
+    /// Returns an iterator over borrowed views of the rows of this
+    /// `LocalDataFrame`, in order, which read values straight from the
+    /// columns instead of copying them into a `Row`.
+    pub fn iter_row_refs(&self) -> impl Iterator<Item = RowRef<'_>> {
+        (0..self.n_rows()).map(move |row_idx| RowRef::new(self, row_idx))
+    }
+
+    /// Returns a reference to the `Column` named `name`, the checked
+    /// version of indexing this `LocalDataFrame` with `df[name]`.
+    ///
+    /// # Errors
+    /// If there is no column named `name`, a `LiquidError::InvalidArgument`
+    /// is returned.
+    pub fn get_checked(&self, name: &str) -> Result<&Column, LiquidError> {
+        self.get_col_idx(name)
+            .and_then(|col_idx| self.column(col_idx))
+            .ok_or_else(|| no_column_named(name))
+    }
+
+    /// Converts this `LocalDataFrame` to an Arrow `RecordBatch` so it can be
+    /// used with other Arrow based tools. `Bool`, `Int`, `Float` and `String`
+    /// columns become nullable `Boolean`, `Int64`, `Float64` and `Utf8`
+    /// arrays, and each field is named after its column, or its column index
+    /// if the column has no name.
+    ///
+    /// # Errors
+    /// If Arrow fails to create the `RecordBatch`, a
+    /// `LiquidError::ArrowError` is returned.
+    #[cfg(feature = "arrow")]
+    pub fn to_arrow(&self) -> Result<RecordBatch, LiquidError> {
+        let fields: Vec<Field> = self
+            .col_labels()
+            .into_iter()
+            .zip(self.data.iter())
+            .map(|(name, col)| {
+                Field::new(name, arrow_io::to_arrow_type(col), true)
+            })
+            .collect();
+        let arrays = self.data.iter().map(|col| arrow_io::to_array(col));
+        let options =
+            RecordBatchOptions::new().with_row_count(Some(self.n_rows()));
+        Ok(RecordBatch::try_new_with_options(
+            Arc::new(ArrowSchema::new(fields)),
+            arrays.collect(),
+            &options,
+        )?)
+    }
+
+    /// Creates a new `LocalDataFrame` from the given Arrow `RecordBatch`,
+    /// with one column per field named after the field. Signed integers and
+    /// unsigned integers of up to 32 bits become `Int` columns, floats become
+    /// `Float` columns, and `Utf8` and `LargeUtf8` arrays become `String`
+    /// columns.
+    ///
+    /// # Errors
+    /// If a field has any other type, a `LiquidError::InvalidArgument` is
+    /// returned, and if two fields have the same name, a
+    /// `LiquidError::NameAlreadyExists` is returned.
+    #[cfg(feature = "arrow")]
+    pub fn from_arrow(batch: &RecordBatch) -> Result<Self, LiquidError> {
+        let mut df = LocalDataFrame::new(&Schema::new());
+        let schema = batch.schema();
+        for (field, array) in schema.fields().iter().zip(batch.columns()) {
+            let col = arrow_io::from_array(array.as_ref())?;
+            df.append_column_checked(col, Some(field.name().clone()))?;
+        }
+
+        Ok(df)
+    }
+
+    /// Writes this `LocalDataFrame` to an Arrow IPC file at `file_name`, as
+    /// a single `RecordBatch` created by [`to_arrow`].
+    ///
+    /// # Errors
+    /// If the file can't be created, a `LiquidError::NetworkError` is
+    /// returned, and if Arrow fails to write it, a `LiquidError::ArrowError`
+    /// is returned.
+    ///
+    /// [`to_arrow`]: struct.LocalDataFrame.html#method.to_arrow
+    #[cfg(feature = "arrow")]
+    pub fn write_ipc(&self, file_name: &str) -> Result<(), LiquidError> {
+        let batch = self.to_arrow()?;
+        let mut writer =
+            FileWriter::try_new(File::create(file_name)?, &batch.schema())?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Reads the Arrow IPC file at `file_name` into a new `LocalDataFrame`,
+    /// converting and concatenating all of its `RecordBatch`es as in
+    /// [`from_arrow`].
+    ///
+    /// # Errors
+    /// If the file can't be opened, a `LiquidError::NetworkError` is
+    /// returned, if it is not a valid Arrow IPC file, a
+    /// `LiquidError::ArrowError` is returned, and if it has columns of an
+    /// unsupported type, a `LiquidError::InvalidArgument` is returned.
+    ///
+    /// [`from_arrow`]: struct.LocalDataFrame.html#method.from_arrow
+    #[cfg(feature = "arrow")]
+    pub fn read_ipc(file_name: &str) -> Result<Self, LiquidError> {
+        let reader = FileReader::try_new(File::open(file_name)?, None)?;
+        let mut df =
+            Self::from_arrow(&RecordBatch::new_empty(reader.schema()))?;
+        for batch in reader {
+            let batch = Self::from_arrow(&batch?)?;
+            df.append_rows_from(&batch, 0..batch.n_rows())?;
+        }
+
+        Ok(df)
+    }
+
+    /// Reads the row groups with the indices in `row_groups` of the Parquet
+    /// file at `file_name` into a new `LocalDataFrame`, converting the
+    /// columns as in [`from_arrow`]. Reading a range of row groups instead of
+    /// the whole file lets a large file be read in parts, e.g. one part per
+    /// node when a `DistributedDataFrame` is created from it, see
+    /// [`parquet_row_group_sizes`].
+    ///
+    /// # Errors
+    /// If `row_groups` is not within the row groups of the file, a
+    /// `LiquidError::InvalidArgument` is returned. If the file can't be
+    /// opened, a `LiquidError::NetworkError` is returned, and if it is not a
+    /// valid Parquet file, a `LiquidError::ParquetError` is returned.
+    ///
+    /// [`from_arrow`]: struct.LocalDataFrame.html#method.from_arrow
+    /// [`parquet_row_group_sizes`]: struct.LocalDataFrame.html#method.parquet_row_group_sizes
+    #[cfg(feature = "parquet")]
+    pub fn from_parquet(
+        file_name: &str,
+        row_groups: Range<usize>,
+    ) -> Result<Self, LiquidError> {
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(file_name)?)?;
+        let num_row_groups = builder.metadata().num_row_groups();
+        if row_groups.start > row_groups.end || row_groups.end > num_row_groups
+        {
+            return Err(LiquidError::InvalidArgument(format!(
+                "{} has {} row groups, can't read row groups {:?}",
+                file_name, num_row_groups, row_groups
+            )));
+        }
+        let empty = RecordBatch::new_empty(builder.schema().clone());
+        let mut df = Self::from_arrow(&empty)?;
+        let reader = builder.with_row_groups(row_groups.collect()).build()?;
+        for batch in reader {
+            let batch = Self::from_arrow(&batch?)?;
+            df.append_rows_from(&batch, 0..batch.n_rows())?;
+        }
+
+        Ok(df)
+    }
+
+    /// Returns the number of rows in each row group of the Parquet file at
+    /// `file_name`, in order, without reading any of the data.
+    ///
+    /// # Errors
+    /// If the file can't be opened, a `LiquidError::NetworkError` is
+    /// returned, and if it is not a valid Parquet file, a
+    /// `LiquidError::ParquetError` is returned.
+    #[cfg(feature = "parquet")]
+    pub fn parquet_row_group_sizes(
+        file_name: &str,
+    ) -> Result<Vec<usize>, LiquidError> {
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(file_name)?)?;
+        Ok(builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|row_group| row_group.num_rows() as usize)
+            .collect())
+    }
+
+    /// Writes this `LocalDataFrame` to a Parquet file at `file_name`, with
+    /// the columns converted as in [`to_arrow`].
+    ///
+    /// # Errors
+    /// If the file can't be created, a `LiquidError::NetworkError` is
+    /// returned, and if Parquet fails to write it, a
+    /// `LiquidError::ParquetError` is returned.
+    ///
+    /// [`to_arrow`]: struct.LocalDataFrame.html#method.to_arrow
+    #[cfg(feature = "parquet")]
+    pub fn to_parquet(&self, file_name: &str) -> Result<(), LiquidError> {
+        let batch = self.to_arrow()?;
+        let mut writer = ArrowWriter::try_new(
+            File::create(file_name)?,
+            batch.schema(),
+            None,
+        )?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+    /// Creates a new `LocalDataFrame` from the CSV file at `file_name`, read
+    /// with the given `options`, see [`CsvOptions`] for the delimiter,
+    /// header, quoting and per-column types. Unless a type is given for a
+    /// column, it is inferred from the first records: empty fields are null,
+    /// `true` and `false` are `Bool`s, and each column gets the most general
+    /// type of its values (`Int` < `Float` < `String`), where `Bool`s mixed
+    /// with numbers are `String`s.
+    ///
+    /// As with [`from_sor_bytes`], records with fewer fields than the
+    /// `Schema` are null in the missing columns, and records with more
+    /// fields, or with a field that can't be parsed as the type of its
+    /// column, are discarded. Records that are not valid UTF-8 are discarded
+    /// too.
+    ///
+    /// The number of discarded records is logged as a warning.
+    ///
+    /// # Errors
+    /// If the file can't be opened or read, a `LiquidError::NetworkError` is
+    /// returned. If the header has the same name twice, a
+    /// `LiquidError::NameAlreadyExists` is returned, and if `options` has a
+    /// type for a column that is not in the file, a
+    /// `LiquidError::InvalidArgument` is returned.
+    ///
+    /// [`CsvOptions`]: struct.CsvOptions.html
+    /// [`from_sor_bytes`]: struct.LocalDataFrame.html#method.from_sor_bytes
+    pub fn from_csv(
+        file_name: &str,
+        options: &CsvOptions,
+    ) -> Result<Self, LiquidError> {
+        let schema = csv_io::infer_schema(file_name, options)?;
+        let mut reader = options.reader(file_name)?;
+        let mut records = csv_io::data_records(&mut reader, options)?;
+        let parsed = records
+            .by_ref()
+            .collect::<Result<Vec<StringRecord>, LiquidError>>()?;
+        let (df, discarded) = LocalDataFrame::from_csv_records(schema, &parsed);
+        csv_io::warn_discarded(discarded + records.n_malformed);
+        Ok(df)
+    }
+
+    /// Creates a new `LocalDataFrame` with the given `schema` by parsing the
+    /// given CSV `records`, see [`from_csv`]. Returns the data frame and the
+    /// number of records that were discarded because they are malformed.
+    ///
+    /// [`from_csv`]: struct.LocalDataFrame.html#method.from_csv
+    pub(crate) fn from_csv_records(
+        schema: Schema,
+        records: &[StringRecord],
+    ) -> (Self, usize) {
+        let (data, discarded) = csv_io::parse_records(&schema, records);
+        let df = LocalDataFrame {
+            schema,
+            data: data.into_iter().map(Arc::new).collect(),
+            n_threads: num_cpus::get(),
+            cur_row_idx: 0,
+            row_names: HashMap::new(),
+        };
+        (df, discarded)
+    }
+
+    /// Creates a new `LocalDataFrame` with the same `Schema` as this one that
+    /// contains only the rows at the given `indices`, in the order given.
+    /// Copies column-to-column without creating intermediate `Row`s.
+    ///
+    /// Will panic if any of the `indices` are out of bounds.
+    pub(crate) fn take_rows(&self, indices: &[usize]) -> Self {
+        let data = self
+            .data
+            .iter()
+            .map(|col| {
+                Arc::new(match col.as_ref() {
+                    Column::Bool(c) => {
+                        Column::Bool(indices.iter().map(|&i| c[i]).collect())
+                    }
+                    Column::Int(c) => {
+                        Column::Int(indices.iter().map(|&i| c[i]).collect())
+                    }
+                    Column::Float(c) => {
+                        Column::Float(indices.iter().map(|&i| c[i]).collect())
+                    }
+                    Column::String(c) => Column::String(
+                        indices.iter().map(|&i| c[i].clone()).collect(),
+                    ),
+                })
+            })
+            .collect();
+
+        LocalDataFrame {
+            schema: self.schema.clone(),
+            data,
+            n_threads: self.n_threads,
+            cur_row_idx: 0,
+            row_names: HashMap::new(),
+        }
+    }
+}
+
+/// A [`Rower`] that groups rows by the values in its key columns and computes
+/// aggregations over each group, used by `LocalDataFrame::group_by` and
+/// `LiquidML::group_by`. The partial results of different threads or nodes
+/// are merged by `join`, so the groups of a `DistributedDataFrame` can be
+/// computed with a distributed `map`.
+///
+/// [`Rower`]: trait.Rower.html
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct GroupByRower {
+    /// The indices of the key columns
+    keys: Vec<usize>,
+    /// The index of the column, the aggregation and whether the column is an
+    /// `Int` column, for every aggregation
+    aggs: Vec<(usize, Agg, bool)>,
+    /// The keys of every group, in the order they were first visited
+    order: Vec<Vec<DataKey>>,
+    /// The state of every aggregation for every group
+    groups: HashMap<Vec<DataKey>, Vec<AggState>>,
+}
+
+impl GroupByRower {
+    /// Creates a new `GroupByRower` for a data frame with the given `schema`
+    /// that groups by the given `keys` columns and computes the given `aggs`.
+    ///
+    /// # Errors
+    /// If any of the columns are out of bounds,
+    /// `LiquidError::ColIndexOutOfBounds` is returned. If any aggregation
+    /// other than `Agg::Count` is of a column that is not an `Int` or `Float`
+    /// column, `LiquidError::TypeMismatch` is returned.
+    pub(crate) fn new(
+        schema: &Schema,
+        keys: &[usize],
+        aggs: &[(usize, Agg)],
+    ) -> Result<Self, LiquidError> {
+        for &key in keys {
+            schema.col_type(key)?;
+        }
+        let aggs = aggs
+            .iter()
+            .map(|&(col_idx, agg)| match (schema.col_type(col_idx)?, agg) {
+                (DataType::Int, _) => Ok((col_idx, agg, true)),
+                (DataType::Float, _) | (_, Agg::Count) => {
+                    Ok((col_idx, agg, false))
+                }
+                _ => Err(LiquidError::TypeMismatch),
+            })
+            .collect::<Result<Vec<_>, LiquidError>>()?;
+
+        Ok(GroupByRower {
+            keys: keys.to_vec(),
+            aggs,
+            order: Vec::new(),
+            groups: HashMap::new(),
+        })
+    }
+
+    /// Consumes this `GroupByRower`, returning a `LocalDataFrame` with one
+    /// row per group, in the order the groups were first visited. It has the
+    /// key columns of the given `schema` followed by one column per
+    /// aggregation, named `<column name>_<aggregation>` if the aggregated
+    /// column is named.
+    pub(crate) fn into_frame(
+        self,
+        schema: &Schema,
+    ) -> Result<LocalDataFrame, LiquidError> {
+        let mut df = LocalDataFrame::new(&Schema::new());
+        for (pos, &key_idx) in self.keys.iter().enumerate() {
+            let keys = self.order.iter().map(|key| &key[pos]);
+            let col = match schema.col_type(key_idx)? {
+                DataType::Bool => Column::Bool(
+                    keys.map(|k| match k {
+                        DataKey::Bool(b) => Some(*b),
+                        _ => None,
+                    })
+                    .collect(),
+                ),
+                DataType::Int => Column::Int(
+                    keys.map(|k| match k {
+                        DataKey::Int(i) => Some(*i),
+                        _ => None,
+                    })
+                    .collect(),
+                ),
+                DataType::Float => Column::Float(
+                    keys.map(|k| match k {
+                        DataKey::Float(bits) => Some(f64::from_bits(*bits)),
+                        _ => None,
+                    })
+                    .collect(),
+                ),
+                DataType::String => Column::String(
+                    keys.map(|k| match k {
+                        DataKey::String(s) => Some(s.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+                ),
+            };
+            df.add_column(col, schema.col_name(key_idx)?.map(String::from))?;
+        }
+        for (pos, &(col_idx, agg, is_int)) in self.aggs.iter().enumerate() {
+            let states = self.order.iter().map(|key| &self.groups[key][pos]);
+            let col = match agg {
+                Agg::Count => {
+                    Column::Int(states.map(|s| Some(s.count)).collect())
+                }
+                Agg::Mean => Column::Float(
+                    states
+                        .map(|s| s.float.map(|sum| sum / s.count as f64))
+                        .collect(),
+                ),
+                _ if is_int => Column::Int(states.map(|s| s.int).collect()),
+                _ => Column::Float(states.map(|s| s.float).collect()),
+            };
+            let name = schema
+                .col_name(col_idx)?
+                .map(|name| format!("{}_{}", name, agg));
+            df.add_column(col, name)?;
+        }
+
+        Ok(df)
+    }
+}
+
+impl Rower for GroupByRower {
+    fn visit(&mut self, row: &Row) -> bool {
+        let key: Vec<DataKey> = self
+            .keys
+            .iter()
+            .map(|&idx| data_key_of(row.get(idx).unwrap()))
+            .collect();
+        if !self.groups.contains_key(&key) {
+            let states = vec![AggState::default(); self.aggs.len()];
+            self.groups.insert(key.clone(), states);
+            self.order.push(key.clone());
+        }
+        let states = self.groups.get_mut(&key).unwrap();
+        for (state, &(col_idx, agg, _)) in states.iter_mut().zip(&self.aggs) {
+            state.add(agg, row.get(col_idx).unwrap());
+        }
+        true
+    }
+
+    fn join(mut self, other: Self) -> Self {
+        for key in other.order {
+            let other_states = &other.groups[&key];
+            match self.groups.get_mut(&key) {
+                Some(states) => {
+                    for ((state, other_state), &(_, agg, _)) in
+                        states.iter_mut().zip(other_states).zip(&self.aggs)
+                    {
+                        state.merge(agg, other_state);
+                    }
+                }
+                None => {
+                    self.groups.insert(key.clone(), other_states.clone());
+                    self.order.push(key);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// The partial result of one aggregation over one group of a `GroupByRower`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+struct AggState {
+    /// The number of non-null values
+    count: i64,
+    /// The sum, min or max of the values of an `Int` column
+    int: Option<i64>,
+    /// The sum, min or max of the values of a numeric column as `f64`s
+    float: Option<f64>,
+}
+
+impl AggState {
+    /// Adds the given `value` to this state of the given `agg`. Nulls are
+    /// skipped.
+    fn add(&mut self, agg: Agg, value: &Data) {
+        let (int, float) = match value {
+            Data::Null => return,
+            Data::Int(i) => (Some(*i), Some(*i as f64)),
+            Data::Float(f) => (None, Some(*f)),
+            _ => (None, None),
+        };
+        self.merge(
+            agg,
+            &AggState {
+                count: 1,
+                int,
+                float,
+            },
+        );
+    }
+
+    /// Combines `other` into this state of the given `agg`
+    fn merge(&mut self, agg: Agg, other: &AggState) {
+        let (int_f, float_f): (fn(i64, i64) -> i64, fn(f64, f64) -> f64) =
+            match agg {
+                Agg::Min => (cmp::min, f64::min),
+                Agg::Max => (cmp::max, f64::max),
+                _ => (i64::wrapping_add, |a, b| a + b),
+            };
+        self.count += other.count;
+        self.int = fold_window(&[self.int, other.int], int_f);
+        self.float = fold_window(&[self.float, other.float], float_f);
+    }
+}
+
+/// The statistics computed by `LocalDataFrame::describe`, in order
+const DESCRIBE_STATS: [&str; 10] = [
+    "count",
+    "null_count",
+    "distinct",
+    "mean",
+    "std",
+    "min",
+    "25%",
+    "50%",
+    "75%",
+    "max",
+];
+
+/// Half the number of values a level of a `QuantileSketch` holds before it is
+/// compacted
+const QUANTILE_SKETCH_CAPACITY: usize = 4096;
+
+/// A [`Rower`] that computes a sketch of every column of a data frame, used
+/// by `LocalDataFrame::describe` and `LiquidML::describe`. The sketches of
+/// different threads or nodes are merged by `join`, so the statistics of a
+/// `DistributedDataFrame` can be computed with a distributed `map`.
+///
+/// [`Rower`]: trait.Rower.html
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct DescribeRower {
+    /// The sketch of every column
+    sketches: Vec<ColumnSketch>,
+}
+
+impl DescribeRower {
+    /// Creates a new `DescribeRower` for a data frame with the given
+    /// `schema`
+    pub(crate) fn new(schema: &Schema) -> Self {
+        let sketches = schema
+            .schema
+            .iter()
+            .map(|data_type| match data_type {
+                DataType::Int | DataType::Float => ColumnSketch {
+                    quantiles: Some(QuantileSketch::default()),
+                    ..ColumnSketch::default()
+                },
+                _ => ColumnSketch {
+                    distinct: Some(HashSet::new()),
+                    ..ColumnSketch::default()
+                },
+            })
+            .collect();
+        DescribeRower { sketches }
+    }
+
+    /// Consumes this `DescribeRower`, returning a `LocalDataFrame` with one
+    /// named row per statistic in `DESCRIBE_STATS` and one `Float` column per
+    /// column of the given `schema`, with the same name.
+    pub(crate) fn into_frame(self, schema: &Schema) -> LocalDataFrame {
+        let mut df = LocalDataFrame::new(&Schema::new());
+        for (col_idx, sketch) in self.sketches.into_iter().enumerate() {
+            // the names of `schema` are unique and all columns have the same
+            // number of rows
+            let name = schema.col_name(col_idx).unwrap().map(String::from);
+            df.add_column(Column::Float(sketch.stats()), name).unwrap();
+        }
+        for (row_idx, stat) in DESCRIBE_STATS.iter().enumerate() {
+            if row_idx < df.n_rows() {
+                df.set_row_name(row_idx, stat.to_string()).unwrap();
+            }
+        }
+        df
+    }
+}
+
+impl Rower for DescribeRower {
+    fn visit(&mut self, row: &Row) -> bool {
+        for (col_idx, sketch) in self.sketches.iter_mut().enumerate() {
+            sketch.add(row.get(col_idx).unwrap());
+        }
+        true
+    }
+
+    fn join(mut self, other: Self) -> Self {
+        for (sketch, other) in self.sketches.iter_mut().zip(other.sketches) {
+            sketch.merge(other);
+        }
+        self
+    }
+}
+
+/// A mergeable summary of the values of one column of a `DescribeRower`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct ColumnSketch {
+    /// The number of non-null values
+    count: usize,
+    /// The number of nulls
+    nulls: usize,
+    /// The mean of the numeric values
+    mean: f64,
+    /// The sum of squared differences from the `mean` of the numeric values
+    m2: f64,
+    /// The smallest numeric value
+    min: Option<f64>,
+    /// The largest numeric value
+    max: Option<f64>,
+    /// The quantile sketch of a numeric column
+    quantiles: Option<QuantileSketch>,
+    /// The distinct values of a `Bool` or `String` column
+    distinct: Option<HashSet<DataKey>>,
+}
+
+impl ColumnSketch {
+    /// Adds the given `value` to this sketch
+    fn add(&mut self, value: &Data) {
+        let x = match value {
+            Data::Null => {
+                self.nulls += 1;
+                return;
+            }
+            Data::Int(i) => *i as f64,
+            Data::Float(f) => *f,
+            _ => {
+                self.count += 1;
+                if let Some(distinct) = &mut self.distinct {
+                    distinct.insert(data_key_of(value));
+                }
+                return;
+            }
+        };
+        // Welford's algorithm, as in `LocalDataFrame::summary`
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = fold_window(&[self.min, Some(x)], f64::min);
+        self.max = fold_window(&[self.max, Some(x)], f64::max);
+        if let Some(quantiles) = &mut self.quantiles {
+            quantiles.add(x);
+        }
+    }
+
+    /// Combines `other` into this sketch of the same column
+    fn merge(&mut self, other: ColumnSketch) {
+        let count = self.count + other.count;
+        if count > 0 {
+            let delta = other.mean - self.mean;
+            let (n_a, n_b) = (self.count as f64, other.count as f64);
+            self.mean += delta * n_b / count as f64;
+            self.m2 += other.m2 + delta * delta * n_a * n_b / count as f64;
+        }
+        self.count = count;
+        self.nulls += other.nulls;
+        self.min = fold_window(&[self.min, other.min], f64::min);
+        self.max = fold_window(&[self.max, other.max], f64::max);
+        if let (Some(quantiles), Some(other)) =
+            (&mut self.quantiles, other.quantiles)
+        {
+            quantiles.merge(other);
+        }
+        if let (Some(distinct), Some(other)) =
+            (&mut self.distinct, other.distinct)
+        {
+            distinct.extend(other);
+        }
+    }
+
+    /// Returns the statistics of this sketch in the order of
+    /// `DESCRIBE_STATS`
+    fn stats(self) -> Vec<Option<f64>> {
+        let numeric = self.quantiles.is_some() && self.count > 0;
+        let numeric_stat = |x: f64| if numeric { Some(x) } else { None };
+        let quartile = |q: f64| {
+            self.quantiles
+                .as_ref()
+                .and_then(|sketch| sketch.quantile(q))
+        };
+        vec![
+            Some(self.count as f64),
+            Some(self.nulls as f64),
+            self.distinct.as_ref().map(|d| d.len() as f64),
+            numeric_stat(self.mean),
+            if self.count > 1 && numeric {
+                Some((self.m2 / (self.count - 1) as f64).sqrt())
+            } else {
+                None
+            },
+            self.min,
+            quartile(0.25),
+            quartile(0.5),
+            quartile(0.75),
+            self.max,
+        ]
+    }
+}
+
+/// A mergeable sketch of the distribution of a numeric column, used to
+/// estimate its quantiles. Values are kept in levels, where every value in
+/// level `i` stands for `2^i` values of the column. Once a level holds
+/// `2 * QUANTILE_SKETCH_CAPACITY` values it is compacted by sorting it and
+/// promoting every other value to the next level, so the sketch is exact
+/// until it is first compacted and uses logarithmic space after that.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct QuantileSketch {
+    /// The values in every level of the sketch
+    levels: Vec<Vec<f64>>,
+    /// The number of compactions so far, used to alternate between promoting
+    /// the even and odd values so the quantiles are not skewed
+    compactions: usize,
+}
+
+impl QuantileSketch {
+    /// Adds the value `x` to this sketch
+    fn add(&mut self, x: f64) {
+        self.insert(0, Some(x));
+    }
+
+    /// Combines `other` into this sketch
+    fn merge(&mut self, other: QuantileSketch) {
+        for (level, values) in other.levels.into_iter().enumerate() {
+            self.insert(level, values);
+        }
+    }
+
+    /// Adds the given `values` to the given `level` of this sketch, and
+    /// compacts it and the levels above it if they are full
+    fn insert<I>(&mut self, mut level: usize, values: I)
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        if self.levels.len() <= level {
+            self.levels.resize(level + 1, Vec::new());
+        }
+        self.levels[level].extend(values);
+        while level < self.levels.len()
+            && self.levels[level].len() >= 2 * QUANTILE_SKETCH_CAPACITY
+        {
+            let mut values = std::mem::take(&mut self.levels[level]);
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            // only an even number of values can be promoted without changing
+            // the total weight of the sketch
+            if values.len() % 2 == 1 {
+                self.levels[level].push(values.pop().unwrap());
+            }
+            let promoted: Vec<f64> = values
+                .into_iter()
+                .skip(self.compactions % 2)
+                .step_by(2)
+                .collect();
+            self.compactions += 1;
+            if self.levels.len() == level + 1 {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level + 1].extend(promoted);
+            level += 1;
+        }
+    }
+
+    /// Returns the `q`th quantile of this sketch using the nearest-rank
+    /// method, or `None` if it is empty
+    fn quantile(&self, q: f64) -> Option<f64> {
+        let mut weighted: Vec<(f64, usize)> = self
+            .levels
+            .iter()
+            .enumerate()
+            .flat_map(|(level, values)| {
+                values.iter().map(move |&x| (x, 1 << level))
+            })
+            .collect();
+        weighted
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let total: usize = weighted.iter().map(|(_, weight)| weight).sum();
+        let rank = cmp::max(1, (q * total as f64).ceil() as usize);
+        let mut seen: usize = 0;
+        weighted
+            .iter()
+            .find(|(_, weight)| {
+                seen += *weight;
+                seen >= rank
+            })
+            .map(|(x, _)| *x)
+    }
+}
+
+/// A hashable representation of a single value in a `Column`, used as the key
+/// in hash based operations such as joins. Floats are compared by their bit
+/// representation.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+enum DataKey {
+    Bool(bool),
+    Int(i64),
+    Float(u64),
+    String(String),
+    Null,
+}
+
+impl fmt::Display for DataKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataKey::Bool(b) => write!(f, "{}", b),
+            DataKey::Int(i) => write!(f, "{}", i),
+            DataKey::Float(bits) => write!(f, "{}", f64::from_bits(*bits)),
+            DataKey::String(s) => write!(f, "{}", s),
+            DataKey::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// Get the `DataKey` of the value at `row_idx` in the given `col`. Will panic
+/// if `row_idx` is out of bounds.
+fn data_key(col: &Column, row_idx: usize) -> DataKey {
+    match col {
+        Column::Bool(c) => c[row_idx].map_or(DataKey::Null, DataKey::Bool),
+        Column::Int(c) => c[row_idx].map_or(DataKey::Null, DataKey::Int),
+        Column::Float(c) => {
+            c[row_idx].map_or(DataKey::Null, |f| DataKey::Float(f.to_bits()))
+        }
+        Column::String(c) => c[row_idx]
+            .as_ref()
+            .map_or(DataKey::Null, |s| DataKey::String(s.clone())),
+    }
+}
+
+/// Get the `DataKey` of the given `value`
+fn data_key_of(value: &Data) -> DataKey {
+    match value {
+        Data::Bool(b) => DataKey::Bool(*b),
+        Data::Int(i) => DataKey::Int(*i),
+        Data::Float(f) => DataKey::Float(f.to_bits()),
+        Data::String(s) => DataKey::String(s.clone()),
+        Data::Null => DataKey::Null,
+    }
+}
+
+/// Creates a new `Column` of the same type as `col` that has the value of
+/// `col` at each of the given `rows`, or a null where the row is `None`. Will
+/// panic if any of the `rows` are out of bounds.
+fn gather_rows(col: &Column, rows: &[Option<usize>]) -> Column {
+    match col {
+        Column::Bool(c) => {
+            Column::Bool(rows.iter().map(|r| r.and_then(|r| c[r])).collect())
+        }
+        Column::Int(c) => {
+            Column::Int(rows.iter().map(|r| r.and_then(|r| c[r])).collect())
+        }
+        Column::Float(c) => {
+            Column::Float(rows.iter().map(|r| r.and_then(|r| c[r])).collect())
+        }
+        Column::String(c) => Column::String(
+            rows.iter().map(|r| r.and_then(|r| c[r].clone())).collect(),
+        ),
+    }
+}
+
+/// Casts every value in `col` to the given `data_type`, returning `None` if
+/// any value can't be cast. See `LocalDataFrame::cast_column` for the rules.
+fn cast(col: &Column, data_type: &DataType) -> Option<Column> {
+    /// Applies `f` to every non-null value of `col`
+    fn all<T, U>(
+        col: &[Option<T>],
+        f: impl Fn(&T) -> Option<U>,
+    ) -> Option<Vec<Option<U>>> {
+        col.iter()
+            .map(|x| match x {
+                Some(x) => f(x).map(Some),
+                None => Some(None),
+            })
+            .collect()
+    }
+    fn to_bool(x: f64) -> Option<bool> {
+        if x == 0.0 {
+            Some(false)
+        } else if x == 1.0 {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    match (col, data_type) {
+        (Column::Bool(c), DataType::Bool) => Some(Column::Bool(c.clone())),
+        (Column::Bool(c), DataType::Int) => {
+            all(c, |b| Some(*b as i64)).map(Column::Int)
+        }
+        (Column::Bool(c), DataType::Float) => {
+            all(c, |b| Some(*b as i64 as f64)).map(Column::Float)
+        }
+        (Column::Bool(c), DataType::String) => {
+            all(c, |b| Some(b.to_string())).map(Column::String)
+        }
+        (Column::Int(c), DataType::Bool) => {
+            all(c, |i| to_bool(*i as f64)).map(Column::Bool)
+        }
+        (Column::Int(c), DataType::Int) => Some(Column::Int(c.clone())),
+        (Column::Int(c), DataType::Float) => {
+            all(c, |i| Some(*i as f64)).map(Column::Float)
+        }
+        (Column::Int(c), DataType::String) => {
+            all(c, |i| Some(i.to_string())).map(Column::String)
+        }
+        (Column::Float(c), DataType::Bool) => {
+            all(c, |f| to_bool(*f)).map(Column::Bool)
+        }
+        (Column::Float(c), DataType::Int) => all(c, |f| {
+            // `i64::MAX as f64` rounds up to 2^63, which doesn't fit
+            if f.fract() == 0.0 && *f >= i64::MIN as f64 && *f < i64::MAX as f64
+            {
+                Some(*f as i64)
+            } else {
+                None
+            }
+        })
+        .map(Column::Int),
+        (Column::Float(c), DataType::Float) => Some(Column::Float(c.clone())),
+        (Column::Float(c), DataType::String) => {
+            all(c, |f| Some(f.to_string())).map(Column::String)
+        }
+        (Column::String(c), DataType::Bool) => {
+            all(c, |s| s.trim().parse().ok()).map(Column::Bool)
+        }
+        (Column::String(c), DataType::Int) => {
+            all(c, |s| s.trim().parse().ok()).map(Column::Int)
+        }
+        (Column::String(c), DataType::Float) => {
+            all(c, |s| s.trim().parse().ok()).map(Column::Float)
+        }
+        (Column::String(c), DataType::String) => {
+            Some(Column::String(c.clone()))
+        }
+    }
+}
+
+/// Returns whether the value at `row_idx` in the given `col` is null.
+fn is_null(col: &Column, row_idx: usize) -> bool {
+    match col {
+        Column::Bool(c) => c[row_idx].is_none(),
+        Column::Int(c) => c[row_idx].is_none(),
+        Column::Float(c) => c[row_idx].is_none(),
+        Column::String(c) => c[row_idx].is_none(),
+    }
+}
+
+/// Returns the values of the given numeric `col` as `f64`s. The values of a
+/// `Float` column are borrowed rather than copied.
+///
+/// # Errors
+/// If `col` is `None`, `LiquidError::ColIndexOutOfBounds` is returned. If
+/// `col` is not an `Int` or `Float` column, `LiquidError::TypeMismatch` is
+/// returned.
+fn numeric_values(
+    col: Option<&Column>,
+) -> Result<Cow<[Option<f64>]>, LiquidError> {
+    match col {
+        Some(Column::Int(c)) => {
+            Ok(Cow::Owned(c.iter().map(|x| x.map(|x| x as f64)).collect()))
+        }
+        Some(Column::Float(c)) => Ok(Cow::Borrowed(c)),
+        Some(_) => Err(LiquidError::TypeMismatch),
+        None => Err(LiquidError::ColIndexOutOfBounds),
+    }
+}
+
+/// Applies `f` to the trailing window of `window` values ending at every
+/// index of `col`. The result is null for indices before the first full
+/// window.
+fn rolling_window<T, U>(
+    col: &[Option<T>],
+    window: usize,
+    f: impl Fn(&[Option<T>]) -> Option<U>,
+) -> Vec<Option<U>> {
+    (0..col.len())
+        .map(|i| {
+            if i + 1 < window {
+                None
+            } else {
+                f(&col[i + 1 - window..=i])
+            }
+        })
+        .collect()
+}
+
+/// Combines all the non-null values of `window` with `f`, or returns `None`
+/// if they are all null.
+fn fold_window<T: Copy>(window: &[Option<T>], f: fn(T, T) -> T) -> Option<T> {
+    window.iter().flatten().fold(None, |acc, &x| match acc {
+        Some(acc) => Some(f(acc, x)),
+        None => Some(x),
+    })
+}
+
+/// Appends the values of `src` to `dst`, which must have the same type
+fn extend_column(dst: &mut Column, src: &Column) {
+    match (dst, src) {
+        (Column::Bool(dst), Column::Bool(src)) => dst.extend_from_slice(src),
+        (Column::Int(dst), Column::Int(src)) => dst.extend_from_slice(src),
+        (Column::Float(dst), Column::Float(src)) => dst.extend_from_slice(src),
+        (Column::String(dst), Column::String(src)) => {
+            dst.extend_from_slice(src)
+        }
+        _ => unreachable!("the columns have the same type"),
+    }
+}
+
+/// Infers the `Schema` of a `SoR` file from its first
+/// `SOR_SCHEMA_INFERENCE_ROWS` `lines`, see `LocalDataFrame::from_sor_bytes`
+/// for the rules. `sorer`'s `infer_schema` can only read a file, so each
+/// line is parsed with `sorer`'s `parse_line`, which gives every field its
+/// most specific type, and each column gets the most general of those
+/// types. Columns with no values are `Bool`s.
+fn infer_sor_schema(lines: &[&[u8]]) -> Schema {
+    /// The rank of the type of `value`, from most to least specific
+    fn rank(value: &Data) -> usize {
+        match value {
+            Data::Null | Data::Bool(_) => 0,
+            Data::Int(_) => 1,
+            Data::Float(_) => 2,
+            Data::String(_) => 3,
+        }
+    }
+    let mut ranks = Vec::new();
+    let rows = lines
+        .iter()
+        .take(SOR_SCHEMA_INFERENCE_ROWS)
+        .filter_map(|line| parse_line(line));
+    for row in rows {
+        if row.len() > ranks.len() {
+            ranks.resize(row.len(), 0);
+        }
+        for (r, value) in ranks.iter_mut().zip(row.iter()) {
+            *r = cmp::max(*r, rank(value));
+        }
+    }
+    let mut schema = Schema::new();
+    for r in ranks {
+        let data_type = match r {
+            0 => DataType::Bool,
+            1 => DataType::Int,
+            2 => DataType::Float,
+            _ => DataType::String,
+        };
+        schema.add_column(data_type, None).unwrap();
+    }
+    schema
+}
+
+/// Applies `f` to every pair of values of `lhs` and `rhs`. The result is
+/// null where either value is null.
+fn zip_with<T: Copy, U>(
+    lhs: &[Option<T>],
+    rhs: &[Option<T>],
+    f: impl Fn(T, T) -> Option<U>,
+) -> Vec<Option<U>> {
+    lhs.iter()
+        .zip(rhs)
+        .map(|(x, y)| match (x, y) {
+            (Some(x), Some(y)) => f(*x, *y),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Applies `f` to every value of `lhs` with `rhs` as the right hand side.
+/// The result is null where the value is null.
+fn map_with<T: Copy, U>(
+    lhs: &[Option<T>],
+    rhs: T,
+    f: impl Fn(T, T) -> Option<U>,
+) -> Vec<Option<U>> {
+    lhs.iter().map(|x| x.and_then(|x| f(x, rhs))).collect()
+}
+
+/// Returns the `Int` arithmetic for `op`, which is `None` on overflow and
+/// division by zero.
+fn int_arith(op: ArithOp) -> fn(i64, i64) -> Option<i64> {
+    match op {
+        ArithOp::Add => i64::checked_add,
+        ArithOp::Sub => i64::checked_sub,
+        ArithOp::Mul => i64::checked_mul,
+        ArithOp::Div => i64::checked_div,
+    }
+}
+
+/// Returns the `Float` arithmetic for `op`.
+fn float_arith(op: ArithOp) -> fn(f64, f64) -> f64 {
+    match op {
+        ArithOp::Add => |x, y| x + y,
+        ArithOp::Sub => |x, y| x - y,
+        ArithOp::Mul => |x, y| x * y,
+        ArithOp::Div => |x, y| x / y,
+    }
+}
+
+/// Returns whether `ord` satisfies the comparison `op`.
+fn cmp_matches(op: CmpOp, ord: Ordering) -> bool {
+    match op {
+        CmpOp::Eq => ord == Ordering::Equal,
+        CmpOp::Ne => ord != Ordering::Equal,
+        CmpOp::Lt => ord == Ordering::Less,
+        CmpOp::Le => ord != Ordering::Greater,
+        CmpOp::Gt => ord == Ordering::Greater,
+        CmpOp::Ge => ord != Ordering::Less,
+    }
+}
+
+/// Returns the given numeric `value` as an `f64`.
+///
+/// # Errors
+/// If `value` is not a `Data::Int` or `Data::Float`,
+/// `LiquidError::TypeMismatch` is returned.
+fn numeric_scalar(value: &Data) -> Result<f64, LiquidError> {
+    match value {
+        Data::Int(x) => Ok(*x as f64),
+        Data::Float(x) => Ok(*x),
+        _ => Err(LiquidError::TypeMismatch),
+    }
+}
+
+/// Applies the arithmetic `op` element-wise to the numeric columns `lhs` and
+/// `rhs`, see `LocalDataFrame::arith`.
+fn arith_columns(
+    lhs: &Column,
+    rhs: &Column,
+    op: ArithOp,
+) -> Result<Column, LiquidError> {
+    if let (Column::Int(a), Column::Int(b)) = (lhs, rhs) {
+        return Ok(Column::Int(zip_with(a, b, int_arith(op))));
+    }
+    let a = numeric_values(Some(lhs))?;
+    let b = numeric_values(Some(rhs))?;
+    let f = float_arith(op);
+    Ok(Column::Float(zip_with(&a, &b, |x, y| Some(f(x, y)))))
+}
+
+/// Applies the arithmetic `op` element-wise to the numeric column `lhs` and
+/// the numeric `rhs`, see `LocalDataFrame::arith_scalar`.
+fn arith_scalar_column(
+    lhs: &Column,
+    rhs: &Data,
+    op: ArithOp,
+) -> Result<Column, LiquidError> {
+    if let (Column::Int(a), Data::Int(y)) = (lhs, rhs) {
+        return Ok(Column::Int(map_with(a, *y, int_arith(op))));
+    }
+    let a = numeric_values(Some(lhs))?;
+    let y = numeric_scalar(rhs)?;
+    let f = float_arith(op);
+    Ok(Column::Float(map_with(&a, y, |x, y| Some(f(x, y)))))
+}
+
+/// Compares the columns `lhs` and `rhs` element-wise with `op`, see
+/// `LocalDataFrame::compare`.
+fn compare_columns(
+    lhs: &Column,
+    rhs: &Column,
+    op: CmpOp,
+) -> Result<Column, LiquidError> {
+    let matches = |ord| cmp_matches(op, ord);
+    let result = match (lhs, rhs) {
+        (Column::Int(a), Column::Int(b)) => {
+            zip_with(a, b, |x, y| Some(matches(x.cmp(&y))))
+        }
+        (Column::Bool(a), Column::Bool(b)) => {
+            zip_with(a, b, |x, y| Some(matches(x.cmp(&y))))
+        }
+        (Column::String(a), Column::String(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| match (x, y) {
+                (Some(x), Some(y)) => Some(matches(x.cmp(y))),
+                _ => None,
+            })
+            .collect(),
+        (Column::Int(_), _) | (Column::Float(_), _) => {
+            let a = numeric_values(Some(lhs))?;
+            let b = numeric_values(Some(rhs))?;
+            zip_with(&a, &b, |x, y| x.partial_cmp(&y).map(matches))
+        }
+        _ => return Err(LiquidError::TypeMismatch),
+    };
+    Ok(Column::Bool(result))
+}
+
+/// Compares the column `lhs` element-wise with the value `rhs` using `op`,
+/// see `LocalDataFrame::compare_scalar`.
+fn compare_scalar_column(
+    lhs: &Column,
+    rhs: &Data,
+    op: CmpOp,
+) -> Result<Column, LiquidError> {
+    let matches = |ord| cmp_matches(op, ord);
+    let result = match (lhs, rhs) {
+        (Column::Int(a), Data::Int(y)) => {
+            map_with(a, *y, |x, y| Some(matches(x.cmp(&y))))
+        }
+        (Column::Bool(a), Data::Bool(y)) => {
+            map_with(a, *y, |x, y| Some(matches(x.cmp(&y))))
+        }
+        (Column::String(a), Data::String(y)) => a
+            .iter()
+            .map(|x| x.as_ref().map(|x| matches(x.cmp(y))))
+            .collect(),
+        (Column::Int(_), _) | (Column::Float(_), _) => {
+            let a = numeric_values(Some(lhs))?;
+            let y = numeric_scalar(rhs)?;
+            map_with(&a, y, |x, y| x.partial_cmp(&y).map(matches))
+        }
+        _ => return Err(LiquidError::TypeMismatch),
+    };
+    Ok(Column::Bool(result))
+}
+
+/// Collects the given `values` into a `Column` of type `data_type`, where
+/// `Data::Null` values become nulls. Stops at the first error in `values`.
+///
+/// # Errors
+/// If any non-null value is not of type `data_type`,
+/// `LiquidError::TypeMismatch` is returned.
+fn column_from_data<I>(
+    data_type: &DataType,
+    values: I,
+) -> Result<Column, LiquidError>
+where
+    I: IntoIterator<Item = Result<Data, LiquidError>>,
+{
+    let mut col = empty_column(data_type);
+    for value in values {
+        push_data(&mut col, value?)?;
+    }
+
+    Ok(col)
+}
+
+/// Creates an empty `Column` of type `data_type`.
+fn empty_column(data_type: &DataType) -> Column {
+    match data_type {
+        DataType::Bool => Column::Bool(Vec::new()),
+        DataType::Int => Column::Int(Vec::new()),
+        DataType::Float => Column::Float(Vec::new()),
+        DataType::String => Column::String(Vec::new()),
+    }
+}
+
+/// Appends `value` to `col`, where `Data::Null` is appended as a null.
+///
+/// # Errors
+/// If `value` is not null and not of the type of `col`,
+/// `LiquidError::TypeMismatch` is returned.
+fn push_data(col: &mut Column, value: Data) -> Result<(), LiquidError> {
+    match (col, value) {
+        (Column::Bool(c), Data::Bool(b)) => c.push(Some(b)),
+        (Column::Int(c), Data::Int(i)) => c.push(Some(i)),
+        (Column::Float(c), Data::Float(f)) => c.push(Some(f)),
+        (Column::String(c), Data::String(s)) => c.push(Some(s)),
+        (Column::Bool(c), Data::Null) => c.push(None),
+        (Column::Int(c), Data::Null) => c.push(None),
+        (Column::Float(c), Data::Null) => c.push(None),
+        (Column::String(c), Data::Null) => c.push(None),
+        _ => return Err(LiquidError::TypeMismatch),
+    }
+    Ok(())
+}
+
+/// Returns the running sum of the numeric `col`, starting from `offset`,
+/// which must be of the same type. Nulls are skipped and stay null.
+///
+/// # Errors
+/// If `col` is not numeric or `offset` is not of its type,
+/// `LiquidError::TypeMismatch` is returned.
+fn cumulative_sum(col: &Column, offset: &Data) -> Result<Column, LiquidError> {
+    match (col, offset) {
+        (Column::Int(c), Data::Int(offset)) => {
+            let mut sum = *offset;
+            Ok(Column::Int(
+                c.iter()
+                    .map(|x| {
+                        x.map(|x| {
+                            sum = sum.wrapping_add(x);
+                            sum
+                        })
+                    })
+                    .collect(),
+            ))
+        }
+        (Column::Float(c), Data::Float(offset)) => {
+            let mut sum = *offset;
+            Ok(Column::Float(
+                c.iter()
+                    .map(|x| {
+                        x.map(|x| {
+                            sum += x;
+                            sum
+                        })
+                    })
+                    .collect(),
+            ))
+        }
+        _ => Err(LiquidError::TypeMismatch),
+    }
+}
+
+/// Returns the number of rows in a sample of the given `fraction` of `n_rows`
+/// rows, rounded to the nearest row, with `fraction` clamped to `[0, 1]`.
+pub(crate) fn sample_size(fraction: f64, n_rows: usize) -> usize {
+    let fraction = fraction.max(0.0).min(1.0);
+    cmp::min((fraction * n_rows as f64).round() as usize, n_rows)
+}
+
+/// Creates a `LocalDataFrame` with a column for each of the given `names`,
+/// in order, and a row for each of the given `records`, where a name missing
+/// from a record is null in that row. The type of each column is the type of
+/// its first non-null value.
+///
+/// # Errors
+/// If the non-null values of a name have different types,
+/// `LiquidError::TypeMismatch` is returned, and if a name only has null
+/// values, so that its type is unknown, `LiquidError::InvalidArgument` is
+/// returned.
+fn frame_from_records<S: AsRef<str>>(
+    names: &[S],
+    records: &[HashMap<String, Data>],
+) -> Result<LocalDataFrame, LiquidError> {
+    let mut df = LocalDataFrame::new(&Schema::new());
+    for name in names {
+        let name = name.as_ref();
+        let values = records
+            .iter()
+            .map(|record| record.get(name).unwrap_or(&Data::Null));
+        let data_type = match values.clone().find(|v| **v != Data::Null) {
+            Some(Data::Bool(_)) => DataType::Bool,
+            Some(Data::Int(_)) => DataType::Int,
+            Some(Data::Float(_)) => DataType::Float,
+            Some(_) => DataType::String,
+            None => {
+                return Err(LiquidError::InvalidArgument(format!(
+                    "the type of {} is unknown, all of its values are null",
+                    name
+                )))
+            }
+        };
+        let col = column_from_data(&data_type, values.map(|v| Ok(v.clone())))?;
+        df.append_column_checked(col, Some(name.to_string()))?;
+    }
+
+    Ok(df)
+}
+
+/// Returns the complete, non-empty lines of `contents`, a range of bytes of
+/// a `SoR` file. If the range starts in the middle of a line, its partial
+/// first line is skipped, and unless the range ends at the end of the file,
+/// its partial last line is skipped.
+fn sor_lines(contents: &[u8], mid_line: bool, at_eof: bool) -> Vec<&[u8]> {
+    let mut start = 0;
+    if mid_line {
+        start = match contents.iter().position(|b| *b == b'\n') {
+            Some(i) => i + 1,
+            None => contents.len(),
+        };
+    }
+    let mut end = contents.len();
+    if !at_eof {
+        end = match contents[start..].iter().rposition(|b| *b == b'\n') {
+            Some(i) => start + i + 1,
+            None => start,
+        };
+    }
+    contents[start..end]
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .collect()
+}
+
+/// Parses the given `lines` of a `SoR` file with `sorer`'s parser into
+/// `Column`s of the types in `schema`. Rows with fewer fields than the
+/// `schema` are null in the missing columns. Also returns the number of
+/// rows that were discarded for having more fields than the `schema`, and
+/// the number discarded for being malformed otherwise.
+fn parse_sor_lines(
+    schema: &[DataType],
+    lines: &[&[u8]],
+) -> Result<(Vec<Column>, usize, usize), LiquidError> {
+    let mut columns: Vec<Column> = schema.iter().map(empty_column).collect();
+    let (mut wider, mut malformed) = (0, 0);
+    for line in lines {
+        match parse_line_with_schema(line, schema) {
+            Some(row) => {
+                let n_fields = row.len();
+                for (col, value) in columns.iter_mut().zip(row) {
+                    push_data(col, value)?;
+                }
+                for col in columns.iter_mut().skip(n_fields) {
+                    push_data(col, Data::Null)?;
+                }
+            }
+            None if parse_line(line)
+                .map_or(false, |row| row.len() > schema.len()) =>
+            {
+                wider += 1
+            }
+            None => malformed += 1,
+        }
+    }
+
+    Ok((columns, wider, malformed))
+}
+
+fn filter_helper<T: Rower>(
+    df: &LocalDataFrame,
+    r: &mut T,
+    start: usize,
+    end: usize,
+) -> LocalDataFrame {
+    let mut df2 = LocalDataFrame::new(&df.schema);
+    let mut row = Row::new(&df.schema);
+
+    for i in start..end {
+        df.fill_row(i, &mut row).unwrap();
+        if r.visit(&row) {
+            // `row` was filled from `df`, which has the same schema as `df2`
+            df2.push_row_unchecked(&row);
+        }
+    }
+
+    df2
+}
+
+fn map_helper<T: Rower>(
+    df: &LocalDataFrame,
+    mut rower: T,
+    start: usize,
+    end: usize,
+) -> T {
+    let mut row = Row::new(&df.schema);
+    // NOTE: IS THIS THE ~10% slower way to do counted loop???? @tom
+    for i in start..end {
+        df.fill_row(i, &mut row).unwrap();
+        rower.visit(&row);
+    }
+    rower
+}
+
+impl From<Column> for LocalDataFrame {
+    /// Construct a new `DataFrame` with the given `column`.
+    fn from(column: Column) -> Self {
+        LocalDataFrame::from(vec![column])
+    }
+}
+
+impl From<Vec<Column>> for LocalDataFrame {
+    /// Construct a new `DataFrame` with the given `columns`.
+    fn from(data: Vec<Column>) -> Self {
+        let mut schema = Schema::new();
+        for column in &data {
+            match &column {
+                Column::Bool(_) => {
+                    schema.add_column(DataType::Bool, None).unwrap()
+                }
+                Column::Int(_) => {
+                    schema.add_column(DataType::Int, None).unwrap()
+                }
+                Column::Float(_) => {
+                    schema.add_column(DataType::Float, None).unwrap()
+                }
+                Column::String(_) => {
+                    schema.add_column(DataType::String, None).unwrap()
+                }
+            };
+        }
+        let n_threads = num_cpus::get();
+        LocalDataFrame {
+            schema,
+            n_threads,
+            data: data.into_iter().map(Arc::new).collect(),
+            cur_row_idx: 0,
+            row_names: HashMap::new(),
+        }
+    }
+}
+
+impl From<Data> for LocalDataFrame {
+    /// Construct a new `DataFrame` with the given `scalar` value.
+    fn from(scalar: Data) -> Self {
+        let c = match scalar {
+            Data::Bool(x) => Column::Bool(vec![Some(x)]),
+            Data::Int(x) => Column::Int(vec![Some(x)]),
+            Data::Float(x) => Column::Float(vec![Some(x)]),
+            Data::String(x) => Column::String(vec![Some(x)]),
+            Data::Null => panic!("Can't make a DataFrame from a null value"),
+        };
+        LocalDataFrame::from(c)
+    }
+}
+
+impl TryFrom<Vec<Row>> for LocalDataFrame {
+    type Error = LiquidError;
+
+    /// Construct a new `DataFrame` with the given `rows`. See the
+    /// `FromIterator<Row>` implementation for details.
+    fn try_from(rows: Vec<Row>) -> Result<Self, LiquidError> {
+        rows.into_iter().collect()
+    }
+}
+
+impl From<Vec<(String, Column)>> for LocalDataFrame {
+    /// Construct a new `DataFrame` with the given named `columns`, in order.
+    /// Shorter columns are padded with nulls, as in `add_column`.
+    ///
+    /// Will panic if two columns have the same name. Use a
+    /// `DataFrameBuilder` to get an error instead.
+    fn from(columns: Vec<(String, Column)>) -> Self {
+        let mut df = LocalDataFrame::new(&Schema::new());
+        for (name, col) in columns {
+            df.add_column(col, Some(name)).unwrap();
+        }
+        df
+    }
+}
+
+impl TryFrom<Vec<HashMap<String, Data>>> for LocalDataFrame {
+    type Error = LiquidError;
+
+    /// Construct a new `DataFrame` from the given `records`, with one row
+    /// per record and one column per distinct key, sorted by name. A key
+    /// missing from a record is null in that row, and the type of each
+    /// column is the type of its first non-null value.
+    ///
+    /// # Errors
+    /// If the non-null values of a key have different types,
+    /// `LiquidError::TypeMismatch` is returned, and if a key only has null
+    /// values, so that its type is unknown, `LiquidError::InvalidArgument` is
+    /// returned.
+    fn try_from(
+        records: Vec<HashMap<String, Data>>,
+    ) -> Result<Self, LiquidError> {
+        let mut names: Vec<&String> = records
+            .iter()
+            .flat_map(|record| record.keys())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        names.sort();
+        frame_from_records(&names, &records)
+    }
+}
+
+impl From<&LocalDataFrame> for Vec<HashMap<String, Data>> {
+    /// Converts every row of the given `df` to a map from column names to
+    /// values, the opposite of `TryFrom<Vec<HashMap<String, Data>>>`. Unnamed
+    /// columns are keyed by their index, and null values are left out.
+    fn from(df: &LocalDataFrame) -> Self {
+        let names = df.col_labels();
+        (0..df.n_rows())
+            .map(|row_idx| {
+                names
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(col_idx, name)| {
+                        match df.get(col_idx, row_idx).unwrap() {
+                            Data::Null => None,
+                            value => Some((name.clone(), value)),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<Row> for Result<LocalDataFrame, LiquidError> {
+    /// Construct a new `DataFrame` from the `Row`s of the given iterator, in
+    /// order. The `Schema`, including column names, is taken from the first
+    /// `Row`. If there are no `Row`s, the `DataFrame` has no columns.
+    ///
+    /// # Errors
+    /// If any `Row` has a different `Schema` than the first one,
+    /// `LiquidError::TypeMismatch` is returned.
+    fn from_iter<I: IntoIterator<Item = Row>>(rows: I) -> Self {
+        let mut rows = rows.into_iter();
+        let mut df = match rows.next() {
+            Some(row) => {
+                let mut df = LocalDataFrame::new(&row.schema);
+                df.push_row_unchecked(&row);
+                df
+            }
+            None => return Ok(LocalDataFrame::new(&Schema::new())),
+        };
+        for row in rows {
+            df.add_row(&row)?;
+        }
+
+        Ok(df)
+    }
+}
+
+impl PartialEq for LocalDataFrame {
+    /// Two `LocalDataFrame`s are equal if they have the same `Schema`,
+    /// including the column names, and the same data. The number of threads
+    /// depends on the machine a `LocalDataFrame` was created on, so it is
+    /// not compared.
+    fn eq(&self, other: &Self) -> bool {
+        self.schema == other.schema && self.data == other.data
+    }
+}
+
+impl Index<usize> for LocalDataFrame {
+    type Output = Column;
+
+    /// Returns a reference to the `Column` at `col_idx`, e.g. `df[0]`. Use
+    /// `get` to read a single value.
+    ///
+    /// Will panic if `col_idx` is out of bounds.
+    fn index(&self, col_idx: usize) -> &Self::Output {
+        match self.column(col_idx) {
+            Some(col) => col,
+            None => panic!("column index {} is out of bounds", col_idx),
+        }
+    }
+}
+
+impl Index<&str> for LocalDataFrame {
+    type Output = Column;
+
+    /// Returns a reference to the `Column` named `name`, e.g. `df["age"]`.
+    /// Use `get_checked` to get an error instead of a panic.
+    ///
+    /// Will panic if there is no column named `name`.
+    fn index(&self, name: &str) -> &Self::Output {
+        match self.get_checked(name) {
+            Ok(col) => col,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+impl std::fmt::Display for LocalDataFrame {
+    /// Prints this `LocalDataFrame` as an aligned table with at most
+    /// `DISPLAY_MAX_ROWS` rows, as in [`to_table`]. The width of the
+    /// formatter, e.g. `{:30}`, sets the maximum width of each column, which
+    /// is `DISPLAY_MAX_COL_WIDTH` by default.
+    ///
+    /// [`to_table`]: struct.LocalDataFrame.html#method.to_table
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let max_col_width = f.width().unwrap_or(DISPLAY_MAX_COL_WIDTH);
+        write!(f, "{}", self.to_table(DISPLAY_MAX_ROWS, max_col_width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::{MapRower, Row, Rower};
+
+    #[derive(Clone)]
+    struct PosIntSummer {
+        sum: i64,
+    }
+
+    impl Rower for PosIntSummer {
+        fn visit(&mut self, r: &Row) -> bool {
+            let i = r.get(0).unwrap();
+            match i {
+                Data::Int(val) => {
+                    if *val < 0 {
+                        return false;
+                    }
+                    self.sum += *val;
+                    true
+                }
+                _ => panic!(),
+            }
+        }
+
+        fn join(mut self, other: Self) -> Self {
+            self.sum += other.sum;
+            self
+        }
+    }
+
+    fn init() -> LocalDataFrame {
+        let s = Schema::from(vec![DataType::Int]);
+        let mut r = Row::new(&s);
+        let mut df = LocalDataFrame::new(&s);
+
+        for i in 0..1000 {
+            if i % 2 == 0 {
+                r.set_int(0, i * -1).unwrap();
+            } else {
+                r.set_int(0, i).unwrap();
+            }
+            df.add_row(&r).unwrap();
+        }
+
+        df
+    }
+
+    #[test]
+    fn test_combine_err_case() {
+        let s = Schema::from(vec![DataType::Int]);
+        let df1 = LocalDataFrame::new(&s);
+        let s = Schema::from(vec![DataType::Bool]);
+        let df2 = LocalDataFrame::new(&s);
+        assert!(df1.combine(df2).is_err());
+    }
+
+    #[test]
+    fn test_combine() {
+        let s = Schema::from(vec![]);
+        let mut df1 = LocalDataFrame::new(&s);
+        let mut df2 = LocalDataFrame::new(&s);
+        let col1 = Column::Int(vec![Some(1), Some(2), Some(3)]);
+        let col2 = Column::Bool(vec![Some(false), Some(false), Some(false)]);
+        df1.add_column(col1, Some("col1".to_string())).unwrap();
+        df1.add_column(col2, None).unwrap();
+        let col3 = Column::Int(vec![Some(4), Some(5), Some(6)]);
+        let col4 = Column::Bool(vec![Some(true), Some(true), Some(true)]);
+        df2.add_column(col3, None).unwrap();
+        df2.add_column(col4, None).unwrap();
+        let res = df1.combine(df2);
+        assert!(res.is_ok());
+        let combined = res.unwrap();
+        let mut res_schema = Schema::from(vec![DataType::Int, DataType::Bool]);
+        res_schema.col_names.insert("col1".to_string(), 0);
+        assert_eq!(combined.get_schema(), &res_schema);
+        let r = PosIntSummer { sum: 0 };
+        assert_eq!(combined.map(r).sum, 21);
+    }
+
+    #[test]
+    fn test_map() {
+        let df = init();
+        let mut rower = PosIntSummer { sum: 0 };
+        rower = df.map(rower);
+        assert_eq!(1000 * 1000 / 4, rower.sum);
+        assert_eq!(1000, df.n_rows());
+    }
+
+    #[test]
+    fn test_pmap() {
+        let df = init();
+        let mut rower = PosIntSummer { sum: 0 };
+        rower = df.pmap(rower);
+        assert_eq!(1000 * 1000 / 4, rower.sum);
+        assert_eq!(1000, df.n_rows());
+    }
+
+    #[test]
+    fn test_pmap_w_1_thread() {
+        let mut df = init();
+        df.n_threads = 1;
+        let mut rower = PosIntSummer { sum: 0 };
+        rower = df.pmap(rower);
+        assert_eq!(1000 * 1000 / 4, rower.sum);
+        assert_eq!(1000, df.n_rows());
+    }
+
+    #[test]
+    fn test_filter() {
+        let df = init();
+        let mut rower = PosIntSummer { sum: 0 };
+        let df2 = df.filter(&mut rower);
+        assert_eq!(df2.n_rows(), 501);
+        assert_eq!(df2.n_cols(), 1);
+        assert_eq!(df2.get(0, 10).unwrap(), Data::Int(19));
+    }
+
+    #[test]
+    fn test_sample() {
+        let df = init();
+        let s1 = df.sample(100, 42);
+        let s2 = df.sample(100, 42);
+        assert_eq!(s1.n_rows(), 100);
+        assert_eq!(s1, s2);
+        assert_eq!(df.sample(5000, 42).n_rows(), df.n_rows());
+    }
+
+    #[test]
+    fn test_sample_frac_and_stratified() {
+        let df = init();
+        assert_eq!(df.sample_frac(0.25, 3).n_rows(), 250);
+        assert_eq!(df.sample_frac(2.0, 3).n_rows(), df.n_rows());
+        assert_eq!(df.sample_frac(-1.0, 3).n_rows(), 0);
+
+        let labels = Column::String(
+            (0..100)
+                .map(|i| Some(if i < 80 { "a" } else { "b" }.to_string()))
+                .chain(vec![None; 20])
+                .collect(),
+        );
+        let mut labeled = LocalDataFrame::from(labels);
+        labeled
+            .add_column(Column::Int((0..120).map(Some).collect()), None)
+            .unwrap();
+        let sampled = labeled.sample_stratified(0, 0.5, 9).unwrap();
+        assert_eq!(sampled, labeled.sample_stratified(0, 0.5, 9).unwrap());
+        let count = |value: Data| {
+            (0..sampled.n_rows())
+                .filter(|&i| sampled.get(0, i).unwrap() == value)
+                .count()
+        };
+        assert_eq!(count(Data::String("a".to_string())), 40);
+        assert_eq!(count(Data::String("b".to_string())), 10);
+        assert_eq!(count(Data::Null), 10);
+        let ids: Vec<i64> = (0..sampled.n_rows())
+            .map(|i| match sampled.get(1, i).unwrap() {
+                Data::Int(x) => x,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(labeled.sample_stratified(2, 0.5, 9).is_err());
+    }
+
+    #[test]
+    fn test_train_test_split() {
+        let df = init();
+        let (train, test) = df.train_test_split(0.8, 7);
+        assert_eq!(train.n_rows(), 800);
+        assert_eq!(train.n_rows() + test.n_rows(), df.n_rows());
+        let (train2, test2) = df.train_test_split(0.8, 7);
+        assert_eq!(train, train2);
+        assert_eq!(test, test2);
+    }
+
+    #[test]
+    fn test_pfilter() {
+        let df = init();
+        let mut rower = PosIntSummer { sum: 0 };
+        let df2 = df.pfilter(&mut rower);
+        assert_eq!(df2.n_rows(), 501);
+        assert_eq!(df2.n_cols(), 1);
+        assert_eq!(df2.get(0, 10).unwrap(), Data::Int(19));
+    }
+
+    fn init_join() -> (LocalDataFrame, LocalDataFrame) {
+        let mut left = LocalDataFrame::new(&Schema::new());
+        left.add_column(
+            Column::Int(vec![Some(1), Some(2), Some(3)]),
+            Some("id".to_string()),
+        )
+        .unwrap();
+        left.add_column(
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("c".to_string()),
+            ]),
+            Some("name".to_string()),
+        )
+        .unwrap();
+        let mut right = LocalDataFrame::new(&Schema::new());
+        right
+            .add_column(
+                Column::Int(vec![Some(1), Some(1), Some(3), None]),
+                Some("id".to_string()),
+            )
+            .unwrap();
+        right
+            .add_column(
+                Column::Float(vec![Some(0.1), Some(0.2), Some(0.3), Some(0.4)]),
+                Some("val".to_string()),
+            )
+            .unwrap();
+        (left, right)
+    }
+
+    #[test]
+    fn test_inner_join_one_to_many() {
+        let (left, right) = init_join();
+        let joined = left.inner_join(&right, 0, 0).unwrap();
+        assert_eq!(joined.n_rows(), 3);
+        assert_eq!(joined.n_cols(), 4);
+        assert_eq!(joined.get_col_idx("id_right"), Some(2));
+        assert_eq!(joined.get_col_idx("val"), Some(3));
+        assert_eq!(joined.get(0, 0).unwrap(), Data::Int(1));
+        assert_eq!(joined.get(3, 0).unwrap(), Data::Float(0.1));
+        assert_eq!(joined.get(0, 1).unwrap(), Data::Int(1));
+        assert_eq!(joined.get(3, 1).unwrap(), Data::Float(0.2));
+        assert_eq!(joined.get(1, 2).unwrap(), Data::String("c".to_string()));
+        assert_eq!(joined.get(3, 2).unwrap(), Data::Float(0.3));
+        assert!(left.inner_join(&right, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_inner_join_no_matches() {
+        let (left, _) = init_join();
+        let right = LocalDataFrame::from(Column::Int(vec![Some(7), Some(8)]));
+        let joined = left.inner_join(&right, 0, 0).unwrap();
+        assert_eq!(joined.n_rows(), 0);
+        assert_eq!(joined.n_cols(), 3);
+    }
+
+    #[test]
+    fn test_join_kinds() {
+        let (left, right) = init_join();
+        let inner = left.join(&right, 0, 0, JoinKind::Inner).unwrap();
+        assert_eq!(inner, left.inner_join(&right, 0, 0).unwrap());
+
+        // the left row with id 2 has no match
+        let joined = left.join(&right, 0, 0, JoinKind::Left).unwrap();
+        assert_eq!(joined.n_rows(), 4);
+        assert_eq!(joined.get_col_idx("id_right"), Some(2));
+        assert_eq!(joined.get(1, 2).unwrap(), Data::String("b".to_string()));
+        assert_eq!(joined.get(2, 2).unwrap(), Data::Null);
+        assert_eq!(joined.get(3, 2).unwrap(), Data::Null);
+        assert_eq!(joined.get(3, 3).unwrap(), Data::Float(0.3));
+
+        // the right row with a null id never matches
+        let joined = left.join(&right, 0, 0, JoinKind::Right).unwrap();
+        assert_eq!(joined.n_rows(), 4);
+        assert_eq!(joined.get(0, 3).unwrap(), Data::Null);
+        assert_eq!(joined.get(1, 3).unwrap(), Data::Null);
+        assert_eq!(joined.get(3, 3).unwrap(), Data::Float(0.4));
+
+        let joined = left.join(&right, 0, 0, JoinKind::Outer).unwrap();
+        assert_eq!(joined.n_rows(), 5);
+        assert_eq!(joined.get(1, 2).unwrap(), Data::String("b".to_string()));
+        assert_eq!(joined.get(3, 4).unwrap(), Data::Float(0.4));
+        assert!(left.join(&right, 0, 1, JoinKind::Outer).is_err());
+    }
+
+    #[test]
+    fn test_null_count() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(3), None]),
+            Column::Bool(vec![None, Some(true), Some(false), Some(true)]),
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("c".to_string()),
+                Some("d".to_string()),
+            ]),
+        ]);
+        assert_eq!(df.null_count(0).unwrap(), 2);
+        assert_eq!(df.null_count(1).unwrap(), 1);
+        assert_eq!(df.null_count(2).unwrap(), 0);
+        assert!(df.null_count(3).is_err());
+        assert_eq!(df.total_nulls(), 3);
+    }
+
+    #[test]
+    fn test_add_incompatible_row() {
+        let mut df = init();
+        let mut r = Row::new(&Schema::from(vec![DataType::String]));
+        r.set_string(0, "not an int".to_string()).unwrap();
+        assert!(df.add_row(&r).is_err());
+        assert_eq!(df.n_rows(), 1000);
+    }
+
+    struct DoubleNonNull;
+
+    impl MapRower for DoubleNonNull {
+        fn map_row(&self, row: &Row) -> Option<Row> {
+            match (row.get(0).unwrap(), row.get(1).unwrap()) {
+                (_, Data::Null) => None,
+                (Data::Int(x), _) => {
+                    let mut new_row = row.clone();
+                    new_row.set_int(0, x * 2).unwrap();
+                    Some(new_row)
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_to_frame() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), Some(3)]),
+            Column::Bool(vec![Some(true), None, Some(false)]),
+        ]);
+        let mapped = df.map_to_frame(&DoubleNonNull).unwrap();
+        assert_eq!(mapped.n_rows(), 2);
+        assert_eq!(mapped.get(0, 0).unwrap(), Data::Int(2));
+        assert_eq!(mapped.get(0, 1).unwrap(), Data::Int(6));
+        assert_eq!(mapped.get(1, 1).unwrap(), Data::Bool(false));
+    }
+
+    #[test]
+    fn test_row_names() {
+        let mut df = init();
+        df.set_row_name(3, "three".to_string()).unwrap();
+        assert_eq!(df.get_row_idx("three"), Some(3));
+        assert_eq!(df.row_name(3), Some("three"));
+        assert_eq!(
+            df.get_named_row("three").unwrap().get(0).unwrap(),
+            &Data::Int(3)
+        );
+        assert!(df.set_row_name(4, "three".to_string()).is_err());
+        assert!(df.set_row_name(1000, "oob".to_string()).is_err());
+        df.set_row_name(3, "tres".to_string()).unwrap();
+        assert_eq!(df.get_row_idx("three"), None);
+        assert_eq!(df.get_row_idx("tres"), Some(3));
+        assert_eq!(df.get_row_idx("foo"), None);
+    }
+
+    #[test]
+    fn test_count_distinct() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), None, Some(1), None, Some(3)]),
+            Column::Bool(vec![None; 6]),
+        ]);
+        assert_eq!(df.count_distinct(0).unwrap(), 3);
+        assert_eq!(df.count_distinct(1).unwrap(), 0);
+        assert!(df.count_distinct(2).is_err());
+    }
+
+    #[test]
+    fn test_set_col_names() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1)]),
+            Column::Bool(vec![Some(true)]),
+            Column::Float(vec![Some(1.5)]),
+        ]);
+        let names = |n: &[&str]| -> Vec<String> {
+            n.iter().map(|s| s.to_string()).collect()
+        };
+        assert!(df.set_col_names(names(&["a", "b"])).is_err());
+        assert!(df.set_col_names(names(&["a", "b", "a"])).is_err());
+        assert_eq!(df.get_col_idx("a"), None);
+        df.set_col_names(names(&["id", "flag", "score"])).unwrap();
+        assert_eq!(df.get_col_idx("flag"), Some(1));
+        assert_eq!(df.col_name(2).unwrap(), Some("score"));
+    }
+
+    #[test]
+    fn test_append_column_checked() {
+        let mut df = LocalDataFrame::new(&Schema::new());
+        df.append_column_checked(Column::Int(vec![Some(1), Some(2)]), None)
+            .unwrap();
+        let short = Column::Bool(vec![Some(true)]);
+        assert!(df.append_column_checked(short.clone(), None).is_err());
+        assert_eq!(df.n_cols(), 1);
+        assert_eq!(df.get_schema().width(), 1);
+        // `add_column` still pads the shorter column with nulls
+        df.add_column(short, None).unwrap();
+        assert_eq!(df.get(1, 1).unwrap(), Data::Null);
+    }
+
+    #[test]
+    fn test_pivot() {
+        let mut long = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(1), Some(2), Some(3), None]),
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("a".to_string()),
+            ]),
+            Column::Float(vec![
+                Some(1.0),
+                Some(2.0),
+                Some(3.0),
+                Some(4.0),
+                Some(5.0),
+            ]),
+        ]);
+        let wide = long.pivot(0, 1, 2).unwrap();
+        assert_eq!(wide.n_cols(), 3);
+        assert_eq!(wide.n_rows(), 3);
+        assert_eq!(wide.get_col_idx("a"), Some(1));
+        assert_eq!(wide.get_col_idx("b"), Some(2));
+        assert_eq!(
+            wide.data,
+            vec![
+                Arc::new(Column::Int(vec![Some(1), Some(2), Some(3)])),
+                Arc::new(Column::Float(vec![Some(1.0), Some(3.0), None])),
+                Arc::new(Column::Float(vec![Some(2.0), None, Some(4.0)])),
+            ]
+        );
+        assert!(long.pivot(0, 1, 3).is_err());
+
+        let mut duplicate = Row::new(long.get_schema());
+        duplicate.set_int(0, 1).unwrap();
+        duplicate.set_string(1, "a".to_string()).unwrap();
+        duplicate.set_float(2, 6.0).unwrap();
+        long.add_row(&duplicate).unwrap();
+        match long.pivot(0, 1, 2) {
+            Err(LiquidError::DuplicateEntry) => (),
+            _ => panic!("expected a duplicate (index, column) pair to error"),
+        }
+    }
+
+    #[test]
+    fn test_cast_to_schema() {
+        let ints = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(-3)]),
+            Column::Int(vec![Some(0), Some(1), Some(2)]),
+        ]);
+        let floats = ints.cast_to_schema(&Schema::from("FF")).unwrap();
+        assert_eq!(floats.get_schema().schema, Schema::from("FF").schema);
+        assert_eq!(
+            floats.data,
+            vec![
+                Arc::new(Column::Float(vec![Some(1.0), None, Some(-3.0)])),
+                Arc::new(Column::Float(vec![Some(0.0), Some(1.0), Some(2.0)])),
+            ]
+        );
+        assert_eq!(floats.cast_to_schema(&Schema::from("II")).unwrap(), ints);
+        assert!(ints.cast_to_schema(&Schema::from("F")).is_err());
+        match ints.cast_to_schema(&Schema::from("IB")) {
+            Err(LiquidError::InvalidCast(1)) => (),
+            _ => panic!("expected column 1 to fail to cast to a Bool"),
+        }
+    }
+
+    #[test]
+    fn test_drop_nulls() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(3), Some(4)]),
+            Column::Bool(vec![Some(true), Some(false), None, Some(false)]),
+            Column::String(vec![
+                None,
+                Some("b".to_string()),
+                Some("c".to_string()),
+                Some("d".to_string()),
+            ]),
+        ]);
+
+        let complete = df.drop_nulls();
+        assert_eq!(complete.n_rows(), 1);
+        assert_eq!(complete.get(0, 0).unwrap(), Data::Int(4));
+
+        let subset = df.drop_nulls_subset(&[0, 1]).unwrap();
+        assert_eq!(subset.n_rows(), 2);
+        assert_eq!(subset.get(0, 0).unwrap(), Data::Int(1));
+        assert_eq!(subset.get(2, 0).unwrap(), Data::Null);
+        assert_eq!(subset.get(0, 1).unwrap(), Data::Int(4));
+
+        assert_eq!(df.drop_nulls_subset(&[]).unwrap().n_rows(), 4);
+        assert!(df.drop_nulls_subset(&[3]).is_err());
+    }
+
+    #[test]
+    fn test_corr() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), Some(3), None, Some(4)]),
+            Column::Float(vec![
+                Some(2.0),
+                Some(4.0),
+                Some(6.0),
+                Some(1.0),
+                Some(8.0),
+            ]),
+            Column::Float(vec![
+                Some(-1.0),
+                Some(-2.0),
+                Some(-3.0),
+                Some(-0.5),
+                Some(-4.0),
+            ]),
+            Column::Int(vec![Some(5), Some(5), Some(5), Some(5), Some(5)]),
+            Column::Bool(vec![Some(true); 5]),
+        ]);
+        assert!((df.corr(0, 1).unwrap() - 1.0).abs() < 1e-12);
+        assert!((df.corr(0, 2).unwrap() + 1.0).abs() < 1e-12);
+        assert!((df.corr(1, 2).unwrap() + 1.0).abs() < 1e-12);
+        assert!(df.corr(0, 3).unwrap().is_nan());
+        assert!(matches!(df.corr(0, 4), Err(LiquidError::TypeMismatch)));
+        assert!(matches!(
+            df.corr(0, 5),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_rolling() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), None, Some(4), Some(5)]),
+            Column::String(vec![None; 5]),
+        ]);
+        assert_eq!(
+            df.rolling(0, 3, Agg::Sum).unwrap(),
+            Column::Int(vec![None, None, Some(3), Some(6), Some(9)])
+        );
+        assert_eq!(
+            df.rolling(0, 3, Agg::Mean).unwrap(),
+            Column::Float(vec![None, None, Some(1.5), Some(3.0), Some(4.5)])
+        );
+        assert_eq!(
+            df.rolling(0, 2, Agg::Max).unwrap(),
+            Column::Int(vec![None, Some(2), Some(2), Some(4), Some(5)])
+        );
+        assert_eq!(
+            df.rolling(0, 2, Agg::Count).unwrap(),
+            Column::Int(vec![None, Some(2), Some(1), Some(1), Some(2)])
+        );
+        assert!(matches!(
+            df.rolling(0, 0, Agg::Sum),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            df.rolling(1, 2, Agg::Sum),
+            Err(LiquidError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_columns_are_copy_on_write() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2)]),
+            Column::Float(vec![Some(1.0), Some(2.0)]),
+        ]);
+        let mut copy = df.clone();
+        assert_eq!(Arc::strong_count(&df.data[0]), 2);
+        assert!(Arc::ptr_eq(&df.data[1], &copy.data[1]));
+
+        // mutating the copy only clones the mutated column
+        copy.set_int(0, 0, 10).unwrap();
+        assert_eq!(copy.get(0, 0).unwrap(), Data::Int(10));
+        assert_eq!(df.get(0, 0).unwrap(), Data::Int(1));
+        assert_eq!(Arc::strong_count(&df.data[0]), 1);
+        assert!(Arc::ptr_eq(&df.data[1], &copy.data[1]));
+
+        // casting a column to its own type shares it
+        let cast = df.cast_to_schema(&Schema::from("IB")).unwrap_err();
+        assert!(matches!(cast, LiquidError::InvalidCast(1)));
+        let cast = df.cast_to_schema(&Schema::from("IS")).unwrap();
+        assert!(Arc::ptr_eq(&df.data[0], &cast.data[0]));
+    }
+
+    #[test]
+    fn test_hash_partition() {
+        let keys = vec![Some(1), Some(2), None, Some(1), Some(3), Some(2)];
+        let df = LocalDataFrame::from(vec![
+            Column::Int(keys.clone()),
+            Column::Int((0..6).map(Some).collect()),
+        ]);
+        let partitions = df.hash_partition(0, 3).unwrap();
+        assert_eq!(partitions.len(), 3);
+        assert_eq!(partitions.iter().map(|p| p.n_rows()).sum::<usize>(), 6);
+
+        // every row appears in exactly one partition, and equal keys are
+        // always in the same partition
+        let mut seen = Vec::new();
+        let mut key_partition = HashMap::new();
+        for (i, partition) in partitions.iter().enumerate() {
+            for row_idx in 0..partition.n_rows() {
+                seen.push(partition.get(1, row_idx).unwrap());
+                let key = partition.get(0, row_idx).unwrap();
+                if key == Data::Null {
+                    assert_eq!(i, 0);
+                } else if let Some(j) = key_partition.insert(key.to_string(), i)
+                {
+                    assert_eq!(i, j);
+                }
+            }
         }
+        seen.sort_by_key(|d| match d {
+            Data::Int(x) => *x,
+            _ => unreachable!(),
+        });
+        assert_eq!(seen, (0..6).map(Data::Int).collect::<Vec<_>>());
+        assert!(df.hash_partition(0, 0).is_err());
+        assert!(df.hash_partition(2, 1).is_err());
+    }
 
-        df
+    #[test]
+    fn test_assert_schema() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1)]),
+            Column::String(vec![Some("a".to_string())]),
+        ]);
+        df.set_col_names(vec!["id".to_string(), "name".to_string()])
+            .unwrap();
+        assert!(df.assert_schema(&Schema::from("IS")).is_ok());
+
+        let mut named = Schema::new();
+        named.add_column(DataType::Int, None).unwrap();
+        named
+            .add_column(DataType::String, Some("name".to_string()))
+            .unwrap();
+        assert!(df.assert_schema(&named).is_ok());
+
+        let mut renamed = Schema::new();
+        renamed
+            .add_column(DataType::Int, Some("user".to_string()))
+            .unwrap();
+        renamed.add_column(DataType::String, None).unwrap();
+        assert!(matches!(
+            df.assert_schema(&renamed),
+            Err(LiquidError::ColumnMismatch(0))
+        ));
+        assert!(matches!(
+            df.assert_schema(&Schema::from("IF")),
+            Err(LiquidError::ColumnMismatch(1))
+        ));
+        assert!(matches!(
+            df.assert_schema(&Schema::from("I")),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
     }
 
     #[test]
-    fn test_combine_err_case() {
-        let s = Schema::from(vec![DataType::Int]);
-        let df1 = LocalDataFrame::new(&s);
-        let s = Schema::from(vec![DataType::Bool]);
-        let df2 = LocalDataFrame::new(&s);
-        assert!(df1.combine(df2).is_err());
+    fn test_unique_by() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), Some(1), None, None, Some(2)]),
+            Column::Int((0..6).map(Some).collect()),
+        ]);
+        let unique = df.unique_by(&[0]).unwrap();
+        assert_eq!(unique.n_rows(), 3);
+        assert_eq!(*unique.data[0], Column::Int(vec![Some(1), Some(2), None]));
+        assert_eq!(
+            *unique.data[1],
+            Column::Int(vec![Some(0), Some(1), Some(3)])
+        );
+
+        // every row is unique across both columns
+        assert_eq!(df.unique_by(&[0, 1]).unwrap().n_rows(), 6);
+        assert_eq!(df.unique_by(&[]).unwrap().n_rows(), 1);
+        assert!(df.unique_by(&[2]).is_err());
     }
 
     #[test]
-    fn test_combine() {
-        let s = Schema::from(vec![]);
-        let mut df1 = LocalDataFrame::new(&s);
-        let mut df2 = LocalDataFrame::new(&s);
-        let col1 = Column::Int(vec![Some(1), Some(2), Some(3)]);
-        let col2 = Column::Bool(vec![Some(false), Some(false), Some(false)]);
-        df1.add_column(col1, Some("col1".to_string())).unwrap();
-        df1.add_column(col2, None).unwrap();
-        let col3 = Column::Int(vec![Some(4), Some(5), Some(6)]);
-        let col4 = Column::Bool(vec![Some(true), Some(true), Some(true)]);
-        df2.add_column(col3, None).unwrap();
-        df2.add_column(col4, None).unwrap();
-        let res = df1.combine(df2);
-        assert!(res.is_ok());
-        let combined = res.unwrap();
-        let mut res_schema = Schema::from(vec![DataType::Int, DataType::Bool]);
-        res_schema.col_names.insert("col1".to_string(), 0);
-        assert_eq!(combined.get_schema(), &res_schema);
-        let r = PosIntSummer { sum: 0 };
-        assert_eq!(combined.map(r).sum, 21);
+    fn test_pmap_empty() {
+        let df = LocalDataFrame::new(&Schema::from("I"));
+        let rower = df.pmap(PosIntSummer { sum: 5 });
+        assert_eq!(rower.sum, 5);
+
+        // fewer rows than threads
+        let df = LocalDataFrame::from(Column::Int(vec![Some(1), Some(2)]));
+        let rower = df.pmap(PosIntSummer { sum: 5 });
+        assert_eq!(rower.sum, 8);
     }
 
     #[test]
-    fn test_map() {
-        let df = init();
-        let mut rower = PosIntSummer { sum: 0 };
-        rower = df.map(rower);
-        assert_eq!(1000 * 1000 / 4, rower.sum);
-        assert_eq!(1000, df.n_rows());
+    fn test_append_rows_from() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(0)]),
+            Column::String(vec![None]),
+        ]);
+        let other = LocalDataFrame::from(vec![
+            Column::Int((1..=5).map(Some).collect()),
+            Column::String(
+                ["a", "b", "c", "d", "e"]
+                    .iter()
+                    .map(|s| Some(s.to_string()))
+                    .collect(),
+            ),
+        ]);
+        df.append_rows_from(&other, 1..4).unwrap();
+        assert_eq!(df.n_rows(), 4);
+        assert_eq!(
+            *df.data[0],
+            Column::Int(vec![Some(0), Some(2), Some(3), Some(4)])
+        );
+        assert_eq!(df.get(1, 3).unwrap(), Data::String("d".to_string()));
+
+        df.append_rows_from(&other, 5..5).unwrap();
+        assert_eq!(df.n_rows(), 4);
+        assert!(df.append_rows_from(&other, 3..6).is_err());
+        let ints = LocalDataFrame::from(Column::Int(vec![Some(1)]));
+        assert!(matches!(
+            df.append_rows_from(&ints, 0..1),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert_eq!(df.n_rows(), 4);
     }
 
     #[test]
-    fn test_pmap() {
+    fn test_value_counts() {
+        let words = vec![
+            Some("b"),
+            Some("a"),
+            None,
+            Some("a"),
+            Some("c"),
+            Some("b"),
+            Some("a"),
+        ];
+        let df = LocalDataFrame::from(Column::String(
+            words.iter().map(|w| w.map(String::from)).collect(),
+        ));
+        let counts = df.value_counts(0).unwrap();
+        assert_eq!(counts.get_col_idx("value"), Some(0));
+        assert_eq!(counts.get_col_idx("count"), Some(1));
+        assert_eq!(
+            *counts.data[0],
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                None,
+                Some("c".to_string()),
+            ])
+        );
+        assert_eq!(
+            *counts.data[1],
+            Column::Int(vec![Some(3), Some(2), Some(1), Some(1)])
+        );
+        assert!(df.value_counts(1).is_err());
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let schema = Schema::from("IS");
+        let rows = (0..3).map(|i| {
+            let mut row = Row::new(&schema);
+            row.set_int(0, i).unwrap();
+            if i != 1 {
+                row.set_string(1, i.to_string()).unwrap();
+            }
+            row
+        });
+        let df: LocalDataFrame = rows
+            .collect::<Result<LocalDataFrame, LiquidError>>()
+            .unwrap();
+        assert_eq!(df.n_rows(), 3);
+        assert_eq!(*df.data[0], Column::Int(vec![Some(0), Some(1), Some(2)]));
+        assert_eq!(
+            *df.data[1],
+            Column::String(vec![
+                Some("0".to_string()),
+                None,
+                Some("2".to_string())
+            ])
+        );
+
+        let empty = LocalDataFrame::try_from(Vec::<Row>::new()).unwrap();
+        assert_eq!(empty.n_cols(), 0);
+
+        let other = Row::new(&Schema::from("F"));
+        let mismatched = vec![Row::new(&schema), other];
+        assert!(matches!(
+            LocalDataFrame::try_from(mismatched),
+            Err(LiquidError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int((0..100).map(Some).collect()),
+            Column::String(
+                (0..100)
+                    .map(|i| Some(format!("a long string {}", i)))
+                    .collect(),
+            ),
+        ]);
+        let usage = df.memory_usage();
+        assert_eq!(usage.len(), 2);
+        assert!(usage[0] >= 100 * std::mem::size_of::<Option<i64>>());
+        assert!(usage[1] > usage[0]);
+        assert_eq!(df.total_memory_usage(), usage[0] + usage[1]);
+    }
+
+    #[test]
+    fn test_map_columns_parallel() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(-3)]),
+            Column::Float(vec![Some(1.5), Some(-2.0), None]),
+            Column::String(vec![Some("a".to_string()), None, None]),
+        ]);
+        df.n_threads = 2;
+        let negated = df
+            .map_columns_parallel(|_, col| match col {
+                Column::Int(c) => {
+                    Column::Int(c.iter().map(|x| x.map(|x| -x)).collect())
+                }
+                Column::Float(c) => {
+                    Column::Float(c.iter().map(|x| x.map(|x| -x)).collect())
+                }
+                _ => col.clone(),
+            })
+            .unwrap();
+        assert_eq!(negated.get_schema(), df.get_schema());
+        assert_eq!(
+            *negated.data[0],
+            Column::Int(vec![Some(-1), None, Some(3)])
+        );
+        assert_eq!(
+            *negated.data[1],
+            Column::Float(vec![Some(-1.5), Some(2.0), None])
+        );
+        assert_eq!(negated.data[2], df.data[2]);
+
+        // changing the type or length of a column is an error
+        let to_bool =
+            |_: usize, col: &Column| Column::Bool(vec![None; col.len()]);
+        assert!(matches!(
+            df.map_columns_parallel(to_bool),
+            Err(LiquidError::ColumnMismatch(0))
+        ));
+        let shorten = |_: usize, col: &Column| match col {
+            Column::Int(c) => Column::Int(c[1..].to_vec()),
+            _ => col.clone(),
+        };
+        assert!(matches!(
+            df.map_columns_parallel(shorten),
+            Err(LiquidError::RowIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_explode() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), Some(3)]),
+            Column::String(vec![
+                Some("red,blue".to_string()),
+                None,
+                Some("green".to_string()),
+            ]),
+            Column::Bool(vec![Some(true), Some(false), None]),
+        ]);
+        let exploded = df.explode(1, ',').unwrap();
+        assert_eq!(exploded.get_schema(), df.get_schema());
+        assert_eq!(
+            *exploded.data[0],
+            Column::Int(vec![Some(1), Some(1), Some(2), Some(3)])
+        );
+        assert_eq!(
+            *exploded.data[1],
+            Column::String(vec![
+                Some("red".to_string()),
+                Some("blue".to_string()),
+                None,
+                Some("green".to_string()),
+            ])
+        );
+        assert_eq!(
+            *exploded.data[2],
+            Column::Bool(vec![Some(true), Some(true), Some(false), None])
+        );
+
+        assert!(matches!(df.explode(0, ','), Err(LiquidError::TypeMismatch)));
+        assert!(matches!(
+            df.explode(3, ','),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_concat_union() {
+        let mut a = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2)]),
+            Column::String(vec![Some("a".to_string()), None]),
+        ]);
+        a.schema
+            .set_col_names(vec!["id".to_string(), "name".to_string()])
+            .unwrap();
+        let mut b = LocalDataFrame::from(vec![
+            Column::Float(vec![Some(0.5)]),
+            Column::Int(vec![Some(3)]),
+            Column::String(vec![Some("c".to_string())]),
+        ]);
+        b.schema
+            .set_col_names(vec![
+                "score".to_string(),
+                "id".to_string(),
+                "name".to_string(),
+            ])
+            .unwrap();
+
+        let union = a.concat_union(&b).unwrap();
+        assert_eq!(union.n_rows(), 3);
+        assert_eq!(union.n_cols(), 3);
+        assert_eq!(union.get_schema().col_idx("id"), Some(0));
+        assert_eq!(union.get_schema().col_idx("name"), Some(1));
+        assert_eq!(union.get_schema().col_idx("score"), Some(2));
+        assert_eq!(
+            *union.data[0],
+            Column::Int(vec![Some(1), Some(2), Some(3)])
+        );
+        assert_eq!(
+            *union.data[1],
+            Column::String(vec![
+                Some("a".to_string()),
+                None,
+                Some("c".to_string())
+            ])
+        );
+        // `a` has no score, so its rows are null
+        assert_eq!(*union.data[2], Column::Float(vec![None, None, Some(0.5)]));
+
+        // the other way around, `b` has no missing columns
+        let union = b.concat_union(&a).unwrap();
+        assert_eq!(union.n_cols(), 3);
+        assert_eq!(*union.data[0], Column::Float(vec![Some(0.5), None, None]));
+
+        // shared names must have the same type
+        b.schema
+            .set_col_names(vec![
+                "id".to_string(),
+                "other".to_string(),
+                "name".to_string(),
+            ])
+            .unwrap();
+        assert!(matches!(
+            a.concat_union(&b),
+            Err(LiquidError::ColumnMismatch(0))
+        ));
+    }
+
+    #[test]
+    fn test_iter_typed_cols() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(3), None, Some(-1), Some(10)]),
+            Column::Float(vec![Some(0.5), Some(1.5), None, None]),
+            Column::Bool(vec![None, Some(true), Some(false), None]),
+            Column::String(vec![
+                Some("a".to_string()),
+                None,
+                Some("b".to_string()),
+                None,
+            ]),
+        ]);
+        let sum: i64 = df.iter_int_col(0).unwrap().flatten().sum();
+        let expected: i64 = (0..df.n_rows())
+            .filter_map(|row_idx| match df.get(0, row_idx).unwrap() {
+                Data::Int(x) => Some(x),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(sum, expected);
+        assert_eq!(sum, 12);
+        assert_eq!(df.iter_float_col(1).unwrap().flatten().sum::<f64>(), 2.0);
+        assert_eq!(
+            df.iter_bool_col(2).unwrap().collect::<Vec<_>>(),
+            vec![None, Some(true), Some(false), None]
+        );
+        assert_eq!(
+            df.iter_string_col(3).unwrap().collect::<Vec<_>>(),
+            vec![Some("a"), None, Some("b"), None]
+        );
+
+        assert!(matches!(df.iter_int_col(1), Err(LiquidError::TypeMismatch)));
+        assert!(matches!(
+            df.iter_string_col(4),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_to_rows() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(3)]),
+            Column::Bool(vec![None, Some(true), Some(false)]),
+            Column::String(vec![Some("a".to_string()), None, None]),
+        ]);
+        df.set_col_names(vec![
+            "x".to_string(),
+            "y".to_string(),
+            "z".to_string(),
+        ])
+        .unwrap();
+        let rows = df.to_rows();
+        assert_eq!(rows.len(), df.n_rows());
+        assert_eq!(rows[0].get(0).unwrap(), &Data::Int(1));
+        assert_eq!(rows[1].get(1).unwrap(), &Data::Bool(true));
+        assert_eq!(rows[2].get(2).unwrap(), &Data::Null);
+
+        let round_trip = rows
+            .into_iter()
+            .collect::<Result<LocalDataFrame, LiquidError>>()
+            .unwrap();
+        assert_eq!(round_trip, df);
+        assert!(LocalDataFrame::new(&Schema::from("IB"))
+            .to_rows()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_set_row() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), Some(3)]),
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("c".to_string()),
+            ]),
+            Column::Float(vec![Some(0.5), None, Some(1.5)]),
+        ]);
+        let mut row = Row::new(df.get_schema());
+        row.set_int(0, 20).unwrap();
+        row.set_float(2, 2.5).unwrap();
+        df.set_row(1, &row).unwrap();
+        assert_eq!(*df.data[0], Column::Int(vec![Some(1), Some(20), Some(3)]));
+        assert_eq!(
+            *df.data[1],
+            Column::String(vec![
+                Some("a".to_string()),
+                None,
+                Some("c".to_string())
+            ])
+        );
+        assert_eq!(
+            *df.data[2],
+            Column::Float(vec![Some(0.5), Some(2.5), Some(1.5)])
+        );
+
+        assert!(matches!(
+            df.set_row(3, &row),
+            Err(LiquidError::RowIndexOutOfBounds)
+        ));
+        let other = Row::new(&Schema::from("ISB"));
+        assert!(matches!(
+            df.set_row(0, &other),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert_eq!(df.n_rows(), 3);
+    }
+
+    #[test]
+    fn test_clip() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(-10), None, Some(3), Some(42)]),
+            Column::Float(vec![Some(-0.5), Some(0.25), None, Some(9.0)]),
+            Column::Bool(vec![None, None, None, None]),
+        ]);
+        assert_eq!(
+            df.clip(0, -1.6, 5.2).unwrap(),
+            Column::Int(vec![Some(-2), None, Some(3), Some(5)])
+        );
+        assert_eq!(
+            df.clip(1, 0.0, 1.0).unwrap(),
+            Column::Float(vec![Some(0.0), Some(0.25), None, Some(1.0)])
+        );
+
+        assert!(matches!(
+            df.clip(2, 0.0, 1.0),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(matches!(
+            df.clip(3, 0.0, 1.0),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+        assert!(matches!(
+            df.clip(1, 1.0, 0.0),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_filter_rows() {
         let df = init();
-        let mut rower = PosIntSummer { sum: 0 };
-        rower = df.pmap(rower);
-        assert_eq!(1000 * 1000 / 4, rower.sum);
-        assert_eq!(1000, df.n_rows());
+        let df2 = df.filter_rows(|row| match row.get(0).unwrap() {
+            Data::Int(x) => *x % 2 == 1,
+            _ => false,
+        });
+        assert_eq!(df2.n_rows(), 500);
+        assert_eq!(df2.get_schema(), df.get_schema());
+        assert_eq!(df2.get(0, 10).unwrap(), Data::Int(21));
+        assert_eq!(df.filter_rows(|_| false).n_rows(), 0);
     }
 
     #[test]
-    fn test_pmap_w_1_thread() {
+    fn test_group_by() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("a".to_string()),
+                None,
+                Some("b".to_string()),
+            ]),
+            Column::Int(vec![Some(1), Some(2), Some(3), Some(4), None]),
+            Column::Float(vec![Some(1.0), Some(4.0), Some(3.0), None, None]),
+        ]);
+        df.set_col_names(vec![
+            "k".to_string(),
+            "x".to_string(),
+            "y".to_string(),
+        ])
+        .unwrap();
+        df.n_threads = 2;
+        let grouped = df
+            .group_by(
+                &[0],
+                &[
+                    (1, Agg::Sum),
+                    (1, Agg::Count),
+                    (2, Agg::Mean),
+                    (2, Agg::Max),
+                    (0, Agg::Count),
+                ],
+            )
+            .unwrap();
+        assert_eq!(grouped.n_rows(), 3);
+        assert_eq!(grouped.n_cols(), 6);
+        assert_eq!(grouped.get_col_idx("x_sum"), Some(1));
+        assert_eq!(grouped.get_col_idx("y_mean"), Some(3));
+        assert_eq!(
+            *grouped.data[0],
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                None
+            ])
+        );
+        assert_eq!(
+            *grouped.data[1],
+            Column::Int(vec![Some(4), Some(2), Some(4)])
+        );
+        assert_eq!(
+            *grouped.data[2],
+            Column::Int(vec![Some(2), Some(1), Some(1)])
+        );
+        assert_eq!(
+            *grouped.data[3],
+            Column::Float(vec![Some(2.0), Some(4.0), None])
+        );
+        assert_eq!(
+            *grouped.data[4],
+            Column::Float(vec![Some(3.0), Some(4.0), None])
+        );
+        assert_eq!(
+            *grouped.data[5],
+            Column::Int(vec![Some(2), Some(2), Some(0)])
+        );
+
+        let big = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(0), Some(0)]),
+            Column::Int(vec![Some(i64::MAX), Some(1)]),
+        ]);
+        let wrapped = big.group_by(&[0], &[(1, Agg::Sum)]).unwrap();
+        assert_eq!(wrapped.get(1, 0).unwrap(), Data::Int(i64::MIN));
+
+        assert!(matches!(
+            df.group_by(&[0], &[(0, Agg::Sum)]),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(matches!(
+            df.group_by(&[3], &[]),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_select() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2)]),
+            Column::Bool(vec![Some(true), None]),
+            Column::String(vec![Some("a".to_string()), None]),
+            Column::Float(vec![None, Some(2.5)]),
+        ]);
+        df.set_col_names(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+            "d".to_string(),
+        ])
+        .unwrap();
+        let selected = df.select(&["d", "a"]).unwrap();
+        assert_eq!(selected.n_cols(), 2);
+        assert_eq!(selected.n_rows(), 2);
+        assert_eq!(
+            selected.get_schema().schema,
+            vec![DataType::Float, DataType::Int]
+        );
+        assert_eq!(selected.get_col_idx("a"), Some(1));
+        assert_eq!(selected.get(0, 1).unwrap(), Data::Float(2.5));
+        // the columns are shared, not copied
+        assert!(Arc::ptr_eq(&selected.data[0], &df.data[3]));
+        assert_eq!(
+            df.select_idx(&[0, 3]).unwrap(),
+            df.select(&["a", "d"]).unwrap()
+        );
+
+        assert!(matches!(
+            df.select(&["e"]),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            df.select_idx(&[4]),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+        assert!(matches!(
+            df.select_idx(&[0, 0]),
+            Err(LiquidError::NameAlreadyExists)
+        ));
+    }
+
+    #[test]
+    fn test_concat_and_hstack() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2)]),
+            Column::String(vec![Some("a".to_string()), None]),
+        ]);
+        df.set_col_names(vec!["x".to_string(), "y".to_string()])
+            .unwrap();
+        let other = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(3)]),
+            Column::String(vec![Some("c".to_string())]),
+        ]);
+        let concatenated = df.concat(&other).unwrap();
+        assert_eq!(concatenated.n_rows(), 3);
+        assert_eq!(concatenated.get_col_idx("y"), Some(1));
+        assert_eq!(concatenated.get(0, 2).unwrap(), Data::Int(3));
+        assert_eq!(df.n_rows(), 2);
+        assert!(matches!(
+            df.concat(&LocalDataFrame::from(Column::Int(vec![Some(1)]))),
+            Err(LiquidError::TypeMismatch)
+        ));
+
+        let mut right =
+            LocalDataFrame::from(Column::Float(vec![None, Some(0.5)]));
+        right.set_col_names(vec!["z".to_string()]).unwrap();
+        let stacked = df.hstack(&right).unwrap();
+        assert_eq!(stacked.n_cols(), 3);
+        assert_eq!(stacked.n_rows(), 2);
+        assert_eq!(stacked.get_col_idx("z"), Some(2));
+        assert_eq!(stacked.get(2, 1).unwrap(), Data::Float(0.5));
+        assert!(Arc::ptr_eq(&stacked.data[2], &right.data[0]));
+        assert!(matches!(
+            df.hstack(&other),
+            Err(LiquidError::RowIndexOutOfBounds)
+        ));
+        assert!(matches!(
+            df.hstack(&df),
+            Err(LiquidError::NameAlreadyExists)
+        ));
+    }
+
+    #[test]
+    fn test_arith_and_compare() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(6), Some(i64::MAX), None, Some(3)]),
+            Column::Int(vec![Some(2), Some(1), Some(1), Some(0)]),
+            Column::Float(vec![Some(0.5), Some(1.0), Some(2.0), None]),
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                None,
+                Some("a".to_string()),
+            ]),
+        ]);
+        assert_eq!(
+            df.arith(0, 1, ArithOp::Div).unwrap(),
+            Column::Int(vec![Some(3), Some(i64::MAX), None, None])
+        );
+        assert_eq!(
+            df.arith(0, 1, ArithOp::Add).unwrap(),
+            Column::Int(vec![Some(8), None, None, Some(3)])
+        );
+        assert_eq!(
+            df.arith(1, 2, ArithOp::Mul).unwrap(),
+            Column::Float(vec![Some(1.0), Some(1.0), Some(2.0), None])
+        );
+        assert_eq!(
+            df.arith_scalar(1, ArithOp::Sub, &Data::Int(1)).unwrap(),
+            Column::Int(vec![Some(1), Some(0), Some(0), Some(-1)])
+        );
+        assert_eq!(
+            df.arith_scalar(2, ArithOp::Div, &Data::Float(0.5)).unwrap(),
+            Column::Float(vec![Some(1.0), Some(2.0), Some(4.0), None])
+        );
+        assert_eq!(
+            df.arith_scalar(1, ArithOp::Mul, &Data::Float(0.5)).unwrap(),
+            Column::Float(vec![Some(1.0), Some(0.5), Some(0.5), Some(0.0)])
+        );
+        assert_eq!(
+            df.compare_scalar(2, CmpOp::Gt, &Data::Int(1)).unwrap(),
+            Column::Bool(vec![Some(false), Some(false), Some(true), None])
+        );
+        assert!(matches!(
+            df.arith(0, 3, ArithOp::Add),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(matches!(
+            df.arith(0, 4, ArithOp::Add),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+
+        assert_eq!(
+            df.compare(1, 2, CmpOp::Gt).unwrap(),
+            Column::Bool(vec![Some(true), Some(false), Some(false), None])
+        );
+        assert_eq!(
+            df.compare_scalar(0, CmpOp::Le, &Data::Int(6)).unwrap(),
+            Column::Bool(vec![Some(true), Some(false), None, Some(true)])
+        );
+        assert_eq!(
+            df.compare_scalar(3, CmpOp::Eq, &Data::String("a".to_string()))
+                .unwrap(),
+            Column::Bool(vec![Some(true), Some(false), None, Some(true)])
+        );
+        assert!(matches!(
+            df.compare_scalar(3, CmpOp::Eq, &Data::Int(1)),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(matches!(
+            df.compare_scalar(0, CmpOp::Eq, &Data::Null),
+            Err(LiquidError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_summary() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(2), None, Some(4), Some(4), Some(5)]),
+            Column::Float(vec![None, Some(1.5), None, None, None]),
+            Column::Float(vec![None; 5]),
+            Column::String(vec![Some("a".to_string()), None, None, None, None]),
+        ]);
+        let summary = df.summary(0).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.sum, Data::Int(15));
+        assert_eq!(summary.mean, Some(3.75));
+        assert_eq!(summary.min, Some(Data::Int(2)));
+        assert_eq!(summary.max, Some(Data::Int(5)));
+        let var = 1.583_333_333_333_333_f64;
+        assert!((summary.var.unwrap() - var).abs() < 1e-9);
+        assert!((summary.std.unwrap() - var.sqrt()).abs() < 1e-9);
+        assert_eq!(df.sum(0).unwrap(), Data::Int(15));
+        assert_eq!(df.max(0).unwrap(), Some(Data::Int(5)));
+        assert_eq!(df.sum(1).unwrap(), Data::Float(1.5));
+        assert_eq!(df.min(1).unwrap(), Some(Data::Float(1.5)));
+        // Int sums don't lose precision above 2^53
+        let big =
+            LocalDataFrame::from(Column::Int(vec![Some(1 << 60), Some(1)]));
+        assert_eq!(big.sum(0).unwrap(), Data::Int((1 << 60) + 1));
+
+        // a single value has no variance, and no values have no mean
+        assert_eq!(df.mean(1).unwrap(), Some(1.5));
+        assert_eq!(df.var(1).unwrap(), None);
+        assert_eq!(df.sum(2).unwrap(), Data::Float(0.0));
+        assert_eq!(df.min(2).unwrap(), None);
+        assert_eq!(df.std(2).unwrap(), None);
+
+        assert_eq!(df.count(0).unwrap(), 4);
+        assert_eq!(df.count(3).unwrap(), 1);
+        assert!(matches!(df.summary(3), Err(LiquidError::TypeMismatch)));
+        assert!(matches!(df.count(4), Err(LiquidError::ColIndexOutOfBounds)));
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(4), Some(1), None, Some(3), Some(2)]),
+            Column::Float(vec![None, None, Some(2.5), None, None]),
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("a".to_string()),
+                None,
+                None,
+            ]),
+        ]);
+        df.set_col_names(vec![
+            "x".to_string(),
+            "y".to_string(),
+            "s".to_string(),
+        ])
+        .unwrap();
+        let described = df.describe();
+        assert_eq!(described.n_rows(), 10);
+        assert_eq!(described.n_cols(), 3);
+        assert_eq!(described.get_col_idx("x"), Some(0));
+        assert_eq!(described.get_col_idx("s"), Some(2));
+        let stat = |col_idx, name| {
+            described
+                .get(col_idx, described.get_row_idx(name).unwrap())
+                .unwrap()
+        };
+
+        assert_eq!(stat(0, "count"), Data::Float(4.0));
+        assert_eq!(stat(0, "null_count"), Data::Float(1.0));
+        assert_eq!(stat(0, "distinct"), Data::Null);
+        assert_eq!(stat(0, "mean"), Data::Float(2.5));
+        match stat(0, "std") {
+            Data::Float(std) => {
+                assert!((std - df.std(0).unwrap().unwrap()).abs() < 1e-9)
+            }
+            other => panic!("unexpected std {:?}", other),
+        }
+        assert_eq!(stat(0, "min"), Data::Float(1.0));
+        assert_eq!(stat(0, "25%"), Data::Float(1.0));
+        assert_eq!(stat(0, "50%"), Data::Float(2.0));
+        assert_eq!(stat(0, "75%"), Data::Float(3.0));
+        assert_eq!(stat(0, "max"), Data::Float(4.0));
+
+        // a single value has no standard deviation
+        assert_eq!(stat(1, "mean"), Data::Float(2.5));
+        assert_eq!(stat(1, "std"), Data::Null);
+        assert_eq!(stat(1, "50%"), Data::Float(2.5));
+
+        assert_eq!(stat(2, "count"), Data::Float(3.0));
+        assert_eq!(stat(2, "null_count"), Data::Float(2.0));
+        assert_eq!(stat(2, "distinct"), Data::Float(2.0));
+        assert_eq!(stat(2, "mean"), Data::Null);
+        assert_eq!(stat(2, "max"), Data::Null);
+    }
+
+    #[test]
+    fn test_quantile_sketch_compacts() {
+        let mut sketch = QuantileSketch::default();
+        let mut other = QuantileSketch::default();
+        for x in (0..100_000).rev() {
+            sketch.add(x as f64);
+            other.add((x + 100_000) as f64);
+        }
+        sketch.merge(other);
+        assert!(sketch
+            .levels
+            .iter()
+            .all(|level| { level.len() < 2 * QUANTILE_SKETCH_CAPACITY }));
+        let total: usize = sketch
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(i, l)| l.len() << i)
+            .sum();
+        assert_eq!(total, 200_000);
+        for &(q, expected) in &[(0.25, 50_000.0), (0.5, 100_000.0)] {
+            let estimate = sketch.quantile(q).unwrap();
+            assert!((estimate - expected).abs() < 1_000.0, "{}", estimate);
+        }
+    }
+
+    #[test]
+    fn test_unique() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), Some(1), Some(1), None, None]),
+            Column::Bool(vec![
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(false),
+                None,
+                None,
+            ]),
+        ]);
+        let unique = df.unique(None).unwrap();
+        assert_eq!(
+            unique,
+            LocalDataFrame::from(vec![
+                Column::Int(vec![Some(1), Some(2), Some(1), None]),
+                Column::Bool(vec![Some(true), Some(true), Some(false), None]),
+            ])
+        );
+        let unique = df.unique(Some(&[1])).unwrap();
+        assert_eq!(unique.n_rows(), 3);
+        assert_eq!(unique.get(1, 1).unwrap(), Data::Bool(false));
+        assert!(matches!(
+            df.unique(Some(&[2])),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_hash_partition_by() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int((0..20).map(|x| Some(x % 4)).collect()),
+            Column::Int((0..20).map(|x| Some(x % 2)).collect()),
+        ]);
+        let single = df.hash_partition(0, 3).unwrap();
+        let by = df.hash_partition_by(&[0], 3).unwrap();
+        assert_eq!(single, by);
+        let partitions = df.hash_partition_by(&[0, 1], 3).unwrap();
+        let total: usize = partitions.iter().map(|p| p.n_rows()).sum();
+        assert_eq!(total, 20);
+        // every key is in exactly one partition
+        for key in 0..4 {
+            let holding = partitions.iter().filter(|p| {
+                (0..p.n_rows()).any(|i| p.get(0, i).unwrap() == Data::Int(key))
+            });
+            assert_eq!(holding.count(), 1);
+        }
+        assert!(df.hash_partition_by(&[0, 2], 3).is_err());
+    }
+
+    #[test]
+    fn test_setters_and_fill_row_return_errors() {
+        let mut df = LocalDataFrame::from(Column::Int(vec![Some(1), None]));
+        assert!(df.set_int(0, 1, 5).is_ok());
+        assert_eq!(df.get(0, 1).unwrap(), Data::Int(5));
+        assert!(matches!(
+            df.set_int(0, 2, 5),
+            Err(LiquidError::RowIndexOutOfBounds)
+        ));
+        assert!(matches!(
+            df.set_int(1, 0, 5),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+        assert!(matches!(
+            df.set_float(0, 0, 5.0),
+            Err(LiquidError::TypeMismatch)
+        ));
+        // a column replaced with one of a different type
+        df.data[0] = Arc::new(Column::Bool(vec![Some(true), None]));
+        assert!(matches!(
+            df.set_int(0, 0, 5),
+            Err(LiquidError::TypeMismatch)
+        ));
+
+        let mut row = Row::new(df.get_schema());
+        assert!(matches!(
+            df.fill_row(2, &mut row),
+            Err(LiquidError::RowIndexOutOfBounds)
+        ));
+        assert!(matches!(
+            df.get(0, 2),
+            Err(LiquidError::RowIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_typed_column_slices() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(3)]),
+            Column::Float(vec![Some(0.5), Some(1.5), None]),
+            Column::Bool(vec![None, Some(true), Some(false)]),
+            Column::String(vec![Some("a".to_string()), None, None]),
+        ]);
+        assert_eq!(df.get_int_col(0).unwrap(), &[Some(1), None, Some(3)]);
+        assert_eq!(df.get_float_col(1).unwrap()[1], Some(1.5));
+        assert_eq!(
+            df.get_bool_col(2).unwrap(),
+            &[None, Some(true), Some(false)]
+        );
+        assert_eq!(df.get_string_col(3).unwrap()[0].as_deref(), Some("a"));
+        assert!(matches!(df.get_int_col(1), Err(LiquidError::TypeMismatch)));
+        assert!(matches!(
+            df.get_string_col(4),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_map_column_and_with_column() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(3)]),
+            Column::Float(vec![Some(0.5), Some(1.5), None]),
+        ]);
+        df.set_col_names(vec!["a".to_string(), "b".to_string()])
+            .unwrap();
+
+        let mapped = df
+            .map_column(0, &DataType::Float, |value| match value {
+                Data::Int(x) => Data::Float(*x as f64 / 2.0),
+                _ => Data::Null,
+            })
+            .unwrap();
+        assert_eq!(mapped.get_schema().schema[0], DataType::Float);
+        assert_eq!(mapped.get_col_idx("a"), Some(0));
+        assert_eq!(mapped.get(0, 2).unwrap(), Data::Float(1.5));
+        assert_eq!(mapped.get(0, 1).unwrap(), Data::Null);
+        assert!(Arc::ptr_eq(&mapped.data[1], &df.data[1]));
+        assert!(matches!(
+            df.map_column(0, &DataType::Bool, |v| v.clone()),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(df.map_column(2, &DataType::Int, |v| v.clone()).is_err());
+
+        let sum = |row: &Row| match (row.get(0).unwrap(), row.get(1).unwrap()) {
+            (Data::Int(a), Data::Float(b)) => Data::Float(*a as f64 + b),
+            _ => Data::Null,
+        };
+        let with_sum = df.with_column("sum", &DataType::Float, sum).unwrap();
+        assert_eq!(with_sum.n_cols(), 3);
+        assert_eq!(with_sum.get_col_idx("sum"), Some(2));
+        assert_eq!(with_sum.get(2, 0).unwrap(), Data::Float(1.5));
+        assert_eq!(with_sum.get(2, 2).unwrap(), Data::Null);
+        // an existing column is replaced, and may change type
+        let replaced = df
+            .with_column("b", &DataType::Bool, |row| {
+                Data::Bool(row.get(1).unwrap() != &Data::Null)
+            })
+            .unwrap();
+        assert_eq!(replaced.n_cols(), 2);
+        assert_eq!(replaced.get(1, 2).unwrap(), Data::Bool(false));
+    }
+
+    #[test]
+    fn test_rename_reorder_and_drop_cols() {
+        let mut df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1)]),
+            Column::Float(vec![Some(2.0)]),
+            Column::Bool(vec![Some(true)]),
+        ]);
+        df.set_col_names(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+        ])
+        .unwrap();
+        df.rename_col("a", "id".to_string()).unwrap();
+        assert_eq!(df.get_col_idx("id"), Some(0));
+        assert!(matches!(
+            df.rename_col("id", "b".to_string()),
+            Err(LiquidError::NameAlreadyExists)
+        ));
+
+        df.reorder_cols(&[2, 0, 1]).unwrap();
+        assert_eq!(df.get(0, 0).unwrap(), Data::Bool(true));
+        assert_eq!(df.get(1, 0).unwrap(), Data::Int(1));
+        assert_eq!(df.get_col_idx("id"), Some(1));
+        assert_eq!(df.get_schema().schema[2], DataType::Float);
+        assert!(df.reorder_cols(&[1, 1, 0]).is_err());
+        assert_eq!(df.get(0, 0).unwrap(), Data::Bool(true));
+
+        df.drop_col("c").unwrap();
+        assert_eq!(df.n_cols(), 2);
+        assert_eq!(df.get(0, 0).unwrap(), Data::Int(1));
+        assert_eq!(df.get_col_idx("b"), Some(1));
+        assert!(matches!(
+            df.drop_col("c"),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_null_toolkit() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), None, Some(3), None]),
+            Column::String(vec![
+                None,
+                Some("b".to_string()),
+                Some("c".to_string()),
+                None,
+            ]),
+        ]);
+        assert_eq!(
+            df.null_mask(0).unwrap(),
+            Column::Bool(vec![
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(true)
+            ])
+        );
+        assert!(df.null_mask(2).is_err());
+
+        let filled = df.fill_null(0, &Data::Int(0)).unwrap();
+        assert_eq!(filled.null_count(0).unwrap(), 0);
+        assert_eq!(filled.get(0, 1).unwrap(), Data::Int(0));
+        assert_eq!(filled.get(0, 2).unwrap(), Data::Int(3));
+        assert!(Arc::ptr_eq(&filled.data[1], &df.data[1]));
+        let filled = df.fill_null(1, &Data::String("z".to_string())).unwrap();
+        assert_eq!(filled.get(1, 0).unwrap(), Data::String("z".to_string()));
+        assert!(matches!(
+            df.fill_null(0, &Data::Float(0.0)),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(matches!(
+            df.fill_null(0, &Data::Null),
+            Err(LiquidError::TypeMismatch)
+        ));
+
+        assert_eq!(df.drop_nulls().n_rows(), 1);
+        assert_eq!(df.drop_nulls_subset(&[0]).unwrap().n_rows(), 2);
+        assert!(df.drop_nulls_subset(&[2]).is_err());
+    }
+
+    #[test]
+    fn test_pivot_agg_and_melt() {
+        let mut long = LocalDataFrame::from(vec![
+            Column::String(vec![
+                Some("x".to_string()),
+                Some("x".to_string()),
+                Some("y".to_string()),
+                Some("x".to_string()),
+            ]),
+            Column::Int(vec![Some(1), Some(2), Some(1), Some(1)]),
+            Column::Int(vec![Some(10), Some(20), Some(30), Some(40)]),
+        ]);
+        long.set_col_names(vec![
+            "store".to_string(),
+            "month".to_string(),
+            "sales".to_string(),
+        ])
+        .unwrap();
+        assert!(matches!(
+            long.pivot(0, 1, 2),
+            Err(LiquidError::DuplicateEntry)
+        ));
+        let wide = long.pivot_agg(0, 1, 2, Agg::Sum).unwrap();
+        assert_eq!(wide.get_col_idx("store"), Some(0));
+        assert_eq!(
+            wide.data,
+            vec![
+                Arc::new(Column::String(vec![
+                    Some("x".to_string()),
+                    Some("y".to_string())
+                ])),
+                Arc::new(Column::Int(vec![Some(50), Some(30)])),
+                Arc::new(Column::Int(vec![Some(20), None])),
+            ]
+        );
+
+        let melted = wide.melt(&[0], &[1, 2]).unwrap();
+        assert_eq!(melted.n_rows(), 4);
+        assert_eq!(melted.get_col_idx("variable"), Some(1));
+        assert_eq!(melted.get_col_idx("value"), Some(2));
+        assert_eq!(melted.get(0, 2).unwrap(), Data::String("x".to_string()));
+        assert_eq!(melted.get(1, 2).unwrap(), Data::String("2".to_string()));
+        assert_eq!(melted.get(2, 0).unwrap(), Data::Int(50));
+        assert_eq!(melted.get(2, 3).unwrap(), Data::Null);
+        assert!(matches!(
+            long.melt(&[1], &[0, 2]),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(matches!(
+            long.melt(&[0], &[]),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_window_functions() {
+        let df = LocalDataFrame::from(vec![
+            Column::Int(vec![Some(1), Some(2), None, Some(4), Some(5)]),
+            Column::String(vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("c".to_string()),
+                None,
+                Some("e".to_string()),
+            ]),
+        ]);
+        assert_eq!(
+            df.rolling_sum(0, 2).unwrap(),
+            Column::Int(vec![None, Some(3), Some(2), Some(4), Some(9)])
+        );
+        assert_eq!(
+            df.rolling_mean(0, 3).unwrap(),
+            Column::Float(vec![None, None, Some(1.5), Some(3.0), Some(4.5)])
+        );
+        assert_eq!(
+            df.lag(1, 2).unwrap(),
+            Column::String(vec![
+                None,
+                None,
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("c".to_string()),
+            ])
+        );
+        assert_eq!(
+            df.lead(0, 1).unwrap(),
+            Column::Int(vec![Some(2), None, Some(4), Some(5), None])
+        );
+        assert_eq!(df.lead(0, 5).unwrap(), Column::Int(vec![None; 5]));
+        assert_eq!(
+            df.cumsum(0).unwrap(),
+            Column::Int(vec![Some(1), Some(3), None, Some(7), Some(12)])
+        );
+        assert_eq!(
+            df.window(0, Window::CumSum).unwrap(),
+            df.cumsum(0).unwrap()
+        );
+        assert_eq!(
+            df.window(0, Window::Lag(1)).unwrap(),
+            df.lag(0, 1).unwrap()
+        );
+        assert!(matches!(df.cumsum(1), Err(LiquidError::TypeMismatch)));
+        assert!(matches!(
+            df.lag(2, 1),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let floats = vec![Some(1.0), None, Some(f64::NAN), Some(-2.5)];
+        let mut df = LocalDataFrame::from(Column::Float(floats));
+        df.add_column(Column::Int(vec![Some(1), Some(2), None, Some(4)]), None)
+            .unwrap();
+        let mut close = df.clone();
+        close.set_float(0, 0, 1.0 + 1e-9).unwrap();
+        assert!(df.approx_eq(&df, 0.0));
+        assert!(df.approx_eq(&close, 1e-6));
+        assert!(!df.approx_eq(&close, 1e-12));
+        assert_ne!(df, close);
+
+        let mut other_int = df.clone();
+        other_int.set_int(1, 3, 5).unwrap();
+        assert!(!df.approx_eq(&other_int, 1.0));
+        let mut renamed = df.clone();
+        renamed
+            .set_col_names(vec!["x".to_string(), "y".to_string()])
+            .unwrap();
+        assert!(!df.approx_eq(&renamed, 1.0));
+        assert!(!df.approx_eq(&df.slice(0, 2).unwrap().to_local(), 1.0));
+    }
+
+    #[test]
+    fn test_eq_ignores_n_threads() {
         let mut df = init();
+        let mut other = df.clone();
         df.n_threads = 1;
-        let mut rower = PosIntSummer { sum: 0 };
-        rower = df.pmap(rower);
-        assert_eq!(1000 * 1000 / 4, rower.sum);
-        assert_eq!(1000, df.n_rows());
+        other.n_threads = 8;
+        assert_eq!(df, other);
+        other.set_int(0, 0, -1).unwrap();
+        assert_ne!(df, other);
     }
 
     #[test]
-    fn test_filter() {
+    fn test_head_tail_and_to_table() {
         let df = init();
-        let mut rower = PosIntSummer { sum: 0 };
-        let df2 = df.filter(&mut rower);
-        assert_eq!(df2.n_rows(), 501);
-        assert_eq!(df2.n_cols(), 1);
-        assert_eq!(df2.get(0, 10).unwrap(), Data::Int(19));
+        assert_eq!(df.head(3), df.slice(0, 3).unwrap().to_local());
+        assert_eq!(df.tail(3), df.slice(997, 1000).unwrap().to_local());
+        assert_eq!(df.head(5000).n_rows(), 1000);
+        assert_eq!(df.tail(0).n_rows(), 0);
+
+        let mut small =
+            LocalDataFrame::from(Column::Int(vec![Some(1), None, Some(300)]));
+        small.set_col_names(vec!["id".to_string()]).unwrap();
+        assert_eq!(
+            small.to_string(),
+            "  | id\n  | <int>\n--+------\n0 |     1\n1 |  null\n\
+             2 |   300\n[3 rows x 1 columns]"
+        );
+
+        let table = df.to_table(4, 20);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert!(lines[3].starts_with("  0 |"));
+        assert!(lines[5].starts_with("... | ..."));
+        assert!(lines[7].starts_with("999 |"));
+        assert_eq!(lines[8], "[1000 rows x 1 columns]");
+        assert_eq!(df.to_string().lines().count(), DISPLAY_MAX_ROWS + 5);
     }
 
     #[test]
-    fn test_pfilter() {
-        let df = init();
-        let mut rower = PosIntSummer { sum: 0 };
-        let df2 = df.pfilter(&mut rower);
-        assert_eq!(df2.n_rows(), 501);
-        assert_eq!(df2.n_cols(), 1);
-        assert_eq!(df2.get(0, 10).unwrap(), Data::Int(19));
+    fn test_collection_conversions() {
+        let named = LocalDataFrame::from(vec![
+            ("id".to_string(), Column::Int(vec![Some(1), Some(2)])),
+            ("ok".to_string(), Column::Bool(vec![Some(true)])),
+        ]);
+        assert_eq!(named.get_col_idx("ok"), Some(1));
+        assert_eq!(named.get(1, 1).unwrap(), Data::Null);
+
+        let records: Vec<HashMap<String, Data>> = Vec::from(&named);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["ok"], Data::Bool(true));
+        assert!(!records[1].contains_key("ok"));
+        assert_eq!(LocalDataFrame::try_from(records).unwrap(), named);
+
+        let mut record = HashMap::new();
+        record.insert("x".to_string(), Data::Int(1));
+        let mut other = HashMap::new();
+        other.insert("x".to_string(), Data::Float(1.0));
+        assert!(matches!(
+            LocalDataFrame::try_from(vec![record.clone(), other]),
+            Err(LiquidError::TypeMismatch)
+        ));
+        let mut null = HashMap::new();
+        null.insert("y".to_string(), Data::Null);
+        assert!(matches!(
+            LocalDataFrame::try_from(vec![record, null]),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+        let empty: Vec<HashMap<String, Data>> = Vec::new();
+        assert_eq!(LocalDataFrame::try_from(empty).unwrap().n_cols(), 0);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Species {
+        Cat,
+        Dog,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Pet {
+        name: String,
+        age: u8,
+        weight: Option<f32>,
+        species: Species,
+    }
+
+    #[test]
+    fn test_from_and_to_records() {
+        let pets = vec![
+            Pet {
+                name: "rex".to_string(),
+                age: 3,
+                weight: None,
+                species: Species::Dog,
+            },
+            Pet {
+                name: "tom".to_string(),
+                age: 7,
+                weight: Some(4.5),
+                species: Species::Cat,
+            },
+        ];
+        let df = LocalDataFrame::from_records(&pets).unwrap();
+        assert_eq!(df.n_rows(), 2);
+        assert_eq!(df.get_col_idx("species"), Some(3));
+        assert_eq!(df.get_schema().col_type(1).unwrap(), &DataType::Int);
+        assert_eq!(df.get(2, 0).unwrap(), Data::Null);
+        assert_eq!(df.get(3, 1).unwrap(), Data::String("Cat".to_string()));
+        assert_eq!(df.to_records::<Pet>().unwrap(), pets);
+
+        assert!(matches!(
+            LocalDataFrame::from_records(&[vec![1, 2]]),
+            Err(LiquidError::SerdeError(_))
+        ));
+        let mut renamed = df.clone();
+        renamed.rename_col("age", "years".to_string()).unwrap();
+        assert!(matches!(
+            renamed.to_records::<Pet>(),
+            Err(LiquidError::SerdeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_iter_rows_and_row_refs() {
+        let mut df = LocalDataFrame::from(Column::Int(vec![Some(1), None]));
+        let names = vec![Some("a".to_string()), Some("b".to_string())];
+        df.add_column(Column::String(names), None).unwrap();
+        let rows: Vec<Row> = df.iter_rows().collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get_idx(), Some(1));
+        assert_eq!(rows[1].get(0).unwrap(), &Data::Null);
+        assert_eq!(rows[0].get(1).unwrap(), &Data::String("a".to_string()));
+
+        let refs: Vec<RowRef> = df.iter_row_refs().collect();
+        assert_eq!(refs[1].get_idx(), 1);
+        assert_eq!(refs[0].width(), 2);
+        assert_eq!(refs[0].get_int(0).unwrap(), Some(1));
+        assert_eq!(refs[1].get_int(0).unwrap(), None);
+        assert_eq!(refs[1].get_string(1).unwrap(), Some("b"));
+        assert_eq!(refs[1].get(1).unwrap(), Data::String("b".to_string()));
+        assert!(matches!(
+            refs[0].get_float(0),
+            Err(LiquidError::TypeMismatch)
+        ));
+        assert!(matches!(
+            refs[0].get_bool(2),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_index_columns() {
+        let mut df = LocalDataFrame::from(Column::Int(vec![Some(1), None]));
+        df.set_col_names(vec!["id".to_string()]).unwrap();
+        assert_eq!(df[0], Column::Int(vec![Some(1), None]));
+        assert_eq!(df["id"], df[0]);
+        assert_eq!(df.get_checked("id").unwrap(), &df[0]);
+        assert!(matches!(
+            df.get_checked("nope"),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_arrow_round_trip() {
+        let mut df = LocalDataFrame::from(Column::Int(vec![Some(1), None]));
+        df.add_column(Column::Float(vec![None, Some(2.5)]), Some("f".into()))
+            .unwrap();
+        df.add_column(Column::Bool(vec![Some(true), None]), None)
+            .unwrap();
+        df.add_column(
+            Column::String(vec![Some("a".to_string()), None]),
+            Some("s".into()),
+        )
+        .unwrap();
+
+        let batch = df.to_arrow().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().field(0).name(), "0");
+        let from_arrow = LocalDataFrame::from_arrow(&batch).unwrap();
+        assert_eq!(from_arrow.data, df.data);
+        assert_eq!(from_arrow.get_col_idx("s"), Some(3));
+
+        let path = std::env::temp_dir().join(format!(
+            "liquid_ml_arrow_round_trip_{}.arrow",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        df.write_ipc(path).unwrap();
+        let from_ipc = LocalDataFrame::read_ipc(path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(from_ipc, from_arrow);
+
+        let unsigned: arrow::array::ArrayRef =
+            Arc::new(arrow::array::UInt64Array::from(vec![1]));
+        let unsupported =
+            RecordBatch::try_from_iter(vec![("u", unsigned)]).unwrap();
+        assert!(matches!(
+            LocalDataFrame::from_arrow(&unsupported),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_round_trip() {
+        let mut df =
+            LocalDataFrame::from(Column::Int((0..10).map(Some).collect()));
+        df.add_column(
+            Column::String((0..10).map(|i| Some(i.to_string())).collect()),
+            Some("s".into()),
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "liquid_ml_round_trip_{}.parquet",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        df.to_parquet(path).unwrap();
+
+        assert_eq!(
+            LocalDataFrame::parquet_row_group_sizes(path).unwrap(),
+            [10]
+        );
+        let from_parquet = LocalDataFrame::from_parquet(path, 0..1).unwrap();
+        assert_eq!(from_parquet.data, df.data);
+        assert_eq!(from_parquet.get_col_idx("s"), Some(1));
+        let empty = LocalDataFrame::from_parquet(path, 1..1).unwrap();
+        assert_eq!(empty.n_rows(), 0);
+        assert_eq!(empty.get_schema().schema, df.get_schema().schema);
+        assert!(matches!(
+            LocalDataFrame::from_parquet(path, 0..2),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let path = std::env::temp_dir()
+            .join(format!("liquid_ml_from_csv_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(
+            path,
+            "id,name,ok,score\n\
+             1,\"a, b\",true,1.5\n\
+             2,\"two\nlines\",FALSE,\n\
+             x,c,true,2\n\
+             4,d,false,3,extra\n\
+             5\n",
+        )
+        .unwrap();
+
+        let df =
+            LocalDataFrame::from_csv(path, &CsvOptions::default()).unwrap();
+        assert_eq!(df.get_schema().schema, Schema::from("SSBF").schema);
+        assert_eq!(df.get_col_idx("score"), Some(3));
+        assert_eq!(df.n_rows(), 4);
+        assert_eq!(df.get(1, 0).unwrap(), Data::String("a, b".to_string()));
+        assert_eq!(df.get(1, 1).unwrap(), Data::String("two\nlines".into()));
+        assert_eq!(df.get(3, 3).unwrap(), Data::Null);
+
+        // the record with a non integer id is discarded
+        let options = CsvOptions::default().with_col_type("id", DataType::Int);
+        let df = LocalDataFrame::from_csv(path, &options).unwrap();
+        assert_eq!(df.get_string_col(1).unwrap().len(), 3);
+        assert_eq!(df.get(0, 2).unwrap(), Data::Int(5));
+
+        fs::write(path, "1;true\n2;false\n").unwrap();
+        let options = CsvOptions::default()
+            .with_header(false)
+            .with_delimiter(b';')
+            .with_col_type("1", DataType::String);
+        let df = LocalDataFrame::from_csv(path, &options).unwrap();
+        assert_eq!(df.get_schema().schema, Schema::from("IS").schema);
+        assert_eq!(df.get_col_idx("0"), None);
+        let options =
+            CsvOptions::default().with_col_type("nope", DataType::Int);
+        assert!(matches!(
+            LocalDataFrame::from_csv(path, &options),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+        fs::remove_file(path).unwrap();
+
+        // a directory can be opened but not read, which is an error instead
+        // of an empty data frame
+        let dir = std::env::temp_dir();
+        assert!(matches!(
+            LocalDataFrame::from_csv(dir.to_str().unwrap(), &options),
+            Err(LiquidError::NetworkError(_))
+        ));
     }
 }
@@ -57,10 +57,12 @@
 //!     useful API to help implement the [`Rower`] trait
 //!  - [`Schema`] : This can be especially useful when a [`SoR`] File is read and
 //!     different things need to be done based on the inferred schema
+//!  - [`DataFrameSlice`] : A view of a range of rows of a [`LocalDataFrame`]
+//!     that shares its columns instead of copying them
 //!
-//! The `dataframe` module also declares the [`Rower`] and [`Fielder`] visitor
-//! traits that can be used to build visitors that iterate over the elements of
-//! a row or data frame.
+//! The `dataframe` module also declares the [`Rower`], [`MapRower`] and
+//! [`Fielder`] visitor traits that can be used to build visitors that iterate
+//! over the elements of a row or data frame.
 //!
 //! NOTE: We are likely to add iterators to replace the current visitors, since
 //! iterators are more idiomatic to write in rust
@@ -68,8 +70,10 @@
 //! [`Column`]: struct.Column.html
 //! [`Row`]: struct.Row.html
 //! [`Rower`]: trait.Rower.html
+//! [`MapRower`]: trait.MapRower.html
 //! [`Fielder`]: trait.Fielder.html
 //! [`Schema`]: struct.Schema.html
+//! [`DataFrameSlice`]: struct.DataFrameSlice.html
 //! [`Data`]: struct.Data.html
 //! [`LocalDataFrame`]: struct.LocalDataFrame.html
 //! [`DistributedDataFrame`]: struct.DistributedDataFrame.html
@@ -84,18 +88,38 @@ pub use sorer::{
     schema::DataType,
 };
 
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(feature = "arrow")]
+mod arrow_io;
+
+mod builder;
+pub use builder::DataFrameBuilder;
+
+mod csv_io;
+pub use csv_io::CsvOptions;
+
+mod encoding;
+
 mod distributed_dataframe;
 pub use distributed_dataframe::DistributedDataFrame;
 
 mod local_dataframe;
 pub use local_dataframe::LocalDataFrame;
+pub(crate) use local_dataframe::{DescribeRower, GroupByRower};
+
+mod records;
 
 mod row;
-pub use row::Row;
+pub use row::{Row, RowRef};
 
 mod schema;
 pub use schema::Schema;
 
+mod slice;
+pub use slice::DataFrameSlice;
+
 /// A field visitor that may be implemented to iterate and visit all the
 /// elements of a [`Row`].
 ///
@@ -149,4 +173,196 @@ pub trait Rower {
     /// a total sum or may be much more complicated. In most cases, it is
     /// usually trivial. The returned [`Rower`] will contain the final results.
     fn join(self, other: Self) -> Self;
+
+    /// Called exactly once on the final [`Rower`] of a `map`, after every row
+    /// has been visited and all [`Rower`]s have been joined. This is the
+    /// place for a final computation over the joined results, e.g. dividing
+    /// a running sum by a running count to get a mean. The returned
+    /// [`Rower`] is the result of the `map`. By default, the [`Rower`] is
+    /// returned unchanged.
+    ///
+    /// [`Rower`]: trait.Rower.html
+    fn finalize(self) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+/// A trait for visitors who transform each row of a data frame into a new
+/// row, to build a new data frame in a single pass. Returning `None` drops
+/// the row, so a `MapRower` acts as a combined `map` and `filter`.
+pub trait MapRower {
+    /// This function is called once per row of a data frame. The returned
+    /// [`Row`], if any, is added to the resulting data frame. All returned
+    /// [`Row`]s must have the same [`Schema`].
+    ///
+    /// [`Row`]: struct.Row.html
+    /// [`Schema`]: struct.Schema.html
+    fn map_row(&self, row: &Row) -> Option<Row>;
+}
+
+/// The aggregations that can be computed over a window of values in a
+/// numeric column, e.g. with [`LocalDataFrame::rolling`], or over groups of
+/// rows with [`LocalDataFrame::group_by`]. Null values are skipped.
+///
+/// [`LocalDataFrame::rolling`]: struct.LocalDataFrame.html#method.rolling
+/// [`LocalDataFrame::group_by`]: struct.LocalDataFrame.html#method.group_by
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Agg {
+    /// The sum of the values
+    Sum,
+    /// The arithmetic mean of the values, always a `Float`
+    Mean,
+    /// The smallest value
+    Min,
+    /// The largest value
+    Max,
+    /// The number of non-null values, always an `Int`
+    Count,
+}
+
+impl fmt::Display for Agg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Agg::Sum => write!(f, "sum"),
+            Agg::Mean => write!(f, "mean"),
+            Agg::Min => write!(f, "min"),
+            Agg::Max => write!(f, "max"),
+            Agg::Count => write!(f, "count"),
+        }
+    }
+}
+
+/// The window functions that can be computed over the rows of a column in
+/// order, with [`LocalDataFrame::window`] or across the chunks of a
+/// distributed data frame with [`DistributedDataFrame::with_window`].
+///
+/// [`LocalDataFrame::window`]: struct.LocalDataFrame.html#method.window
+/// [`DistributedDataFrame::with_window`]: struct.DistributedDataFrame.html#method.with_window
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// The sum of a trailing window of the given number of rows, see
+    /// [`LocalDataFrame::rolling_sum`]
+    ///
+    /// [`LocalDataFrame::rolling_sum`]: struct.LocalDataFrame.html#method.rolling_sum
+    RollingSum(usize),
+    /// The mean of a trailing window of the given number of rows, see
+    /// [`LocalDataFrame::rolling_mean`]
+    ///
+    /// [`LocalDataFrame::rolling_mean`]: struct.LocalDataFrame.html#method.rolling_mean
+    RollingMean(usize),
+    /// The value the given number of rows before each row, see
+    /// [`LocalDataFrame::lag`]
+    ///
+    /// [`LocalDataFrame::lag`]: struct.LocalDataFrame.html#method.lag
+    Lag(usize),
+    /// The value the given number of rows after each row, see
+    /// [`LocalDataFrame::lead`]
+    ///
+    /// [`LocalDataFrame::lead`]: struct.LocalDataFrame.html#method.lead
+    Lead(usize),
+    /// The running sum of all the values up to each row, see
+    /// [`LocalDataFrame::cumsum`]
+    ///
+    /// [`LocalDataFrame::cumsum`]: struct.LocalDataFrame.html#method.cumsum
+    CumSum,
+}
+
+impl Window {
+    /// The number of rows before and after each row that the value of this
+    /// `Window` for that row depends on, or `None` for the rows before if it
+    /// depends on all of them
+    pub(crate) fn context(&self) -> (Option<usize>, usize) {
+        match *self {
+            Window::RollingSum(n) | Window::RollingMean(n) => {
+                (Some(n.saturating_sub(1)), 0)
+            }
+            Window::Lag(n) => (Some(n), 0),
+            Window::Lead(n) => (Some(0), n),
+            Window::CumSum => (None, 0),
+        }
+    }
+}
+
+/// Summary statistics of a numeric column of a data frame, computed with
+/// [`LocalDataFrame::summary`]. Null values are skipped, and statistics that
+/// are undefined for the number of non-null values are `None`.
+///
+/// [`LocalDataFrame::summary`]: struct.LocalDataFrame.html#method.summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSummary {
+    /// The number of non-null values
+    pub count: usize,
+    /// The sum of the values, `0` if there are none, of the type of the
+    /// column
+    pub sum: Data,
+    /// The arithmetic mean of the values
+    pub mean: Option<f64>,
+    /// The smallest value, of the type of the column
+    pub min: Option<Data>,
+    /// The largest value, of the type of the column
+    pub max: Option<Data>,
+    /// The sample variance of the values, which needs at least two values
+    pub var: Option<f64>,
+    /// The sample standard deviation of the values, which needs at least two
+    /// values
+    pub std: Option<f64>,
+}
+
+/// The kinds of joins that can be performed between two data frames, e.g.
+/// with [`LocalDataFrame::join`]. Determines what happens to rows that have
+/// no matching row in the other data frame.
+///
+/// [`LocalDataFrame::join`]: struct.LocalDataFrame.html#method.join
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Only rows with a match in both data frames are kept
+    Inner,
+    /// Every row of the left data frame is kept, with nulls for the columns
+    /// of the right data frame if it has no match
+    Left,
+    /// Every row of the right data frame is kept, with nulls for the columns
+    /// of the left data frame if it has no match
+    Right,
+    /// Every row of both data frames is kept, with nulls for the columns of
+    /// the other data frame if it has no match
+    Outer,
+}
+
+/// The arithmetic operations that can be applied element-wise to numeric
+/// columns, e.g. with [`LocalDataFrame::arith`].
+///
+/// [`LocalDataFrame::arith`]: struct.LocalDataFrame.html#method.arith
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithOp {
+    /// Addition
+    Add,
+    /// Subtraction
+    Sub,
+    /// Multiplication
+    Mul,
+    /// Division, which is integer division for two `Int`s
+    Div,
+}
+
+/// The comparisons that can be applied element-wise to columns, e.g. with
+/// [`LocalDataFrame::compare`].
+///
+/// [`LocalDataFrame::compare`]: struct.LocalDataFrame.html#method.compare
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    /// Equal to
+    Eq,
+    /// Not equal to
+    Ne,
+    /// Less than
+    Lt,
+    /// Less than or equal to
+    Le,
+    /// Greater than
+    Gt,
+    /// Greater than or equal to
+    Ge,
 }
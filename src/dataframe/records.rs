@@ -0,0 +1,583 @@
+//! A `serde` serializer and deserializer between records, i.e. values of a
+//! type such as a struct whose fields are primitive values, and the `Data`
+//! in a row of a `LocalDataFrame`, where each field is a column.
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+use sorer::dataframe::Data;
+use std::convert::TryFrom;
+use std::vec;
+
+/// Serializes the given `record` to the name and value of each of its
+/// fields, in order. `record` must be a struct or a map with `String` keys,
+/// whose values are primitives, `String`s, unit enum variants, or `Option`s
+/// of those, where `None` becomes `Data::Null`.
+pub(crate) fn to_fields<T: Serialize>(
+    record: &T,
+) -> Result<Vec<(String, Data)>, bincode::Error> {
+    record.serialize(RecordSerializer)
+}
+
+/// Deserializes a record of type `T` from the name and value of each of its
+/// fields, the opposite of `to_fields`. Fields of `T` that are missing or
+/// `Data::Null` can only be deserialized to `Option`s.
+pub(crate) fn from_fields<T: DeserializeOwned>(
+    fields: Vec<(String, Data)>,
+) -> Result<T, bincode::Error> {
+    T::deserialize(RecordDeserializer { fields })
+}
+
+fn unsupported(what: &str) -> bincode::Error {
+    ser::Error::custom(format!("{} can not be stored in a column", what))
+}
+
+/// Serializes a record to its fields
+struct RecordSerializer;
+
+/// Collects the fields of a struct or map as it is serialized
+struct FieldCollector {
+    fields: Vec<(String, Data)>,
+    /// The key of a map entry whose value has not been serialized yet
+    key: Option<String>,
+}
+
+impl ser::Serializer for RecordSerializer {
+    type Ok = Vec<(String, Data)>;
+    type Error = bincode::Error;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = FieldCollector;
+    type SerializeStruct = FieldCollector;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(FieldCollector {
+            fields: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+
+    fn serialize_map(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(FieldCollector {
+            fields: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(
+        self,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_tuple(
+        self,
+        _len: usize,
+    ) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported("a record that is not a struct or map"))
+    }
+}
+
+impl ser::SerializeStruct for FieldCollector {
+    type Ok = Vec<(String, Data)>;
+    type Error = bincode::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.fields
+            .push((key.to_string(), value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.fields)
+    }
+}
+
+impl ser::SerializeMap for FieldCollector {
+    type Ok = Vec<(String, Data)>;
+    type Error = bincode::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(
+        &mut self,
+        key: &T,
+    ) -> Result<(), Self::Error> {
+        match key.serialize(ValueSerializer)? {
+            Data::String(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(unsupported("a map key that is not a string")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let key = self.key.take().expect("serialize_key is called first");
+        self.fields.push((key, value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.fields)
+    }
+}
+
+/// Serializes the value of a single field to `Data`
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Data;
+    type Error = bincode::Error;
+    type SerializeSeq = Impossible<Data, Self::Error>;
+    type SerializeTuple = Impossible<Data, Self::Error>;
+    type SerializeTupleStruct = Impossible<Data, Self::Error>;
+    type SerializeTupleVariant = Impossible<Data, Self::Error>;
+    type SerializeMap = Impossible<Data, Self::Error>;
+    type SerializeStruct = Impossible<Data, Self::Error>;
+    type SerializeStructVariant = Impossible<Data, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Data, Self::Error> {
+        Ok(Data::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Data, Self::Error> {
+        Ok(Data::Int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Data, Self::Error> {
+        Ok(Data::Int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Data, Self::Error> {
+        Ok(Data::Int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Data, Self::Error> {
+        Ok(Data::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Data, Self::Error> {
+        Ok(Data::Int(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Data, Self::Error> {
+        Ok(Data::Int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Data, Self::Error> {
+        Ok(Data::Int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Data, Self::Error> {
+        i64::try_from(v)
+            .map(Data::Int)
+            .map_err(|_| unsupported("a u64 larger than i64::MAX"))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Data, Self::Error> {
+        Ok(Data::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Data, Self::Error> {
+        Ok(Data::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Data, Self::Error> {
+        Ok(Data::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Data, Self::Error> {
+        Ok(Data::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Data, Self::Error> {
+        Err(unsupported("a byte array"))
+    }
+
+    fn serialize_none(self) -> Result<Data, Self::Error> {
+        Ok(Data::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(
+        self,
+        value: &T,
+    ) -> Result<Data, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Data, Self::Error> {
+        Ok(Data::Null)
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<Data, Self::Error> {
+        Ok(Data::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Data, Self::Error> {
+        Ok(Data::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Data, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Data, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(
+        self,
+        _len: usize,
+    ) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Self::SerializeMap, Self::Error> {
+        Err(unsupported("a nested map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(unsupported("a nested struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+}
+
+/// Deserializes a record from its fields, as a map from their names to their
+/// values
+struct RecordDeserializer {
+    fields: Vec<(String, Data)>,
+}
+
+impl<'de> de::Deserializer<'de> for RecordDeserializer {
+    type Error = bincode::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(FieldAccess {
+            fields: self.fields.into_iter(),
+            value: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Visits the fields of a record one at a time
+struct FieldAccess {
+    fields: vec::IntoIter<(String, Data)>,
+    /// The value of the field whose name was just visited
+    value: Option<Data>,
+}
+
+impl<'de> de::MapAccess<'de> for FieldAccess {
+    type Error = bincode::Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.fields.next() {
+            Some((name, value)) => {
+                self.value = Some(value);
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value = self.value.take().expect("next_key_seed is called first");
+        seed.deserialize(ValueDeserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// Deserializes the value of a single field from `Data`
+struct ValueDeserializer(Data);
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = bincode::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Data::Bool(b) => visitor.visit_bool(b),
+            Data::Int(i) => visitor.visit_i64(i),
+            Data::Float(f) => visitor.visit_f64(f),
+            Data::String(s) => visitor.visit_string(s),
+            Data::Null => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Data::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Data::Null => visitor.visit_unit(),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Data::String(variant) => {
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            other => Err(de::Error::custom(format!(
+                "expected a variant of {}, found {:?}",
+                name, other
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
@@ -1,5 +1,5 @@
 //! Structs and functions for working with rows of data in a `DataFrame`.
-use crate::dataframe::{Fielder, Schema};
+use crate::dataframe::{Fielder, LocalDataFrame, Schema};
 use crate::error::LiquidError;
 use deepsize::DeepSizeOf;
 use serde::{Deserialize, Serialize};
@@ -22,7 +22,8 @@ macro_rules! row_setter {
     ($func_name:ident, $type:ty, $sorer_type:ident) => {
         /// Sets the field in this `Row` at the given `col_idx` to have the given
         /// `data`. The `DataType` at the `col_idx` must be an `Int` or it will
-        /// return a `TypeMismatch` error.
+        /// return a `TypeMismatch` error. If `col_idx` is out of bounds, a
+        /// `ColIndexOutOfBounds` error is returned.
         pub fn $func_name(
             &mut self,
             col_idx: usize,
@@ -30,13 +31,13 @@ macro_rules! row_setter {
         ) -> Result<(), LiquidError> {
             match self.schema.schema.get(col_idx) {
                 Some(DataType::$sorer_type) => {
-                    match self.data.get(col_idx).unwrap() {
+                    let d = &mut self.data[col_idx];
+                    match d {
                         Data::Null | Data::$sorer_type(_) => {
-                            *self.data.get_mut(col_idx).unwrap() =
-                                Data::$sorer_type(data);
+                            *d = Data::$sorer_type(data);
                             Ok(())
                         }
-                        _ => panic!("Something is horribly wrong"),
+                        _ => Err(LiquidError::TypeMismatch),
                     }
                 }
                 None => Err(LiquidError::ColIndexOutOfBounds),
@@ -138,6 +139,77 @@ impl Index<&str> for Row {
     }
 }
 
+/// Generates a typed getter of `RowRef` that reads the value straight from
+/// the column
+macro_rules! row_ref_getter {
+    ($func_name:ident, $col_getter:ident, $type:ty) => {
+        /// Returns the value of the column at `col_idx` in this row, or
+        /// `None` if it is null, without copying it into a `Data`.
+        ///
+        /// # Errors
+        /// - `LiquidError::ColIndexOutOfBounds` if `col_idx` is out of bounds
+        /// - `LiquidError::TypeMismatch` if the column is of a different type
+        pub fn $func_name(
+            &self,
+            col_idx: usize,
+        ) -> Result<Option<$type>, LiquidError> {
+            Ok(self.df.$col_getter(col_idx)?[self.idx])
+        }
+    };
+}
+
+/// A borrowed view of a single row of a `LocalDataFrame`, returned by
+/// [`LocalDataFrame::iter_row_refs`]. Unlike a `Row`, a `RowRef` does not
+/// copy the values of the row, so its typed getters never allocate.
+///
+/// [`LocalDataFrame::iter_row_refs`]: struct.LocalDataFrame.html#method.iter_row_refs
+#[derive(Clone, Copy, Debug)]
+pub struct RowRef<'a> {
+    /// The `LocalDataFrame` this is a row of
+    df: &'a LocalDataFrame,
+    /// The index of this row in `df`
+    idx: usize,
+}
+
+impl<'a> RowRef<'a> {
+    /// Creates a new `RowRef` of the row at `idx` of `df`, which must be in
+    /// bounds.
+    pub(crate) fn new(df: &'a LocalDataFrame, idx: usize) -> Self {
+        RowRef { df, idx }
+    }
+
+    /// Returns the index of this row in its `LocalDataFrame`.
+    pub fn get_idx(&self) -> usize {
+        self.idx
+    }
+
+    /// Returns the number of columns in this row.
+    pub fn width(&self) -> usize {
+        self.df.n_cols()
+    }
+
+    /// Returns the value of the column at `col_idx` in this row as a `Data`,
+    /// which copies `String` values.
+    pub fn get(&self, col_idx: usize) -> Result<Data, LiquidError> {
+        self.df.get(col_idx, self.idx)
+    }
+
+    row_ref_getter!(get_int, get_int_col, i64);
+    row_ref_getter!(get_float, get_float_col, f64);
+    row_ref_getter!(get_bool, get_bool_col, bool);
+
+    /// Returns the value of the `String` column at `col_idx` in this row, or
+    /// `None` if it is null. Errors in the same way as [`get_int`].
+    ///
+    /// [`get_int`]: struct.RowRef.html#method.get_int
+    pub fn get_string(
+        &self,
+        col_idx: usize,
+    ) -> Result<Option<&'a str>, LiquidError> {
+        Ok(self.df.get_string_col(col_idx)?[self.idx].as_deref())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +80,106 @@ impl Schema {
         }
     }
 
+    /// Replaces the names of all the columns in this `Schema` with the given
+    /// `names`, where the name at index `i` is given to the column at index
+    /// `i`. If the number of `names` does not match the number of columns, a
+    /// `LiquidError::ColIndexOutOfBounds` is returned, and if the `names` are
+    /// not unique a `LiquidError::NameAlreadyExists` is returned. In both
+    /// cases the existing column names are left unchanged.
+    pub fn set_col_names(
+        &mut self,
+        names: Vec<String>,
+    ) -> Result<(), LiquidError> {
+        if names.len() != self.width() {
+            return Err(LiquidError::ColIndexOutOfBounds);
+        }
+        let mut col_names = HashMap::new();
+        for (idx, name) in names.into_iter().enumerate() {
+            if col_names.insert(name, idx).is_some() {
+                return Err(LiquidError::NameAlreadyExists);
+            }
+        }
+        self.col_names = col_names;
+        Ok(())
+    }
+
+    /// Renames the column named `old` to `new`. Renaming a column to its
+    /// current name does nothing.
+    ///
+    /// # Errors
+    /// If there is no column named `old`, a `LiquidError::InvalidArgument` is
+    /// returned, and if another column is already named `new` a
+    /// `LiquidError::NameAlreadyExists` is returned. In both cases the
+    /// existing column names are left unchanged.
+    pub fn rename_col(
+        &mut self,
+        old: &str,
+        new: String,
+    ) -> Result<(), LiquidError> {
+        let idx = self.col_idx(old).ok_or_else(|| no_column_named(old))?;
+        match self.col_names.get(&new) {
+            Some(&other) if other == idx => return Ok(()),
+            Some(_) => return Err(LiquidError::NameAlreadyExists),
+            None => (),
+        }
+        self.col_names.remove(old);
+        self.col_names.insert(new, idx);
+        Ok(())
+    }
+
+    /// Reorders the columns of this `Schema` so that the column at index
+    /// `order[i]` is moved to index `i`, keeping their names. `order` must
+    /// contain every column index exactly once.
+    ///
+    /// # Errors
+    /// If `order` doesn't have one index per column or contains an index
+    /// that is out of bounds, a `LiquidError::ColIndexOutOfBounds` is
+    /// returned, and if it contains an index more than once a
+    /// `LiquidError::InvalidArgument` is returned. In both cases this
+    /// `Schema` is left unchanged.
+    pub fn reorder_cols(&mut self, order: &[usize]) -> Result<(), LiquidError> {
+        if order.len() != self.width() {
+            return Err(LiquidError::ColIndexOutOfBounds);
+        }
+        let mut new_idxs = vec![None; self.width()];
+        for (new_idx, &old_idx) in order.iter().enumerate() {
+            match new_idxs.get_mut(old_idx) {
+                Some(Some(_)) => {
+                    return Err(LiquidError::InvalidArgument(format!(
+                        "column {} appears more than once",
+                        old_idx
+                    )))
+                }
+                Some(slot) => *slot = Some(new_idx),
+                None => return Err(LiquidError::ColIndexOutOfBounds),
+            }
+        }
+        self.schema = order.iter().map(|&i| self.schema[i].clone()).collect();
+        for idx in self.col_names.values_mut() {
+            *idx = new_idxs[*idx].unwrap();
+        }
+        Ok(())
+    }
+
+    /// Removes the column named `name` from this `Schema`, shifting the
+    /// columns after it one index to the left. Returns the index the removed
+    /// column had.
+    ///
+    /// # Errors
+    /// If there is no column named `name`, a `LiquidError::InvalidArgument`
+    /// is returned.
+    pub fn drop_col(&mut self, name: &str) -> Result<usize, LiquidError> {
+        let idx = self.col_idx(name).ok_or_else(|| no_column_named(name))?;
+        self.schema.remove(idx);
+        self.col_names.remove(name);
+        for other in self.col_names.values_mut() {
+            if *other > idx {
+                *other -= 1;
+            }
+        }
+        Ok(idx)
+    }
+
     /// The number of columns in this Schema.
     pub fn width(&self) -> usize {
         self.schema.len()
@@ -148,6 +248,12 @@ impl From<&Vec<Column>> for Schema {
     }
 }
 
+/// The error returned when a column is looked up by a `name` that doesn't
+/// exist
+pub(crate) fn no_column_named(name: &str) -> LiquidError {
+    LiquidError::InvalidArgument(format!("there is no column named {}", name))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,4 +310,47 @@ mod tests {
         assert_eq!(s.width(), 2);
         assert_eq!(s.col_idx("foo"), Some(1));
     }
+
+    #[test]
+    fn test_rename_reorder_and_drop_cols() {
+        let mut s = Schema::from("IFS");
+        s.set_col_names(vec![
+            String::from("a"),
+            String::from("b"),
+            String::from("c"),
+        ])
+        .unwrap();
+        s.rename_col("a", String::from("x")).unwrap();
+        assert_eq!(s.col_idx("x"), Some(0));
+        assert_eq!(s.col_idx("a"), None);
+        assert!(matches!(
+            s.rename_col("x", String::from("b")),
+            Err(LiquidError::NameAlreadyExists)
+        ));
+        assert!(s.rename_col("a", String::from("y")).is_err());
+        s.rename_col("x", String::from("x")).unwrap();
+
+        s.reorder_cols(&[2, 0, 1]).unwrap();
+        assert_eq!(
+            s.schema,
+            vec![DataType::String, DataType::Int, DataType::Float]
+        );
+        assert_eq!(s.col_idx("c"), Some(0));
+        assert_eq!(s.col_idx("x"), Some(1));
+        assert!(matches!(
+            s.reorder_cols(&[0, 0, 1]),
+            Err(LiquidError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            s.reorder_cols(&[0, 3, 1]),
+            Err(LiquidError::ColIndexOutOfBounds)
+        ));
+        assert!(s.reorder_cols(&[0, 1]).is_err());
+        assert_eq!(s.col_idx("c"), Some(0));
+
+        assert_eq!(s.drop_col("x").unwrap(), 1);
+        assert_eq!(s.schema, vec![DataType::String, DataType::Float]);
+        assert_eq!(s.col_idx("b"), Some(1));
+        assert!(s.drop_col("x").is_err());
+    }
 }
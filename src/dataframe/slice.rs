@@ -0,0 +1,179 @@
+//! Defines a `DataFrameSlice`, a lightweight view of a range of rows of a
+//! `LocalDataFrame`.
+use crate::dataframe::{LocalDataFrame, Row, Rower, Schema};
+use crate::error::LiquidError;
+use sorer::dataframe::Data;
+use std::ops::Range;
+
+/// A read-only view of a contiguous range of rows of a [`LocalDataFrame`],
+/// created with [`LocalDataFrame::slice`]. The view shares the columns of
+/// the [`LocalDataFrame`] it was created from and only stores the offset and
+/// length of its rows, so creating one never copies any data. Row indices
+/// of a `DataFrameSlice` are relative to the start of the slice.
+///
+/// Use [`to_local`] to copy the rows of a `DataFrameSlice` into a new
+/// [`LocalDataFrame`], e.g. to split a chunk before sending it to other
+/// nodes.
+///
+/// [`LocalDataFrame`]: struct.LocalDataFrame.html
+/// [`LocalDataFrame::slice`]: struct.LocalDataFrame.html#method.slice
+/// [`to_local`]: struct.DataFrameSlice.html#method.to_local
+#[derive(Clone, Debug)]
+pub struct DataFrameSlice {
+    /// The `LocalDataFrame` this is a view of, whose columns are shared
+    frame: LocalDataFrame,
+    /// The range of rows of `frame` in this view
+    range: Range<usize>,
+}
+
+impl DataFrameSlice {
+    /// Creates a new `DataFrameSlice` of the given `range` of rows of
+    /// `frame`, which must be within the rows of `frame`.
+    pub(crate) fn new(frame: LocalDataFrame, range: Range<usize>) -> Self {
+        DataFrameSlice { frame, range }
+    }
+
+    /// Returns the number of rows in this `DataFrameSlice`.
+    pub fn n_rows(&self) -> usize {
+        self.range.end - self.range.start
+    }
+
+    /// Returns the number of columns in this `DataFrameSlice`.
+    pub fn n_cols(&self) -> usize {
+        self.frame.n_cols()
+    }
+
+    /// Returns the `Schema` of this `DataFrameSlice`.
+    pub fn get_schema(&self) -> &Schema {
+        self.frame.get_schema()
+    }
+
+    /// Get the `Data` at the given `col_idx`, `row_idx` offsets, where
+    /// `row_idx` is relative to the start of this `DataFrameSlice`.
+    pub fn get(
+        &self,
+        col_idx: usize,
+        row_idx: usize,
+    ) -> Result<Data, LiquidError> {
+        if row_idx >= self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        self.frame.get(col_idx, self.range.start + row_idx)
+    }
+
+    /// Set the fields of the given `Row` struct with values from this
+    /// `DataFrameSlice` at the given `row_idx`, which is relative to the
+    /// start of this `DataFrameSlice`.
+    ///
+    /// If `row_idx` is out of bounds, a `LiquidError::RowIndexOutOfBounds`
+    /// is returned, and if the `row` does not have the same schema as this
+    /// `DataFrameSlice`, a `LiquidError::TypeMismatch` is returned.
+    pub fn fill_row(
+        &self,
+        row_idx: usize,
+        row: &mut Row,
+    ) -> Result<(), LiquidError> {
+        if row_idx >= self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        self.frame.fill_row(self.range.start + row_idx, row)?;
+        row.set_idx(row_idx);
+        Ok(())
+    }
+
+    /// Applies the given `rower` to every row of this `DataFrameSlice`
+    /// sequentially, like `LocalDataFrame::map`. `finalize` is called on the
+    /// `rower` after all rows have been visited.
+    pub fn map<T: Rower>(&self, mut rower: T) -> T {
+        let mut row = Row::new(self.get_schema());
+        for row_idx in 0..self.n_rows() {
+            self.fill_row(row_idx, &mut row).unwrap();
+            rower.visit(&row);
+        }
+        rower.finalize()
+    }
+
+    /// Creates a view of the rows from `start` up to, but not including,
+    /// `end` of this `DataFrameSlice`, relative to its start. No data is
+    /// copied.
+    ///
+    /// # Errors
+    /// If `start` is greater than `end` or `end` is greater than the number
+    /// of rows, `LiquidError::RowIndexOutOfBounds` is returned.
+    pub fn slice(&self, start: usize, end: usize) -> Result<Self, LiquidError> {
+        if start > end || end > self.n_rows() {
+            return Err(LiquidError::RowIndexOutOfBounds);
+        }
+        let offset = self.range.start;
+        Ok(DataFrameSlice::new(
+            self.frame.clone(),
+            offset + start..offset + end,
+        ))
+    }
+
+    /// Copies the rows of this `DataFrameSlice` into a new `LocalDataFrame`
+    /// with the same `Schema`.
+    pub fn to_local(&self) -> LocalDataFrame {
+        let mut df = LocalDataFrame::new(self.get_schema());
+        // the range is always within the rows of `self.frame`
+        df.append_rows_from(&self.frame, self.range.clone())
+            .unwrap();
+        df
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sorer::dataframe::Column;
+    use std::sync::Arc;
+
+    struct IntSummer {
+        sum: i64,
+    }
+
+    impl Rower for IntSummer {
+        fn visit(&mut self, row: &Row) -> bool {
+            if let Data::Int(x) = row.get(0).unwrap() {
+                self.sum += x;
+            }
+            true
+        }
+
+        fn join(self, other: Self) -> Self {
+            IntSummer {
+                sum: self.sum + other.sum,
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice() {
+        let df = LocalDataFrame::from(Column::Int((0..10).map(Some).collect()));
+        let slice = df.slice(2, 7).unwrap();
+        assert_eq!(slice.n_rows(), 5);
+        assert_eq!(slice.n_cols(), 1);
+        assert_eq!(slice.get(0, 0).unwrap(), Data::Int(2));
+        assert!(slice.get(0, 5).is_err());
+        assert_eq!(slice.map(IntSummer { sum: 0 }).sum, 20);
+
+        let mut row = Row::new(slice.get_schema());
+        slice.fill_row(4, &mut row).unwrap();
+        assert_eq!(row.get(0).unwrap(), &Data::Int(6));
+        assert_eq!(row.get_idx(), Some(4));
+
+        // slices of slices are relative and share the same columns
+        let inner = slice.slice(1, 3).unwrap();
+        assert_eq!(inner.get(0, 0).unwrap(), Data::Int(3));
+        assert!(Arc::ptr_eq(&inner.frame.data[0], &df.data[0]));
+        assert_eq!(
+            inner.to_local(),
+            LocalDataFrame::from(Column::Int(vec![Some(3), Some(4)]))
+        );
+
+        assert!(slice.slice(3, 6).is_err());
+        assert!(df.slice(5, 4).is_err());
+        assert!(df.slice(0, 11).is_err());
+        assert_eq!(df.slice(10, 10).unwrap().n_rows(), 0);
+    }
+}
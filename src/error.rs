@@ -19,12 +19,15 @@ pub enum LiquidError {
     /// `String`
     #[error("The requested operation doesn't match the schema data type")]
     TypeMismatch,
-    /// A generic error when there is an underlying error with a `TCP`
-    /// connection
-    #[error("Network error")]
+    /// A generic error when there is an underlying `std::io::Error`, e.g.
+    /// with a `TCP` connection. The underlying error is kept so its kind
+    /// (such as `AddrInUse` or `ConnectionRefused`) can be inspected, and is
+    /// returned by `source()`
+    #[error("Network error: {0}")]
     NetworkError(#[from] std::io::Error),
-    /// An error when serializing or deserializing
-    #[error("Serialization/Deserialization Error")]
+    /// An error when serializing or deserializing. The underlying `bincode`
+    /// error is returned by `source()`
+    #[error("Serialization/Deserialization Error: {0}")]
     SerdeError(#[from] Box<bincode::ErrorKind>),
     /// An error when trying to send messages to nodes that are not currently
     /// connected to this node
@@ -44,4 +47,59 @@ pub enum LiquidError {
     /// is starting up and messages other than `ControlMsg`s are received
     #[error("Unexpected Message")]
     UnexpectedMessage,
+    /// An error when a task spawned by a node, e.g. to process messages from
+    /// other nodes, panicked. Contains a description of the failure
+    #[error("A task running on this node failed: {0}")]
+    NodeTaskFailed(String),
+    /// An error when an address is not a valid `IP:Port` socket address,
+    /// e.g. when a hostname is given instead of an `IP`
+    #[error("Invalid address: {0}")]
+    ParseError(#[from] std::net::AddrParseError),
+    /// Attempted to pivot a `DataFrame` that has more than one row with the
+    /// same index value and column value, so there is no single value for
+    /// that cell of the pivoted `DataFrame`
+    #[error("More than one value for the same index and column in a pivot")]
+    DuplicateEntry,
+    /// Attempted to combine data with a schema that doesn't match, e.g. when
+    /// creating a `DataFrame` from many `SoR` files. Contains the name of the
+    /// mismatched file
+    #[error("The schema of {0} does not match the schema of the first file")]
+    SchemaMismatch(String),
+    /// Attempted to cast a `Column` to a type that one of its values can't
+    /// be converted to, e.g. casting the `String` `"hi"` to an `Int`.
+    /// Contains the index of the `Column`
+    #[error("Can not cast column {0} to the requested type")]
+    InvalidCast(usize),
+    /// Attempted to call a function with an argument that is not valid for
+    /// it, e.g. a window size of `0`. Contains a description of the problem
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    /// A column of a `DataFrame` does not match the expected `Schema`, i.e.
+    /// it has a different type or name. Contains the index of the column
+    #[error("Column {0} does not match the expected schema")]
+    ColumnMismatch(usize),
+    /// A `Client` tried to register with the `Server` from the same address
+    /// as a node that is already registered in its network, and the `Server`
+    /// is configured to reject such registrations. Contains the address
+    #[error("A node is already registered at address {0}")]
+    DuplicateAddress(std::net::SocketAddr),
+    /// The channel of blobs sent by other nodes was closed while waiting for
+    /// one, e.g. during a distributed `map` after the connection to another
+    /// node failed
+    #[error("The channel of blobs from other nodes was closed")]
+    BlobChannelClosed,
+    /// Another node failed during an operation that every node takes part
+    /// in, e.g. node 1 could not read the file a `DistributedDataFrame` is
+    /// created from. Contains the description of the error on that node
+    #[error("Another node failed: {0}")]
+    RemoteError(String),
+    /// An error from Arrow while converting data to or from Arrow arrays or
+    /// reading or writing an Arrow IPC file
+    #[cfg(feature = "arrow")]
+    #[error("Arrow error: {0}")]
+    ArrowError(#[from] arrow::error::ArrowError),
+    /// An error from Parquet while reading or writing a Parquet file
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
 }
@@ -1,12 +1,13 @@
 //! The `KVStore` implementation
+use crate::dataframe::{LocalDataFrame, Rower};
 use crate::error::LiquidError;
-use crate::kv::{Key, Value};
+use crate::kv::{Key, StoredValue, Value};
 use crate::network::{Client, FramedStream};
 use crate::{
     BYTES_PER_GB, BYTES_PER_KIB, KV_STORE_CACHE_SIZE_FRACTION,
-    MAX_NUM_CACHED_VALUES,
+    MAX_NUM_CACHED_VALUES, MAX_VALUE_CHUNK_SIZE,
 };
-use bincode::{deserialize, serialize};
+use bincode::{deserialize, deserialize_from, serialize};
 use deepsize::DeepSizeOf;
 use futures::stream::{SelectAll, StreamExt};
 use log::{debug, error, info};
@@ -14,6 +15,9 @@ use lru::LruCache;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use sysinfo::{RefreshKind, System, SystemExt};
 use tokio::sync::{mpsc::Sender, Mutex, Notify, RwLock};
@@ -27,16 +31,28 @@ use tokio::sync::{mpsc::Sender, Mutex, Notify, RwLock};
 /// `T` on a least-recently used basis. There is a hard limit on the cache size
 /// that is set to `1/3` the total memory of the machine.
 ///
+/// `T` can be any type that can be serialized and sent between threads, so
+/// besides data frames a `KVStore` can hold e.g. model weights or the state
+/// of a [`Rower`]. `T` must also implement `DeepSizeOf` so that the memory
+/// used by the cache can be measured.
+///
+/// [`Rower`]: ../dataframe/trait.Rower.html
 /// [`Key`]: struct.Key.html
 /// [`Value`]: type.Key.html
 #[derive(Debug)]
 pub struct KVStore<T> {
-    /// The data owned by this `KVStore`
+    /// The data owned by this `KVStore`. Guarded by a `RwLock` so that any
+    /// number of concurrent `get`s can read it in parallel, only `put`s take
+    /// the write lock.
     data: RwLock<HashMap<Key, Value>>,
     /// An `LRU` cache of deserialized values of type `T` with a hard maximum
     /// memory limit set on construction. Not all cached values belong to this
     /// `KVStore`, some of it may come from other distributed `KVStore`s not
     /// running on this machine.
+    ///
+    /// Every lookup in the cache updates how recently the value was used, so
+    /// it is guarded by a `Mutex`. The lock is only held to clone the `Arc`
+    /// of a cached value, never while deserializing or waiting on the network.
     cache: Mutex<LruCache<Key, Arc<T>>>,
     /// The `network` layer, used to send and receive messages and data with
     /// other `KVStore`s
@@ -45,6 +61,8 @@ pub struct KVStore<T> {
     internal_notifier: Notify,
     /// The `id` of the node this `KVStore` is running on
     pub(crate) id: usize,
+    /// The name of the network this `KVStore` registered with
+    network_name: String,
     /// A channel to send blobs of data to a higher level component, in
     /// `liquid-ml` this would be the `LiquidML` struct
     ///
@@ -53,6 +71,80 @@ pub struct KVStore<T> {
     /// The total amount of memory (in bytes) this `KVStore` is allowed
     /// to keep in its cache
     max_cache_size: u64,
+    /// The responses to [`PutIfAbsent`] messages sent by this `KVStore` that
+    /// have not yet been returned by [`put_if_absent`]
+    ///
+    /// [`PutIfAbsent`]: enum.KVMessage.html#variant.PutIfAbsent
+    /// [`put_if_absent`]: struct.KVStore.html#method.put_if_absent
+    put_if_absent_results: Mutex<HashMap<Key, bool>>,
+    /// The chunks of [`Value`]s that are currently being received from other
+    /// `KVStore`s, in the order they were sent
+    ///
+    /// [`Value`]: type.Key.html
+    partial_data: Mutex<HashMap<Key, Vec<Value>>>,
+    /// Held while the chunks of a [`Value`] are sent, so that the chunks of
+    /// two [`Value`]s sent at once, which may have the same [`Key`], are
+    /// never interleaved. Other messages may still be sent between chunks.
+    ///
+    /// [`Key`]: struct.Key.html
+    /// [`Value`]: type.Key.html
+    chunked_sends: Mutex<()>,
+    /// A description of the first task spawned by this `KVStore` that
+    /// panicked or was cancelled, if any
+    task_failure: Mutex<Option<String>>,
+    /// Notified when a task spawned by this `KVStore` fails
+    task_failure_notifier: Notify,
+    /// Counters of how this `KVStore` has been used, see [`KVStats`]
+    ///
+    /// [`KVStats`]: struct.KVStats.html
+    counters: Counters,
+}
+
+/// A snapshot of the counters of a [`KVStore`], returned by [`stats`]. The
+/// counters are always on and only count from when the [`KVStore`] was
+/// created.
+///
+/// [`KVStore`]: struct.KVStore.html
+/// [`stats`]: struct.KVStore.html#method.stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KVStats {
+    /// The number of `get`s that were answered from the cache or the data
+    /// owned by the [`KVStore`] without going over the network
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    pub local_hits: u64,
+    /// The number of `get`s that had to request the data from another
+    /// [`KVStore`]
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    pub remote_fetches: u64,
+    /// The number of bytes of serialized values and blobs sent to other
+    /// [`KVStore`]s
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    pub bytes_sent: u64,
+    /// The number of bytes of serialized values and blobs received from other
+    /// [`KVStore`]s
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    pub bytes_received: u64,
+}
+
+/// The atomic counters behind [`KVStats`], cheap enough to always be updated
+///
+/// [`KVStats`]: struct.KVStats.html
+#[derive(Debug, Default)]
+struct Counters {
+    local_hits: AtomicU64,
+    remote_fetches: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl Counters {
+    fn add(counter: &AtomicU64, n: usize) {
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
 }
 
 /// Represents the kind of messages that can be sent between distributed
@@ -83,21 +175,45 @@ pub enum KVMessage {
     /// [`Value`]: type.Key.html
     /// [`Get`]: enum.KVMessage.html#variant.Get
     Data(Key, Value),
+    /// A message used to send one chunk of a large [`Value`] in response to
+    /// [`Get`] messages. [`Value`]s larger than a single chunk are sent as a
+    /// sequence of `DataChunk` messages followed by a [`DataEnd`] message so
+    /// that they never have to be framed as one giant message.
+    ///
+    /// [`Value`]: type.Key.html
+    /// [`Get`]: enum.KVMessage.html#variant.Get
+    /// [`DataEnd`]: enum.KVMessage.html#variant.DataEnd
+    DataChunk(Key, Vec<u8>),
+    /// A message used to mark that all [`DataChunk`]s of the [`Value`] for
+    /// the given [`Key`] have been sent
+    ///
+    /// [`Key`]: struct.Key.html
+    /// [`Value`]: type.Key.html
+    /// [`DataChunk`]: enum.KVMessage.html#variant.DataChunk
+    DataEnd(Key),
     /// A message used to share random blobs of data with other nodes. This
     /// provides a lower level interface to facilitate other kinds of messages
     Blob(Vec<u8>),
+    /// A message used to kindly tell other [`KVStore`]s to put the provided
+    /// [`Key`] and [`Value`] in their local store only if the [`Key`] is not
+    /// already present. Is answered with a [`PutIfAbsentResult`] message.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`Key`]: struct.Key.html
+    /// [`Value`]: type.Key.html
+    /// [`PutIfAbsentResult`]: enum.KVMessage.html#variant.PutIfAbsentResult
+    PutIfAbsent(Key, Value),
+    /// A message used to respond to [`PutIfAbsent`] messages with whether
+    /// the [`Value`] was inserted
+    ///
+    /// [`Value`]: type.Key.html
+    /// [`PutIfAbsent`]: enum.KVMessage.html#variant.PutIfAbsent
+    PutIfAbsentResult(Key, bool),
 }
 
 // TODO: remove `DeserializeOwned + 'static`
-impl<
-        T: Serialize
-            + DeserializeOwned
-            + Sync
-            + Send
-            + PartialEq
-            + DeepSizeOf
-            + 'static,
-    > KVStore<T>
+impl<T: Serialize + DeserializeOwned + Sync + Send + DeepSizeOf + 'static>
+    KVStore<T>
 {
     /// Creates a new distributed [`KVStore`]. Note that you likely do not
     /// want to use a [`KVStore`] directly, and instead would have a much
@@ -121,6 +237,9 @@ impl<
     ///    the [`Server`]
     /// - `num_clients`: the number of nodes in the distributed system,
     ///    including this one.
+    /// - `network_name`: the name of the network to register with at the
+    ///    [`Server`]. Only nodes with the same `network_name` connect to each
+    ///    other, so many independent systems can share one [`Server`].
     /// - `wait_for_all_clients`: whether or not to wait for all other nodes
     ///    to connect to this one before returning the new [`KVStore`].
     ///
@@ -135,6 +254,7 @@ impl<
         my_addr: String,
         blob_sender: Sender<Value>,
         num_clients: usize,
+        network_name: String,
     ) -> Arc<Self> {
         let (my_ip, my_port) = {
             let mut iter = my_addr.split(':');
@@ -147,7 +267,7 @@ impl<
             my_ip,
             Some(my_port),
             num_clients,
-            "kvstore".to_string(),
+            network_name.clone(),
         )
         .await
         .unwrap();
@@ -170,12 +290,19 @@ impl<
             network,
             internal_notifier: Notify::new(),
             id,
+            network_name,
             blob_sender,
             max_cache_size: max_cache_size as u64,
+            put_if_absent_results: Mutex::new(HashMap::new()),
+            partial_data: Mutex::new(HashMap::new()),
+            chunked_sends: Mutex::new(()),
+            task_failure: Mutex::new(None),
+            task_failure_notifier: Notify::new(),
+            counters: Counters::default(),
         });
 
         let kv_clone = kv.clone();
-        tokio::spawn(async move {
+        kv.spawn_watched(async move {
             KVStore::process_messages(kv_clone, read_streams)
                 .await
                 .unwrap();
@@ -203,10 +330,12 @@ impl<
     /// [`LiquidError::NotPresent`]: ../error/enum.LiquidError.html#variant.NotPresent
     pub async fn get(&self, key: &Key) -> Result<Arc<T>, LiquidError> {
         if let Some(val) = { self.cache.lock().await.get(key) } {
+            Counters::add(&self.counters.local_hits, 1);
             return Ok(val.clone());
         }
 
         let serialized_val = self.get_raw(key).await?;
+        Counters::add(&self.counters.local_hits, 1);
         let value: Arc<T> = Arc::new(deserialize(&serialized_val[..])?);
         let v = value.clone();
         self.add_to_cache(key.clone(), v).await?;
@@ -238,6 +367,7 @@ impl<
     /// [`put`]: struct.KVStore.html#method.put
     pub async fn wait_and_get(&self, key: &Key) -> Result<Arc<T>, LiquidError> {
         if let Some(val) = { self.cache.lock().await.get(key) } {
+            Counters::add(&self.counters.local_hits, 1);
             return Ok(val.clone());
         }
 
@@ -250,6 +380,7 @@ impl<
             }
             // get the raw serialized data, its guaranteed to be there
             let serialized_val = self.get_raw(key).await?;
+            Counters::add(&self.counters.local_hits, 1);
             let value: Arc<T> = Arc::new(deserialize(&serialized_val[..])?);
             let v = value.clone();
             // update our LRU cache
@@ -266,13 +397,15 @@ impl<
                     .send_msg(key.home, KVMessage::Get(key.clone()))
                     .await?;
             }
-            while { self.cache.lock().await.get(key) } == None {
+            Counters::add(&self.counters.remote_fetches, 1);
+            loop {
+                if let Some(val) = { self.cache.lock().await.get(key) } {
+                    return Ok(val.clone());
+                }
                 // while the data is not yet in our cache, wait for the
                 // message processing task to notify when it is there
                 self.internal_notifier.notified().await;
             }
-            // it's guaranteed to be in the cache, we can get it
-            self.get(key).await
         }
     }
 
@@ -308,12 +441,81 @@ impl<
             Ok(opt_old_data)
         } else {
             let target_id = key.home;
+            Counters::add(&self.counters.bytes_sent, serial.len());
             let msg = KVMessage::Put(key, serial);
             self.network.lock().await.send_msg(target_id, msg).await?;
             Ok(None)
         }
     }
 
+    /// Puts the data held in `value` to the [`KVStore`] with the `id` in
+    /// `key.home`, but only if that [`KVStore`] does not already have the
+    /// `key` present. Returns `Ok(true)` if the `value` was inserted and
+    /// `Ok(false)` if the `key` already existed, in which case the existing
+    /// [`Value`] is left unchanged.
+    ///
+    /// The check and the insert happen atomically under the lock of the
+    /// [`KVStore`] that owns the `key`, so concurrent producers can't clobber
+    /// each other. If `key` belongs to another [`KVStore`], a [`PutIfAbsent`]
+    /// message is sent and `await`ing this method will block until that
+    /// [`KVStore`] responds. Concurrent calls for the same remote `key` from
+    /// the same node are not supported.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`Value`]: type.Key.html
+    /// [`PutIfAbsent`]: enum.KVMessage.html#variant.PutIfAbsent
+    pub async fn put_if_absent(
+        &self,
+        key: Key,
+        value: T,
+    ) -> Result<bool, LiquidError> {
+        let serial = serialize(&value)?;
+        if key.home == self.id {
+            let inserted = self.insert_if_absent(key.clone(), serial).await;
+            if inserted {
+                self.add_to_cache(key, Arc::new(value)).await?;
+            }
+            Ok(inserted)
+        } else {
+            let target_id = key.home;
+            Counters::add(&self.counters.bytes_sent, serial.len());
+            let msg = KVMessage::PutIfAbsent(key.clone(), serial);
+            {
+                self.network.lock().await.send_msg(target_id, msg).await?;
+            }
+            loop {
+                let result =
+                    { self.put_if_absent_results.lock().await.remove(&key) };
+                match result {
+                    Some(inserted) => return Ok(inserted),
+                    // wait for the message processing task to notify us
+                    // when the result is there
+                    None => self.internal_notifier.notified().await,
+                }
+            }
+        }
+    }
+
+    /// Removes the value for the given `key` from this [`KVStore`], from its
+    /// data if this [`KVStore`] owns the `key`, and from its cache either
+    /// way. Returns the serialized [`Value`] if this [`KVStore`] owned it.
+    ///
+    /// Only this [`KVStore`] is changed, so to remove a `key` for good every
+    /// [`KVStore`] that may have cached it must remove it, e.g. when every
+    /// node drops the chunks of a `DistributedDataFrame` that was replaced.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`Value`]: type.Key.html
+    pub async fn remove(&self, key: &Key) -> Option<Value> {
+        let removed = if key.home == self.id {
+            self.data.write().await.remove(key)
+        } else {
+            None
+        };
+        self.cache.lock().await.pop(key);
+        removed
+    }
+
     /// Sends the given `blob` to the [`KVStore`] with the given `target_id`
     /// This provides a lower level interface to facilitate other kinds of
     /// messages
@@ -324,6 +526,7 @@ impl<
         target_id: usize,
         blob: Value,
     ) -> Result<(), LiquidError> {
+        Counters::add(&self.counters.bytes_sent, blob.len());
         self.network
             .lock()
             .await
@@ -331,6 +534,130 @@ impl<
             .await
     }
 
+    /// Sends the given `blob` to every other [`KVStore`] in the network, e.g.
+    /// to share updated model weights with all nodes at the end of each
+    /// iteration of a training loop. Each node receives the blob the same way
+    /// as one sent with [`send_blob`], which in `liquid_ml` means on the
+    /// `blob_receiver` of its [`LiquidML`] struct.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`send_blob`]: struct.KVStore.html#method.send_blob
+    /// [`LiquidML`]: ../struct.LiquidML.html
+    pub async fn broadcast_blob(&self, blob: Value) -> Result<(), LiquidError> {
+        let mut network = self.network.lock().await;
+        let n_peers = network.directory.len();
+        Counters::add(&self.counters.bytes_sent, blob.len() * n_peers);
+        network.broadcast(KVMessage::Blob(blob)).await
+    }
+
+    /// Serializes the given `value` and sends it as a blob to the [`KVStore`]
+    /// with the given `target_id`, so that callers don't have to serialize
+    /// it themselves. The receiving node can deserialize it back into a `V`
+    /// with [`LiquidML::recv_typed`].
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`LiquidML::recv_typed`]: ../struct.LiquidML.html#method.recv_typed
+    pub async fn send_typed<V: Serialize>(
+        &self,
+        target_id: usize,
+        value: &V,
+    ) -> Result<(), LiquidError> {
+        self.send_blob(target_id, serialize(value)?).await
+    }
+
+    /// Returns the `id` of the node this [`KVStore`] is running on, which is
+    /// the `id` assigned to its [`Client`] by the registration [`Server`].
+    /// It never changes once the [`KVStore`] has been created.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`Client`]: ../network/struct.Client.html
+    /// [`Server`]: ../network/struct.Server.html
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the name of the network this [`KVStore`] registered with at
+    /// the [`Server`].
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`Server`]: ../network/struct.Server.html
+    pub fn network_name(&self) -> &str {
+        &self.network_name
+    }
+
+    /// Returns a snapshot of the counters of this [`KVStore`], which can be
+    /// used to see how much data is being moved over the network, e.g. when
+    /// tuning a `map`.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    pub fn stats(&self) -> KVStats {
+        KVStats {
+            local_hits: self.counters.local_hits.load(Ordering::Relaxed),
+            remote_fetches: self
+                .counters
+                .remote_fetches
+                .load(Ordering::Relaxed),
+            bytes_sent: self.counters.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self
+                .counters
+                .bytes_received
+                .load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns a `LiquidError::NodeTaskFailed` if any task spawned by this
+    /// [`KVStore`], such as the task that processes messages from other
+    /// nodes, has panicked. Returns `Ok(())` otherwise without waiting.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    pub async fn check_tasks(&self) -> Result<(), LiquidError> {
+        match &*self.task_failure.lock().await {
+            Some(failure) => Err(LiquidError::NodeTaskFailed(failure.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Waits until a task spawned by this [`KVStore`] fails and returns the
+    /// `LiquidError::NodeTaskFailed` describing the failure. Useful to
+    /// `select` on alongside other work so a failed task surfaces as an
+    /// error instead of a hang.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    pub async fn task_failed(&self) -> LiquidError {
+        loop {
+            if let Err(e) = self.check_tasks().await {
+                return e;
+            }
+            self.task_failure_notifier.notified().await;
+        }
+    }
+
+    /// Spawns the given `task` and a second task that watches it, recording
+    /// the failure so it can be observed by [`check_tasks`] and
+    /// [`task_failed`] if the `task` panics.
+    ///
+    /// [`check_tasks`]: struct.KVStore.html#method.check_tasks
+    /// [`task_failed`]: struct.KVStore.html#method.task_failed
+    fn spawn_watched<F>(self: &Arc<Self>, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(task);
+        let kv = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle.await {
+                error!("A task on KVStore {} failed: {}", kv.id, e);
+                {
+                    let mut failure = kv.task_failure.lock().await;
+                    if failure.is_none() {
+                        *failure = Some(e.to_string());
+                    }
+                }
+                kv.task_failure_notifier.notify();
+            }
+        });
+    }
+
     /// Processes messages from the queue that is populated by a [`Client`].
     ///
     /// This method processes the messages by doing the following:
@@ -343,8 +670,14 @@ impl<
     ///       [`wait_and_get`] to get the data, either internally from this
     ///       [`KVStore`] or externally over the network from another one. Once
     ///       we have the data, respond with a [`Data`] message containing the
-    ///       requested data.
+    ///       requested data, or with a sequence of [`DataChunk`] messages and
+    ///       a [`DataEnd`] message if the data is too large for one message.
     ///    - [`Data`] message: Deserialize the data and put it into our cache
+    ///    - [`DataChunk`] message: Save the chunk until all chunks of the data
+    ///       have been received. Chunks are saved without spawning a task so
+    ///       that they stay in the order they were sent in.
+    ///    - [`DataEnd`] message: Deserialize the data directly from its saved
+    ///       chunks and put it into our cache
     ///    - [`Put`] message: add the given data to our internal store
     ///    - [`Blob`] message: send the data up a higher level similar to how
     ///       the [`Client`] processes messages
@@ -352,6 +685,8 @@ impl<
     /// [`wait_and_get`]: struct.KVStore.html#method.wait_and_get
     /// [`mpsc`]: https://docs.rs/tokio/0.2.18/tokio/sync/mpsc/fn.channel.html
     /// [`Data`]: enum.KVMessage.html#variant.Data
+    /// [`DataChunk`]: enum.KVMessage.html#variant.DataChunk
+    /// [`DataEnd`]: enum.KVMessage.html#variant.DataEnd
     /// [`Put`]: enum.KVMessage.html#variant.Put
     /// [`Blob`]: enum.KVMessage.html#variant.Blob
     /// [`Client`]: ../network/struct.Client.html
@@ -361,22 +696,20 @@ impl<
         mut streams: SelectAll<FramedStream<KVMessage>>,
     ) -> Result<(), LiquidError> {
         while let Some(Ok(msg)) = streams.next().await {
+            if let KVMessage::DataChunk(..) | KVMessage::DataEnd(_) = msg.msg {
+                self.reassemble(msg.msg).await;
+                continue;
+            }
             let mut blob_sender_clone = self.blob_sender.clone();
             let kv = self.clone();
-            tokio::spawn(async move {
+            self.spawn_watched(async move {
                 match msg.msg {
                     KVMessage::Get(k) => {
                         // This must wait until it has the data to respond
-                        let v = kv.wait_and_get_raw(&k).await.unwrap();
-                        let response = KVMessage::Data(k, v);
-                        kv.network
-                            .lock()
-                            .await
-                            .send_msg(msg.sender_id, response)
-                            .await
-                            .unwrap();
+                        kv.send_value(msg.sender_id, k).await.unwrap();
                     }
                     KVMessage::Data(k, v) => {
+                        Counters::add(&kv.counters.bytes_received, v.len());
                         let v: Arc<T> = Arc::new(deserialize(&v).unwrap());
                         kv.add_to_cache(k, v).await.unwrap();
                         kv.internal_notifier.notify();
@@ -387,19 +720,164 @@ impl<
                             panic!();
                         }
                         debug!("Put key: {:#?} into KVStore", k.clone());
+                        Counters::add(&kv.counters.bytes_received, v.len());
                         kv.data.write().await.insert(k, v);
                         kv.internal_notifier.notify();
                     }
                     KVMessage::Blob(v) => {
+                        Counters::add(&kv.counters.bytes_received, v.len());
                         blob_sender_clone.send(v).await.unwrap();
                     }
+                    KVMessage::PutIfAbsent(k, v) => {
+                        if k.home != kv.id {
+                            error!("Someone tried to `put` the key {:?} on the wrong KV", k);
+                            panic!();
+                        }
+                        Counters::add(&kv.counters.bytes_received, v.len());
+                        let inserted = kv.insert_if_absent(k.clone(), v).await;
+                        let response =
+                            KVMessage::PutIfAbsentResult(k, inserted);
+                        kv.network
+                            .lock()
+                            .await
+                            .send_msg(msg.sender_id, response)
+                            .await
+                            .unwrap();
+                    }
+                    KVMessage::PutIfAbsentResult(k, inserted) => {
+                        kv.put_if_absent_results
+                            .lock()
+                            .await
+                            .insert(k, inserted);
+                        kv.internal_notifier.notify();
+                    }
+                    KVMessage::DataChunk(..) | KVMessage::DataEnd(_) => {
+                        unreachable!()
+                    }
                 }
             });
         }
-        dbg!("no longer processing");
+        info!("KVStore {} is no longer processing messages", self.id);
         Ok(())
     }
 
+    /// Saves a [`DataChunk`] until the [`DataEnd`] message for its [`Key`] is
+    /// received, at which point the [`Value`] is deserialized straight from
+    /// its chunks, without first joining them together, and put into our
+    /// cache.
+    ///
+    /// [`Key`]: struct.Key.html
+    /// [`Value`]: type.Key.html
+    /// [`DataChunk`]: enum.KVMessage.html#variant.DataChunk
+    /// [`DataEnd`]: enum.KVMessage.html#variant.DataEnd
+    async fn reassemble(self: &Arc<Self>, msg: KVMessage) {
+        match msg {
+            KVMessage::DataChunk(k, chunk) => {
+                Counters::add(&self.counters.bytes_received, chunk.len());
+                self.partial_data
+                    .lock()
+                    .await
+                    .entry(k)
+                    .or_insert_with(Vec::new)
+                    .push(chunk);
+            }
+            KVMessage::DataEnd(k) => {
+                let chunks = { self.partial_data.lock().await.remove(&k) }
+                    .unwrap_or_default();
+                let kv = self.clone();
+                self.spawn_watched(async move {
+                    let v: Arc<T> = Arc::new(
+                        deserialize_from(ChunkReader::new(&chunks)).unwrap(),
+                    );
+                    kv.add_to_cache(k, v).await.unwrap();
+                    kv.internal_notifier.notify();
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sends the serialized [`Value`] for the given `key` to the [`KVStore`]
+    /// with the given `target_id`, waiting until we have the data if we
+    /// don't already. [`Value`]s larger than `MAX_VALUE_CHUNK_SIZE` bytes
+    /// are sent in chunks. The [`Value`] is copied out of our data first, so
+    /// `put`s aren't blocked while it is sent, and the lock on our
+    /// [`Client`] is taken once per chunk, so other messages aren't blocked
+    /// until the last chunk is sent.
+    ///
+    /// [`Value`]: type.Key.html
+    /// [`KVStore`]: struct.KVStore.html
+    /// [`Client`]: ../network/struct.Client.html
+    async fn send_value(
+        &self,
+        target_id: usize,
+        key: Key,
+    ) -> Result<(), LiquidError> {
+        if key.home != self.id {
+            let v = self.wait_and_get_raw(&key).await?;
+            Counters::add(&self.counters.bytes_sent, v.len());
+            let response = KVMessage::Data(key, v);
+            return self
+                .network
+                .lock()
+                .await
+                .send_msg(target_id, response)
+                .await;
+        }
+
+        let blob = loop {
+            let blob = self.data.read().await.get(&key).cloned();
+            if let Some(blob) = blob {
+                break blob;
+            }
+            // wait for the message processing task to notify us when the
+            // data is there
+            self.internal_notifier.notified().await;
+        };
+
+        Counters::add(&self.counters.bytes_sent, blob.len());
+        if blob.len() <= MAX_VALUE_CHUNK_SIZE {
+            let response = KVMessage::Data(key, blob);
+            return self
+                .network
+                .lock()
+                .await
+                .send_msg(target_id, response)
+                .await;
+        }
+        let _chunked_send = self.chunked_sends.lock().await;
+        for chunk in blob.chunks(MAX_VALUE_CHUNK_SIZE) {
+            let msg = KVMessage::DataChunk(key.clone(), chunk.to_vec());
+            self.network.lock().await.send_msg(target_id, msg).await?;
+        }
+        self.network
+            .lock()
+            .await
+            .send_msg(target_id, KVMessage::DataEnd(key))
+            .await
+    }
+
+    /// Inserts the serialized `value` into this [`KVStore`] if the `key` is
+    /// not already present, returning whether it was inserted.
+    ///
+    /// [`KVStore`]: struct.KVStore.html
+    async fn insert_if_absent(&self, key: Key, value: Value) -> bool {
+        let inserted = {
+            let mut unlocked = self.data.write().await;
+            if unlocked.contains_key(&key) {
+                false
+            } else {
+                debug!("Put key: {:#?} into KVStore", key.clone());
+                unlocked.insert(key, value);
+                true
+            }
+        };
+        if inserted {
+            self.internal_notifier.notify();
+        }
+        inserted
+    }
+
     /// Gets serialized blobs out of this [`KVStore`]
     ///
     /// [`KVStore`]: struct.KVStore.html
@@ -471,3 +949,118 @@ impl<
         Ok(())
     }
 }
+
+/// Typed accessors for a [`KVStore`] that holds different kinds of
+/// [`StoredValue`]s under one store.
+///
+/// [`KVStore`]: struct.KVStore.html
+/// [`StoredValue`]: enum.StoredValue.html
+impl KVStore<StoredValue> {
+    /// Puts the given `frame` under `key` as a `StoredValue::Frame`, see
+    /// [`put`].
+    ///
+    /// [`put`]: struct.KVStore.html#method.put
+    pub async fn put_frame(
+        &self,
+        key: Key,
+        frame: LocalDataFrame,
+    ) -> Result<Option<Value>, LiquidError> {
+        self.put(key, StoredValue::Frame(frame)).await
+    }
+
+    /// Gets the `LocalDataFrame` stored under `key`, see [`get`]. Returns a
+    /// `LiquidError::TypeMismatch` if the value is not a `Frame`.
+    ///
+    /// [`get`]: struct.KVStore.html#method.get
+    pub async fn get_frame(
+        &self,
+        key: &Key,
+    ) -> Result<LocalDataFrame, LiquidError> {
+        match self.get(key).await?.as_ref() {
+            StoredValue::Frame(frame) => Ok(frame.clone()),
+            _ => Err(LiquidError::TypeMismatch),
+        }
+    }
+
+    /// Puts the given `blob` under `key` as a `StoredValue::Blob`, see
+    /// [`put`].
+    ///
+    /// [`put`]: struct.KVStore.html#method.put
+    pub async fn put_blob(
+        &self,
+        key: Key,
+        blob: Vec<u8>,
+    ) -> Result<Option<Value>, LiquidError> {
+        self.put(key, StoredValue::Blob(blob)).await
+    }
+
+    /// Gets the blob stored under `key`, see [`get`]. Returns a
+    /// `LiquidError::TypeMismatch` if the value is not a `Blob`.
+    ///
+    /// [`get`]: struct.KVStore.html#method.get
+    pub async fn get_blob(&self, key: &Key) -> Result<Vec<u8>, LiquidError> {
+        match self.get(key).await?.as_ref() {
+            StoredValue::Blob(blob) => Ok(blob.clone()),
+            _ => Err(LiquidError::TypeMismatch),
+        }
+    }
+
+    /// Serializes the given `rower` and puts it under `key` as a
+    /// `StoredValue::Rower`, see [`put`].
+    ///
+    /// [`put`]: struct.KVStore.html#method.put
+    pub async fn put_rower<R: Rower + Serialize>(
+        &self,
+        key: Key,
+        rower: &R,
+    ) -> Result<Option<Value>, LiquidError> {
+        self.put(key, StoredValue::Rower(serialize(rower)?)).await
+    }
+
+    /// Gets and deserializes the `Rower` stored under `key`, see [`get`].
+    /// Returns a `LiquidError::TypeMismatch` if the value is not a `Rower`.
+    ///
+    /// [`get`]: struct.KVStore.html#method.get
+    pub async fn get_rower<R: Rower + DeserializeOwned>(
+        &self,
+        key: &Key,
+    ) -> Result<R, LiquidError> {
+        match self.get(key).await?.as_ref() {
+            StoredValue::Rower(rower) => Ok(deserialize(&rower[..])?),
+            _ => Err(LiquidError::TypeMismatch),
+        }
+    }
+}
+
+/// Reads the bytes of a sequence of chunks as if they were one contiguous
+/// blob, so that a chunked [`Value`] can be deserialized without copying its
+/// chunks into a single buffer first
+///
+/// [`Value`]: type.Key.html
+struct ChunkReader<'a> {
+    /// The chunks that have not been started yet
+    chunks: std::slice::Iter<'a, Value>,
+    /// The unread bytes of the current chunk
+    cur: &'a [u8],
+}
+
+impl<'a> ChunkReader<'a> {
+    fn new(chunks: &'a [Value]) -> Self {
+        ChunkReader {
+            chunks: chunks.iter(),
+            cur: &[],
+        }
+    }
+}
+
+impl Read for ChunkReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.cur.is_empty() {
+            match self.chunks.next() {
+                Some(chunk) => self.cur = chunk,
+                None => return Ok(0),
+            }
+        }
+        self.cur.read(buf)
+    }
+}
@@ -23,6 +23,12 @@
 //!    [`Rower`](../dataframe/trait.Rower.html)s
 //!    (and other use cases) in a
 //!    [`DistributedDataFrame`](../dataframe/struct.DistributedDataFrame.html)
+//! - [`send_typed`]: serializes any value and sends it with [`send_blob`]
+//! - [`broadcast_blob`]: sends a blob to every other node in the network
+//!
+//! A `KVStore<StoredValue>` holds data frames, blobs, and serialized
+//! [`Rower`](../dataframe/trait.Rower.html)s under one store, and provides
+//! typed accessors such as [`get_frame`] and [`put_frame`].
 //!
 //!
 //!
@@ -33,15 +39,22 @@
 //! [`wait_and_get`]: struct.KVStore.html#method.wait_and_get
 //! [`put`]: struct.KVStore.html#method.put
 //! [`send_blob`]: struct.KVStore.html#method.send_blob
+//! [`send_typed`]: struct.KVStore.html#method.send_typed
+//! [`broadcast_blob`]: struct.KVStore.html#method.broadcast_blob
+//! [`get_frame`]: struct.KVStore.html#method.get_frame
+//! [`put_frame`]: struct.KVStore.html#method.put_frame
 //! [`KVMessage`]: enum.KVMessage.html
 //! [`Data`]: enum.KVMessage.html#variant.Data
 //! [`Put`]: enum.KVMessage.html#variant.Put
 //! [`Blob`]: enum.KVMessage.html#variant.Blob
+use crate::dataframe::LocalDataFrame;
+use deepsize::DeepSizeOf;
 use rand::{self, Rng};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 mod kv_store;
-pub use crate::kv::kv_store::{KVMessage, KVStore};
+pub use crate::kv::kv_store::{KVMessage, KVStats, KVStore};
 
 /// A `Key` defines where in a [`KVStore`] a [`Value`] is stored, as well as
 /// which node (and thus which [`KVStore`]) 'owns' the [`Value`]
@@ -67,6 +80,27 @@ pub struct Key {
 /// [`KVStore`]: struct.KVStore.html
 pub type Value = Vec<u8>;
 
+/// A value that can be held in a [`KVStore`] alongside values of other
+/// kinds, so that data frames, raw blobs, and serialized [`Rower`]s can share
+/// one `KVStore<StoredValue>`. Use the typed accessors such as [`get_frame`]
+/// and [`put_frame`] to work with a specific kind.
+///
+/// [`KVStore`]: struct.KVStore.html
+/// [`Rower`]: ../dataframe/trait.Rower.html
+/// [`get_frame`]: struct.KVStore.html#method.get_frame
+/// [`put_frame`]: struct.KVStore.html#method.put_frame
+#[derive(Serialize, Deserialize, Debug, Clone, DeepSizeOf)]
+pub enum StoredValue {
+    /// A data frame
+    Frame(LocalDataFrame),
+    /// A raw blob of bytes
+    Blob(Vec<u8>),
+    /// A serialized [`Rower`]
+    ///
+    /// [`Rower`]: ../dataframe/trait.Rower.html
+    Rower(Vec<u8>),
+}
+
 impl Key {
     /// Creates a new [`Key`] that is owned by the [`KVStore`] running on the
     /// node with id equal to `home`. The given `name` defines where in the
@@ -90,3 +124,29 @@ impl Key {
         }
     }
 }
+
+impl fmt::Display for Key {
+    /// Formats this [`Key`] as `name@home`
+    ///
+    /// [`Key`]: struct.Key.html
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.home)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_key_in_map_and_display() {
+        let mut map = HashMap::new();
+        map.insert(Key::new("foo", 1), 1);
+        map.insert(Key::new("foo", 2), 2);
+        map.insert(Key::new("foo", 1), 3);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&Key::new("foo", 1)), Some(&3));
+        assert_eq!(format!("{}", Key::new("foo", 2)), "foo@2");
+    }
+}
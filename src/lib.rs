@@ -107,13 +107,16 @@
 //! Registration process from [`Client`] perspective:
 //! 1. Connect to the [`Server`]
 //! 2. Send the [`Server`] a `Message<ControlMsg::Introduction>` message
-//!    containing the `IP:Port` and `network_name` for this [`Client`]
+//!    containing the `IP:Port`, `network_name` and expected number of nodes
+//!    for this [`Client`]
 //! 3. The [`Server`] will respond with the `Message<ControlMsg::Directory>`
 //!    message containing the `IP:Port` of all other currently connected
 //!    [`Client`]s in that network.
 //! 4. The newly created [`Client`] connects to all other existing [`Client`]s.
 //! 5. The `Client` waits for all other `Client`s that have not yet started to
 //!    connect to it, unless we have connected to all the nodes.
+//! 6. The `Client` waits for the `Message<ControlMsg::NetworkReady>` that the
+//!    [`Server`] broadcasts once all the nodes in the network have registered.
 //!
 //! ### Server
 //! The [`Server`] asynchronously registers new [`Client`]s via
@@ -153,6 +156,11 @@
 //! operations are row-wise processing, but data is held in columnar format
 //! to avoid boxed types and reduced memory usage.
 //!
+//! Converting a [`LocalDataFrame`] to and from Arrow `RecordBatch`es and IPC
+//! files requires the `arrow` feature, and reading and writing Parquet files
+//! requires the `parquet` feature, which also enables `arrow`. Both are off
+//! by default so that crates that don't need them don't build Arrow.
+//!
 //! ### [`DistributedDataFrame`]
 //!
 //! A [`DistributedDataFrame`] is an abstraction over a distributed system of
@@ -405,3 +413,14 @@ pub(crate) const BYTES_PER_KIB: f64 = 1_024.0;
 pub(crate) const BYTES_PER_GB: f64 = 1_073_741_824.0;
 pub(crate) const KV_STORE_CACHE_SIZE_FRACTION: f64 = 0.33;
 pub(crate) const MAX_FRAME_LEN_FRACTION: f64 = 0.8;
+pub(crate) const MIN_COLS_FOR_PARALLEL_PARSE: usize = 4;
+pub(crate) const MIN_BLOB_CHANNEL_CAPACITY: usize = 20;
+pub(crate) const MAX_VALUE_CHUNK_SIZE: usize = 8_388_608;
+pub(crate) const DEFAULT_NETWORK_NAME: &str = "kvstore";
+pub(crate) const SERVER_RECONNECT_ATTEMPTS: usize = 10;
+pub(crate) const SERVER_RECONNECT_BACKOFF_MS: u64 = 100;
+pub(crate) const PEER_CONNECT_ATTEMPTS: usize = 10;
+pub(crate) const PEER_CONNECT_BACKOFF_MS: u64 = 50;
+pub(crate) const SOR_SCHEMA_INFERENCE_ROWS: usize = 500;
+pub(crate) const DISPLAY_MAX_ROWS: usize = 10;
+pub(crate) const DISPLAY_MAX_COL_WIDTH: usize = 20;
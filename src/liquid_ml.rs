@@ -1,10 +1,18 @@
 //! This module defines the implementation of the highest level component in
 //! a `liquid_ml` system.
-use crate::dataframe::{Column, DistributedDataFrame, LocalDataFrame, Rower};
+use crate::dataframe::{
+    Agg, Column, CsvOptions, Data, DataType, DescribeRower,
+    DistributedDataFrame, GroupByRower, JoinKind, LocalDataFrame, Row, Rower,
+    Window,
+};
 use crate::error::LiquidError;
 use crate::kv::KVStore;
+use crate::{DEFAULT_NETWORK_NAME, MIN_BLOB_CHANNEL_CAPACITY};
+use bincode::deserialize;
+use futures::future;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::cmp;
 use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
@@ -54,22 +62,108 @@ pub struct LiquidML {
 
 impl LiquidML {
     /// Create a new `liquid_ml` application that runs at `my_addr` and will
-    /// wait to connect to `num_nodes` nodes before returning.
+    /// wait to connect to `num_nodes` nodes before returning. The nodes join
+    /// the default network of the registration [`Server`], use
+    /// [`with_network_name`] to run more than one application against the
+    /// same [`Server`].
+    ///
+    /// The `blob_receiver` channel is created with a capacity of `num_nodes`
+    /// or `MIN_BLOB_CHANNEL_CAPACITY` blobs, whichever is larger. Use
+    /// [`with_blob_capacity`] to choose a different capacity.
+    ///
+    /// [`with_blob_capacity`]: struct.LiquidML.html#method.with_blob_capacity
+    /// [`with_network_name`]: struct.LiquidML.html#method.with_network_name
+    /// [`Server`]: network/struct.Server.html
     pub async fn new(
         my_addr: &str,
         server_addr: &str,
         num_nodes: usize,
     ) -> Result<Self, LiquidError> {
-        let (blob_sender, blob_receiver) = mpsc::channel(20);
+        let blob_capacity = cmp::max(num_nodes, MIN_BLOB_CHANNEL_CAPACITY);
+        LiquidML::with_blob_capacity(
+            my_addr,
+            server_addr,
+            num_nodes,
+            blob_capacity,
+        )
+        .await
+    }
+
+    /// Create a new `liquid_ml` application that runs at `my_addr` and will
+    /// wait to connect to `num_nodes` nodes before returning. The
+    /// `blob_receiver` will buffer up to `blob_capacity` blobs.
+    ///
+    /// # Backpressure
+    /// Each blob received from another node is forwarded to the
+    /// `blob_receiver` by its own task spawned by the `KVStore`. Once
+    /// `blob_capacity` blobs are waiting to be received, the tasks of any
+    /// further blobs wait until there is space in the channel. The `KVStore`
+    /// keeps processing other messages in the meantime, so a small capacity
+    /// does not stall the network, but every waiting blob stays in memory
+    /// until it is received. If many nodes may send blobs to this node at the
+    /// same time (e.g. when all nodes send results to node 1), a
+    /// `blob_capacity` of at least `num_nodes` lets them all be buffered.
+    pub async fn with_blob_capacity(
+        my_addr: &str,
+        server_addr: &str,
+        num_nodes: usize,
+        blob_capacity: usize,
+    ) -> Result<Self, LiquidError> {
+        LiquidML::connect(
+            my_addr,
+            server_addr,
+            num_nodes,
+            blob_capacity,
+            DEFAULT_NETWORK_NAME,
+        )
+        .await
+    }
+
+    /// Create a new `liquid_ml` application that runs at `my_addr` and will
+    /// wait to connect to `num_nodes` nodes in the network with the given
+    /// `network_name` before returning. Nodes only connect to other nodes in
+    /// the same network, so many independent `liquid_ml` jobs can share one
+    /// registration [`Server`] as long as each job uses its own
+    /// `network_name`.
+    ///
+    /// [`Server`]: network/struct.Server.html
+    pub async fn with_network_name(
+        my_addr: &str,
+        server_addr: &str,
+        num_nodes: usize,
+        network_name: &str,
+    ) -> Result<Self, LiquidError> {
+        let blob_capacity = cmp::max(num_nodes, MIN_BLOB_CHANNEL_CAPACITY);
+        LiquidML::connect(
+            my_addr,
+            server_addr,
+            num_nodes,
+            blob_capacity,
+            network_name,
+        )
+        .await
+    }
+
+    /// Connects a new `liquid_ml` application to the network with the given
+    /// `network_name`, buffering up to `blob_capacity` blobs.
+    async fn connect(
+        my_addr: &str,
+        server_addr: &str,
+        num_nodes: usize,
+        blob_capacity: usize,
+        network_name: &str,
+    ) -> Result<Self, LiquidError> {
+        let (blob_sender, blob_receiver) = mpsc::channel(blob_capacity);
         let kill_notifier = Arc::new(Notify::new());
         let kv = KVStore::new(
             server_addr.to_string(),
             my_addr.to_string(),
             blob_sender,
             num_nodes,
+            network_name.to_string(),
         )
         .await;
-        let node_id = kv.id;
+        let node_id = kv.id();
         let (my_ip, _my_port) = {
             let mut iter = my_addr.split(':');
             let first = iter.next().unwrap();
@@ -157,6 +251,78 @@ impl LiquidML {
         Ok(())
     }
 
+    /// Create a new data frame with the given name from a CSV file read with
+    /// the given `options`, which is assumed to only exist on node 1. Node 1
+    /// will parse the file into chunks in the same way as [`df_from_sor`],
+    /// so that each node gets about the same number of records, and
+    /// distribute them to all the other nodes. The columns are named after
+    /// the header of the file, if there is one.
+    ///
+    /// `await`ing this function will block until the data is completely
+    /// distributed on all nodes. After the data is distributed, each node
+    /// of this distributed `liquid_ml` system will have their `LiquidML`
+    /// struct updated with the information of the new [`DistributedDataFrame`]
+    ///
+    /// **NOTE**: `df_name` must be unique.
+    ///
+    /// [`df_from_sor`]: struct.LiquidML.html#method.df_from_sor
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    pub async fn df_from_csv(
+        &mut self,
+        df_name: &str,
+        file_name: &str,
+        options: &CsvOptions,
+    ) -> Result<(), LiquidError> {
+        let ddf = DistributedDataFrame::from_csv(
+            &self.server_addr,
+            &self.my_ip,
+            file_name,
+            options,
+            self.kv.clone(),
+            df_name,
+            self.num_nodes,
+        )
+        .await?;
+        self.data_frames.insert(df_name.to_string(), ddf);
+        Ok(())
+    }
+
+    /// Create a new data frame with the given name from a Parquet file which
+    /// is assumed to only exist on node 1. Instead of splitting the file by
+    /// bytes like [`df_from_sor`], node 1 assigns consecutive row groups to
+    /// each node so that every node gets about the same number of rows, then
+    /// distributes them. The columns keep their names from the file. If node
+    /// 1 can't read the file, it returns the error and every other node
+    /// returns a `LiquidError::RemoteError`.
+    ///
+    /// `await`ing this function will block until the data is completely
+    /// distributed on all nodes. After the data is distributed, each node
+    /// of this distributed `liquid_ml` system will have their `LiquidML`
+    /// struct updated with the information of the new [`DistributedDataFrame`]
+    ///
+    /// **NOTE**: `df_name` must be unique.
+    ///
+    /// [`df_from_sor`]: struct.LiquidML.html#method.df_from_sor
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    #[cfg(feature = "parquet")]
+    pub async fn df_from_parquet(
+        &mut self,
+        df_name: &str,
+        file_name: &str,
+    ) -> Result<(), LiquidError> {
+        let ddf = DistributedDataFrame::from_parquet(
+            &self.server_addr,
+            &self.my_ip,
+            file_name,
+            self.kv.clone(),
+            df_name,
+            self.num_nodes,
+        )
+        .await?;
+        self.data_frames.insert(df_name.to_string(), ddf);
+        Ok(())
+    }
+
     /// Create a new data frame that consists of all the chunks in `iter` until
     /// `iter` is consumed. Node 1 will call `next` on the `iter` and
     /// distributes these chunks to all the other nodes, sending up to 2 chunks
@@ -204,8 +370,81 @@ impl LiquidML {
         Fut: Future<Output = ()>,
         F: FnOnce(Arc<KVStore<LocalDataFrame>>) -> Fut,
     {
-        f(self.kv.clone()).await;
-        self.kill_notifier.notified().await;
+        self.run_until_cancelled(f, future::pending()).await;
+    }
+
+    /// Given a function, run it on this application like [`run`], but also
+    /// stop as soon as the given `cancel` future resolves, even if `f` has
+    /// not finished yet. This allows embedding code to stop a node on its own
+    /// signal, e.g. a `SIGINT` handler (`tokio::signal::ctrl_c`), a oneshot
+    /// channel, or test teardown, without waiting for a kill signal from the
+    /// [`Server`].
+    ///
+    /// Returns `true` if this function returned because `cancel` resolved,
+    /// and `false` if it returned because of a kill signal from the
+    /// [`Server`].
+    ///
+    /// [`run`]: struct.LiquidML.html#method.run
+    /// [`Server`]: network/struct.Server.html
+    pub async fn run_until_cancelled<F, Fut, C>(self, f: F, cancel: C) -> bool
+    where
+        Fut: Future<Output = ()>,
+        F: FnOnce(Arc<KVStore<LocalDataFrame>>) -> Fut,
+        C: Future<Output = ()>,
+    {
+        let kv = self.kv.clone();
+        let kill_notifier = self.kill_notifier.clone();
+        let run = async move {
+            f(kv).await;
+            kill_notifier.notified().await;
+        };
+        tokio::select! {
+            _ = run => false,
+            _ = cancel => true,
+        }
+    }
+
+    /// Given a function, run it on this application and return whatever the
+    /// future it produces resolves to. Unlike [`run`], this function does not
+    /// wait for a kill signal from the [`Server`], which makes it useful for
+    /// batch jobs that should exit as soon as they are done.
+    ///
+    /// [`run`]: struct.LiquidML.html#method.run
+    /// [`Server`]: network/struct.Server.html
+    pub async fn run_until<T, F, Fut>(self, f: F) -> T
+    where
+        Fut: Future<Output = T>,
+        F: FnOnce(Arc<KVStore<LocalDataFrame>>) -> Fut,
+    {
+        f(self.kv.clone()).await
+    }
+
+    /// Returns a `LiquidError::NodeTaskFailed` if any of the tasks spawned by
+    /// this node to process messages from other nodes has panicked, instead
+    /// of leaving the node silently hanging on a message that will never be
+    /// processed. Returns `Ok(())` otherwise without waiting.
+    pub async fn poll_tasks(&self) -> Result<(), LiquidError> {
+        self.kv.check_tasks().await
+    }
+
+    /// Waits for the next blob sent to this node and deserializes it into a
+    /// `V`. This is the receiving half of [`KVStore::send_typed`], use the
+    /// `blob_receiver` directly to receive the raw bytes instead.
+    ///
+    /// # Errors
+    /// If the blob can not be deserialized into a `V`,
+    /// `LiquidError::SerdeError` is returned. If the channel of blobs has
+    /// been closed, `LiquidError::StreamClosed` is returned.
+    ///
+    /// [`KVStore::send_typed`]: kv/struct.KVStore.html#method.send_typed
+    pub async fn recv_typed<V: DeserializeOwned>(
+        &self,
+    ) -> Result<V, LiquidError> {
+        let blob = { self.blob_receiver.lock().await.recv().await };
+        match blob {
+            Some(blob) => Ok(deserialize(&blob[..])?),
+            None => Err(LiquidError::StreamClosed),
+        }
     }
 
     /// Perform a distributed map operation on the [`DistributedDataFrame`] with
@@ -244,6 +483,344 @@ impl LiquidML {
         df.map(rower).await
     }
 
+    /// Collects all the chunks of the [`DistributedDataFrame`] with the name
+    /// `df_name` onto node 1 and stacks them into a single `LocalDataFrame`
+    /// in the order of their rows. Returns `Some` of the gathered
+    /// `LocalDataFrame` on node 1, and `None` on every other node. Unlike
+    /// [`map`], this reconstitutes the data itself, so the whole data frame
+    /// must fit in memory on node 1.
+    ///
+    /// Each node sends its chunks to node 1 as a blob, so every node must
+    /// call `gather` for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`map`]: struct.LiquidML.html#method.map
+    pub async fn gather(
+        &self,
+        df_name: &str,
+    ) -> Result<Option<LocalDataFrame>, LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let mut chunks = Vec::new();
+        for (range, key) in df.df_chunk_map.iter() {
+            if key.home == self.node_id {
+                let chunk = self.kv.wait_and_get(key).await?;
+                chunks.push((range.start, (*chunk).clone()));
+            }
+        }
+        if self.node_id != 1 {
+            self.kv.send_typed(1, &chunks).await?;
+            return Ok(None);
+        }
+
+        for _ in 1..self.num_nodes {
+            let other: Vec<(usize, LocalDataFrame)> = self.recv_typed().await?;
+            chunks.extend(other);
+        }
+        chunks.sort_by_key(|(start, _)| *start);
+        let mut chunks = chunks.into_iter().map(|(_, chunk)| chunk);
+        match chunks.next() {
+            Some(first) => {
+                Ok(Some(chunks.try_fold(first, |acc, c| acc.combine(c))?))
+            }
+            None => Ok(Some(LocalDataFrame::new(&df.schema))),
+        }
+    }
+
+    /// Groups the rows of the [`DistributedDataFrame`] with the name `df_name`
+    /// by the values in the `keys` columns and computes the given `aggs` over
+    /// every group, as in [`LocalDataFrame::group_by`]. Returns `Some` of the
+    /// grouped `LocalDataFrame` on node 1, and `None` on every other node.
+    ///
+    /// Each node aggregates the groups of its own chunks, and the partial
+    /// aggregates are merged with a distributed [`map`], so only one row per
+    /// group is sent between nodes. The groups are in no particular order.
+    /// Every node must call `group_by` for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`LocalDataFrame::group_by`]: dataframe/struct.LocalDataFrame.html#method.group_by
+    /// [`map`]: struct.LiquidML.html#method.map
+    pub async fn group_by(
+        &self,
+        df_name: &str,
+        keys: &[usize],
+        aggs: &[(usize, Agg)],
+    ) -> Result<Option<LocalDataFrame>, LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let rower = GroupByRower::new(df.get_schema(), keys, aggs)?;
+        match df.map(rower).await? {
+            Some(rower) => Ok(Some(rower.into_frame(df.get_schema())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Computes summary statistics of every column of the
+    /// [`DistributedDataFrame`] with the name `df_name`, as in
+    /// [`LocalDataFrame::describe`]. Returns `Some` of the statistics on node
+    /// 1, and `None` on every other node.
+    ///
+    /// Each node builds a sketch of every column of its own chunks, and the
+    /// sketches are merged with a distributed [`map`], so the chunks are never
+    /// sent between nodes. Quartiles are approximate for columns with many
+    /// values, and distinct counts of `Bool` and `String` columns are exact.
+    /// Every node must call `describe` for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`LocalDataFrame::describe`]: dataframe/struct.LocalDataFrame.html#method.describe
+    /// [`map`]: struct.LiquidML.html#method.map
+    pub async fn describe(
+        &self,
+        df_name: &str,
+    ) -> Result<Option<LocalDataFrame>, LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let rower = DescribeRower::new(df.get_schema());
+        Ok(df.map(rower).await?.map(|r| r.into_frame(df.get_schema())))
+    }
+
+    /// Splits the rows of the [`DistributedDataFrame`] with the name
+    /// `df_name` evenly between the nodes, keeping their order, so that the
+    /// number of rows on any two nodes differs by at most one. The
+    /// rebalanced [`DistributedDataFrame`] replaces the old one under the same
+    /// `df_name`, and the chunks of the old one are removed from the
+    /// [`KVStore`]. Useful for balancing the load of `map`s after operations
+    /// such as `filter` leave some nodes with many more rows than others.
+    ///
+    /// Every node must call `rebalance` for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`KVStore`]: kv/struct.KVStore.html
+    pub async fn rebalance(
+        &mut self,
+        df_name: &str,
+    ) -> Result<(), LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let rebalanced = df.rebalance().await?;
+        self.data_frames.insert(df_name.to_string(), rebalanced);
+
+        Ok(())
+    }
+
+    /// Replaces the column at `col_idx` of the [`DistributedDataFrame`] with
+    /// the name `df_name` by the result of applying `f` to each of its
+    /// values, as in [`LocalDataFrame::map_column`]. The new
+    /// [`DistributedDataFrame`] replaces the old one under the same
+    /// `df_name`.
+    ///
+    /// Each node maps its own chunks, so no rows are sent between nodes, but
+    /// every node must call `map_column` with the same arguments for it to
+    /// complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`LocalDataFrame::map_column`]: dataframe/struct.LocalDataFrame.html#method.map_column
+    pub async fn map_column<F>(
+        &mut self,
+        df_name: &str,
+        col_idx: usize,
+        data_type: &DataType,
+        f: F,
+    ) -> Result<(), LiquidError>
+    where
+        F: Fn(&Data) -> Data,
+    {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let mapped = df.map_column(col_idx, data_type, f).await?;
+        self.data_frames.insert(df_name.to_string(), mapped);
+
+        Ok(())
+    }
+
+    /// Creates or replaces the column named `name` of the
+    /// [`DistributedDataFrame`] with the name `df_name`, whose value in each
+    /// row is the result of applying `f` to that row, as in
+    /// [`LocalDataFrame::with_column`]. The new [`DistributedDataFrame`]
+    /// replaces the old one under the same `df_name`.
+    ///
+    /// Each node computes the column for its own chunks, so no rows are sent
+    /// between nodes, but every node must call `with_column` with the same
+    /// arguments for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`LocalDataFrame::with_column`]: dataframe/struct.LocalDataFrame.html#method.with_column
+    pub async fn with_column<F>(
+        &mut self,
+        df_name: &str,
+        name: &str,
+        data_type: &DataType,
+        f: F,
+    ) -> Result<(), LiquidError>
+    where
+        F: Fn(&Row) -> Data,
+    {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let mapped = df.with_column(name, data_type, f).await?;
+        self.data_frames.insert(df_name.to_string(), mapped);
+
+        Ok(())
+    }
+
+    /// Adds a column named `name` to the [`DistributedDataFrame`] with the
+    /// name `df_name`, with the value of the given `window` function over
+    /// the column at `col_idx` for every row, as in
+    /// [`DistributedDataFrame::with_window`]. The rows are assumed to already
+    /// be in the order the window should be computed in, e.g. after a sort.
+    /// The new [`DistributedDataFrame`] replaces the old one under the same
+    /// `df_name`.
+    ///
+    /// Only the rows at the edges of each chunk that the windows of other
+    /// chunks reach, or the total of each chunk for `Window::CumSum`, are
+    /// sent between nodes, but every node must call `with_window` with the
+    /// same arguments for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`DistributedDataFrame::with_window`]: dataframe/struct.DistributedDataFrame.html#method.with_window
+    pub async fn with_window(
+        &mut self,
+        df_name: &str,
+        col_idx: usize,
+        window: Window,
+        name: &str,
+    ) -> Result<(), LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let windowed = df.with_window(col_idx, window, name).await?;
+        self.data_frames.insert(df_name.to_string(), windowed);
+
+        Ok(())
+    }
+
+    /// Performs a distributed join of the [`DistributedDataFrame`]s with the
+    /// names `left_df` and `right_df`, matching rows where the value in the
+    /// `left_key` column of `left_df` equals the value in the `right_key`
+    /// column of `right_df`. The given `kind` determines whether rows
+    /// without a match are kept. The joined [`DistributedDataFrame`] is
+    /// stored under the name `"<left_df>-<right_df>-joined"`, followed by
+    /// `-<n>` if `left_df` has already been joined `n` times.
+    ///
+    /// Rows are shuffled between nodes so that rows with equal keys are
+    /// joined on the same node, so every node must call `join` for it to
+    /// complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    pub async fn join(
+        &mut self,
+        left_df: &str,
+        right_df: &str,
+        left_key: usize,
+        right_key: usize,
+        kind: JoinKind,
+    ) -> Result<(), LiquidError> {
+        let (left, right) = match (
+            self.data_frames.get(left_df),
+            self.data_frames.get(right_df),
+        ) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Err(LiquidError::NotPresent),
+        };
+        let joined = left.join(right, left_key, right_key, kind).await?;
+        self.data_frames.insert(joined.df_name.clone(), joined);
+
+        Ok(())
+    }
+
+    /// Removes duplicate rows from the [`DistributedDataFrame`] with the name
+    /// `df_name`, keeping one row for each distinct combination of values in
+    /// the `subset` columns, or in all columns if `subset` is `None`. The
+    /// deduplicated [`DistributedDataFrame`] is stored under the name
+    /// `"<df_name>-unique"`.
+    ///
+    /// Rows are shuffled between nodes so that duplicates are removed on the
+    /// same node, so every node must call `unique` for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    pub async fn unique(
+        &mut self,
+        df_name: &str,
+        subset: Option<&[usize]>,
+    ) -> Result<(), LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let unique = df.unique(subset).await?;
+        self.data_frames.insert(unique.df_name.clone(), unique);
+
+        Ok(())
+    }
+
+    /// Samples a `fraction` of the rows of the [`DistributedDataFrame`] with
+    /// the name `df_name` uniformly without replacement, as in
+    /// [`DistributedDataFrame::sample`]. The sample is stored under the name
+    /// `"<df_name>-sample-<seed>"`.
+    ///
+    /// No rows are sent between nodes, but every node must call `sample`
+    /// with the same arguments for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`DistributedDataFrame::sample`]: dataframe/struct.DistributedDataFrame.html#method.sample
+    pub async fn sample(
+        &mut self,
+        df_name: &str,
+        fraction: f64,
+        seed: u64,
+    ) -> Result<(), LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let sample = df.sample(fraction, seed).await?;
+        self.data_frames.insert(sample.df_name.clone(), sample);
+
+        Ok(())
+    }
+
+    /// Randomly splits the rows of the [`DistributedDataFrame`] with the name
+    /// `df_name` into a training set stored under the name
+    /// `"<df_name>-train"` and a test set with `test_fraction` of the rows
+    /// stored under the name `"<df_name>-test"`, as in
+    /// [`DistributedDataFrame::train_test_split`]. The same `seed` always
+    /// produces the same split.
+    ///
+    /// No rows are sent between nodes, but every node must call
+    /// `train_test_split` with the same arguments for it to complete.
+    ///
+    /// [`DistributedDataFrame`]: dataframe/struct.DistributedDataFrame.html
+    /// [`DistributedDataFrame::train_test_split`]: dataframe/struct.DistributedDataFrame.html#method.train_test_split
+    pub async fn train_test_split(
+        &mut self,
+        df_name: &str,
+        test_fraction: f64,
+        seed: u64,
+    ) -> Result<(), LiquidError> {
+        let df = match self.data_frames.get(df_name) {
+            Some(x) => x,
+            None => return Err(LiquidError::NotPresent),
+        };
+        let (train, test) = df.train_test_split(test_fraction, seed).await?;
+        self.data_frames.insert(train.df_name.clone(), train);
+        self.data_frames.insert(test.df_name.clone(), test);
+
+        Ok(())
+    }
+
     /// Perform a distributed filter operation on the [`DistributedDataFrame`]
     /// with the name `df_name` and uses the given `rower`.  This function
     /// does not mutate the [`DistributedDataFrame`] in anyway, instead, it
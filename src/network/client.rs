@@ -2,22 +2,28 @@
 //! provided for `LiquidML` use cases.
 use crate::error::LiquidError;
 use crate::network::{
-    existing_conn_err, increment_msg_id, message, Connection, ControlMsg,
-    FramedSink, FramedStream, Message, MessageCodec,
+    bind_reusable, existing_conn_err, increment_msg_id, message, Connection,
+    ControlMsg, FramedSink, FramedStream, Message, MessageCodec, Transport,
+};
+use crate::{
+    PEER_CONNECT_ATTEMPTS, PEER_CONNECT_BACKOFF_MS, SERVER_RECONNECT_ATTEMPTS,
+    SERVER_RECONNECT_BACKOFF_MS,
 };
 use futures::{
     stream::{self, SelectAll},
     SinkExt,
 };
-use log::{debug, info};
+use log::{debug, error, info, warn};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::io;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{Mutex, Notify};
+use tokio::time::delay_for;
 use tokio_util::codec::{FramedRead, FramedWrite};
 
 /// Represents a `Client` node in a distributed system that is generic for
@@ -25,8 +31,15 @@ use tokio_util::codec::{FramedRead, FramedWrite};
 /// `Client`s. Allows directed communication to any other node that shares the
 /// `Client`'s `client_type`, which enables increased concurrency due to
 /// decreased lock contention.
+///
+/// The `Client` communicates over a [`Transport`] of type `S`, which is a
+/// `TcpStream` unless otherwise specified. Only a `Client` using `TCP` can
+/// listen for and open its own connections with [`new`].
+///
+/// [`Transport`]: trait.Transport.html
+/// [`new`]: struct.Client.html#method.new
 #[derive(Debug)]
-pub struct Client<T> {
+pub struct Client<T, S = TcpStream> {
     /// The `id` of this `Client`, assigned by the [`Server`] on startup
     /// and is monotonically increasing based on the order of connections
     ///
@@ -36,15 +49,15 @@ pub struct Client<T> {
     pub num_nodes: usize,
     /// The `address` of this `Client`
     pub address: SocketAddr,
-    /// The id of the current message
-    pub(crate) msg_id: usize,
+    /// The id of the current message, wraps around to `0` on overflow
+    pub(crate) msg_id: u64,
     /// A directory which is a map of client id to the [`Connection`] with that
     /// `Client`
     ///
     /// [`Connection`]: struct.Connection.html
-    pub(crate) directory: HashMap<usize, Connection<T>>,
+    pub(crate) directory: HashMap<usize, Connection<T, S>>,
     /// The connection to the [`Server`](struct.Server.html)
-    server: Connection<ControlMsg>,
+    server: Connection<ControlMsg, S>,
     /// The name of the network this `Client` will connect to. This is so that,
     /// for example, two different communication networks of
     /// `Client<DistributedDFMsg>` can be created so that separate
@@ -108,68 +121,88 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
     ) -> Result<
         (Arc<Mutex<Self>>, SelectAll<FramedStream<RT>>, Arc<Notify>),
         LiquidError,
+    > {
+        let (c, dir, stream, listener) = Client::register(
+            server_addr,
+            my_ip,
+            my_port,
+            num_nodes,
+            network_name,
+        )
+        .await?;
+        Client::join_network(c, dir, stream, listener).await
+    }
+
+    /// Binds a listener at `my_ip` and `my_port` and registers a new
+    /// `Client` with the [`Server`] at `server_addr`, see [`new`] for the
+    /// parameters. Returns the `Client`, the ids and addresses of the
+    /// `Client`s that were already in the network, the stream for reading
+    /// further messages from the [`Server`] and the listener for the
+    /// `Client`s that join after this one.
+    ///
+    /// [`Server`]: struct.Server.html
+    /// [`new`]: struct.Client.html#method.new
+    async fn register(
+        server_addr: String,
+        my_ip: String,
+        my_port: Option<String>,
+        num_nodes: usize,
+        network_name: String,
+    ) -> Result<
+        (
+            Self,
+            Vec<(usize, SocketAddr)>,
+            FramedStream<ControlMsg>,
+            TcpListener,
+        ),
+        LiquidError,
     > {
         // Setup a TCPListener
-        let listener;
-        let my_address: SocketAddr = match my_port {
-            Some(port) => {
-                let addr = format!("{}:{}", my_ip, port);
-                listener = TcpListener::bind(&addr).await?;
-                addr.parse().unwrap()
-            }
-            None => {
-                let addr = format!("{}:0", my_ip);
-                listener = TcpListener::bind(&addr).await?;
-                listener.local_addr()?.to_string().parse().unwrap()
-            }
-        };
+        let addr: SocketAddr = match my_port {
+            Some(port) => format!("{}:{}", my_ip, port),
+            None => format!("{}:0", my_ip),
+        }
+        .parse()?;
+        let listener = bind_reusable(&addr)?;
+        let my_address = listener.local_addr()?;
         // Connect to the server
         let server_stream = TcpStream::connect(server_addr).await?;
         let server_address = server_stream.peer_addr().unwrap();
-        let (reader, writer) = io::split(server_stream);
-        let mut stream = FramedRead::new(reader, MessageCodec::new());
-        let sink = FramedWrite::new(writer, MessageCodec::new());
-        let mut server = Connection {
-            address: server_address,
-            sink,
-        };
-        // Tell the server our address and type
-        server
-            .sink
-            .send(Message::new(
-                0,
-                0,
-                0,
-                ControlMsg::Introduction {
-                    address: my_address,
-                    network_name: network_name.to_string(),
-                },
-            ))
-            .await?;
-        // Server responds with the addresses of all currently connected clients
-        let dir_msg = message::read_msg(&mut stream).await?;
-        let dir = if let ControlMsg::Directory { dir } = dir_msg.msg {
-            dir
-        } else {
-            return Err(LiquidError::UnexpectedMessage);
-        };
-
-        info!(
-            "Client in network {} got id {} running at address {}",
-            network_name, dir_msg.target_id, &my_address
-        );
-
-        // initialize `self`
-        let mut c = Client {
-            id: dir_msg.target_id,
-            address: my_address,
-            msg_id: dir_msg.msg_id + 1,
-            directory: HashMap::new(),
+        let registration = register_with_server(
+            server_stream,
+            0,
+            my_address,
+            &network_name,
             num_nodes,
-            server,
-            network_name: network_name.to_string(),
-        };
+        )
+        .await?;
+        let (c, dir, stream) = Client::from_registration(
+            registration,
+            my_address,
+            server_address,
+            num_nodes,
+            &network_name,
+        );
+        Ok((c, dir, stream, listener))
+    }
 
+    /// Connects the newly registered `Client` `c` to all the `Client`s in
+    /// `dir`, accepts connections from the `Client`s that join after it on
+    /// `listener`, then waits until the [`Server`] has seen every `Client`
+    /// register. Returns the same tuple as [`new`].
+    ///
+    /// [`Server`]: struct.Server.html
+    /// [`new`]: struct.Client.html#method.new
+    async fn join_network(
+        mut c: Self,
+        dir: Vec<(usize, SocketAddr)>,
+        mut stream: FramedStream<ControlMsg>,
+        listener: TcpListener,
+    ) -> Result<
+        (Arc<Mutex<Self>>, SelectAll<FramedStream<RT>>, Arc<Notify>),
+        LiquidError,
+    > {
+        let num_nodes = c.num_nodes;
         // Connect to all the currently existing clients
         let mut existing_conns = vec![];
         // note this is done serially and could be done concurrently, but
@@ -179,17 +212,23 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
             existing_conns.push(c.connect(id, addr).await?);
         }
 
-        // Listen for further messages from the Server, e.g. `Kill` messages
-        let kill_notifier = Arc::new(Notify::new());
-        Client::<ControlMsg>::recv_server_msg(stream, kill_notifier.clone());
         // block until all the other clients start up and connect to us
         let new_conns =
             Client::accept_new_connections(&mut c, listener, num_nodes).await?;
+        // block until the `Server` has seen all the other clients register
+        wait_for_network_ready(&mut stream).await?;
         let read_streams = stream::select_all(
             existing_conns.into_iter().chain(new_conns.into_iter()),
         );
 
         let concurrent_client = Arc::new(Mutex::new(c));
+        // Listen for further messages from the Server, e.g. `Kill` messages
+        let kill_notifier = Arc::new(Notify::new());
+        Client::recv_server_msg(
+            concurrent_client.clone(),
+            stream,
+            kill_notifier.clone(),
+        );
         Ok((concurrent_client, read_streams, kill_notifier))
     }
 
@@ -200,7 +239,16 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
     /// `Client`s with the same `network_name` as the new network is
     /// independent of the `parent`.
     ///
+    /// Each node registers with the [`Server`] once the node before it has
+    /// registered and sent it a `ControlMsg::Ready`, then sends one to the
+    /// node after it, retrying until that node is listening for it. So the
+    /// nodes can call `register_network` in any order. Returns a
+    /// `LiquidError::UnknownId` if the `parent` is not connected to the node
+    /// after it.
+    ///
     /// The tuple returned is the same as in the `Client::new` function.
+    ///
+    /// [`Server`]: struct.Server.html
     pub async fn register_network<
         T: Send + Sync + DeserializeOwned + Serialize + Clone + 'static,
     >(
@@ -222,93 +270,59 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
             let num_nodes = unlocked.num_nodes;
             (server_addr, my_ip, node_id, unlocked.address, num_nodes)
         };
-        if node_id == 1 {
-            // connect our client right away since we want to be node 1
-            let jh = tokio::spawn(async move {
-                Client::<T>::new(
-                    server_addr,
-                    my_ip,
-                    None,
-                    num_nodes,
-                    network_name,
-                )
-                .await
-            });
-            // Send a ready message to node 2 so that all the other nodes
-            // start connecting to the Server in the correct order
-            let node_2_addr = {
-                let unlocked = parent.lock().await;
-                unlocked.directory.get(&2).unwrap().address
-            };
-            let socket = TcpStream::connect(node_2_addr).await?;
-            let (_, writer) = io::split(socket);
-            let mut sink =
-                FramedWrite::new(writer, MessageCodec::<ControlMsg>::new());
-            let msg = Message::new(0, node_id, 2, ControlMsg::Ready);
-            sink.send(msg).await?;
-            let (network, read_streams, kill_notifier) = jh.await.unwrap()?;
-            assert_eq!(1, { network.lock().await.id });
-            // return the newly registered network
-            Ok((network, read_streams, kill_notifier))
-        } else {
-            // wait to receive a `Ready` message from the node before us
-            // the `parent` passed in
-            let mut listener = TcpListener::bind(listen_addr).await?;
+        if node_id > 1 {
+            // wait to receive a `Ready` message from the node before us in
+            // the `parent`, which has registered in the new network
+            let mut listener = bind_reusable(&listen_addr)?;
             let (socket, _) = listener.accept().await?;
-            let (reader, writer) = io::split(socket);
+            let (reader, _) = io::split(socket);
             let mut stream =
                 FramedRead::new(reader, MessageCodec::<ControlMsg>::new());
-            let mut sink =
-                FramedWrite::new(writer, MessageCodec::<ControlMsg>::new());
-            // wait for the ready message
             let msg = message::read_msg(&mut stream).await?;
-            //assert_eq!(msg.sender_id, node_id);
             match msg.msg {
                 ControlMsg::Ready => (),
                 _ => return Err(LiquidError::UnexpectedMessage),
             };
-            // The node before us has joined the network, it is now time
-            // to connect
-            let client_join_handle = tokio::spawn(async move {
-                Client::<T>::new(
-                    server_addr,
-                    my_ip,
-                    None,
-                    num_nodes,
-                    network_name,
-                )
-                .await
-            });
-
-            // tell the next node we are ready
-            if node_id < num_nodes {
-                // There is another node after us
-                let msg = Message::new(0, node_id, node_id, ControlMsg::Ready);
-                sink.send(msg).await?;
-                let next_node_addr = {
-                    let unlocked = parent.lock().await;
-                    unlocked.directory.get(&(node_id + 1)).unwrap().address
-                };
-                let next_node_socket =
-                    TcpStream::connect(next_node_addr).await?;
-                let (_, next_node_writer) = io::split(next_node_socket);
-                let mut next_node_sink = FramedWrite::new(
-                    next_node_writer,
-                    MessageCodec::<ControlMsg>::new(),
-                );
-                let ready_msg =
-                    Message::new(0, node_id, node_id + 1, ControlMsg::Ready);
-                next_node_sink.send(ready_msg).await?;
-            }
-            let (network, read_streams, kill_notifier) =
-                client_join_handle.await.unwrap()?;
-            // assert that we joined in the right order (kv node id must
-            // match client node id)
-            assert_eq!(node_id, { network.lock().await.id });
-
-            // return the newly registered network
-            Ok((network, read_streams, kill_notifier))
         }
+        // The nodes before us have joined the network, so register now to
+        // get the same id as in the `parent`
+        let (c, dir, stream, listener) = Client::<T>::register(
+            server_addr,
+            my_ip,
+            None,
+            num_nodes,
+            network_name,
+        )
+        .await?;
+        // assert that we joined in the right order (kv node id must match
+        // client node id)
+        assert_eq!(node_id, c.id);
+
+        // tell the next node we are ready, only once we have registered so
+        // that it can't register before us. The last node, e.g. the only node
+        // of a network with one node, has no one to tell
+        if node_id < num_nodes {
+            // There is another node after us
+            let next_node_addr = {
+                let unlocked = parent.lock().await;
+                match unlocked.directory.get(&(node_id + 1)) {
+                    Some(conn) => conn.address,
+                    None => return Err(LiquidError::UnknownId),
+                }
+            };
+            let next_node_socket = connect_to_peer(next_node_addr).await?;
+            let (_, next_node_writer) = io::split(next_node_socket);
+            let mut next_node_sink = FramedWrite::new(
+                next_node_writer,
+                MessageCodec::<ControlMsg>::new(),
+            );
+            let ready_msg =
+                Message::new(0, node_id, node_id + 1, ControlMsg::Ready);
+            next_node_sink.send(ready_msg).await?;
+        }
+
+        // return the newly registered network
+        Client::join_network(c, dir, stream, listener).await
     }
 
     /// Waits and accepts any connection from newly started `Client`s until
@@ -322,7 +336,6 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
         mut listener: TcpListener,
         num_clients: usize,
     ) -> Result<Vec<FramedStream<RT>>, LiquidError> {
-        let accepted_type = self.network_name.clone();
         let mut curr_clients = self.directory.len() + 1;
         let mut streams = vec![];
         loop {
@@ -331,73 +344,271 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
             }
             // wait on connections from new clients
             let (socket, _) = listener.accept().await?;
-            let (reader, writer) = io::split(socket);
-            let mut stream =
-                FramedRead::new(reader, MessageCodec::<ControlMsg>::new());
-            let sink = FramedWrite::new(writer, MessageCodec::<RT>::new());
-            // read the introduction message from the new client
-            let intro = message::read_msg(&mut stream).await?;
-            let (address, network_name) = if let ControlMsg::Introduction {
-                address,
-                network_name,
-            } = intro.msg
-            {
-                (address, network_name)
-            } else {
-                // we should only receive `ControlMsg::Introduction` msgs here
-                return Err(LiquidError::UnexpectedMessage);
-            };
+            streams.push(self.accept(socket).await?);
+            curr_clients += 1;
+        }
+    }
 
-            if accepted_type != network_name {
-                // we only want to connect with other clients that are the same
-                // type as us
-                return Err(LiquidError::UnexpectedMessage);
-            }
+    /// Connects this `Client` with the `Client` running at the given
+    /// `(id, IP:Port)`, see [`connect_over`].
+    ///
+    /// [`connect_over`]: struct.Client.html#method.connect_over
+    async fn connect(
+        &mut self,
+        client_id: usize,
+        client_addr: SocketAddr,
+    ) -> Result<FramedStream<RT>, LiquidError> {
+        // Connect to the given client
+        let stream = TcpStream::connect(&client_addr).await?;
+        self.connect_over(client_id, client_addr, stream).await
+    }
 
-            // increment the message id and check if there was an existing
-            // connection
-            self.msg_id = increment_msg_id(self.msg_id, intro.msg_id);
-            let is_existing_conn =
-                self.directory.contains_key(&intro.sender_id);
+    /// Send the given `message` to the `Client` with the given `target_id`
+    /// running at `addr`, connecting to it first if there is no connection to
+    /// that `Client` in our directory yet. This allows messaging a node that
+    /// is known by its address, e.g. from a [`Server`]'s directory, without
+    /// it having been connected to when this `Client` started.
+    ///
+    /// If a new connection was made, the `FramedStream<RT>` for reading
+    /// messages from the other `Client` is returned so that it can be added
+    /// to the caller's streams. Note that the other `Client` must still be
+    /// accepting connections for the new connection to succeed.
+    ///
+    /// Returns a `LiquidError::ReconnectionError` if `target_id` is already
+    /// connected at a different address.
+    ///
+    /// [`Server`]: struct.Server.html
+    pub async fn send_msg_to_addr(
+        &mut self,
+        target_id: usize,
+        addr: SocketAddr,
+        message: RT,
+    ) -> Result<Option<FramedStream<RT>>, LiquidError> {
+        let new_stream = match self.directory.get(&target_id) {
+            Some(conn) if conn.address == addr => None,
+            Some(_) => return Err(LiquidError::ReconnectionError),
+            None => Some(self.connect(target_id, addr).await?),
+        };
+        self.send_msg(target_id, message).await?;
+        Ok(new_stream)
+    }
 
-            if is_existing_conn {
-                return Err(existing_conn_err(stream, sink));
+    /// Spawns a `tokio` task that will handle receiving [`ControlMsg::Kill`]
+    /// messages from the [`Server`].
+    ///
+    /// If the connection to the [`Server`] is dropped, e.g. because the
+    /// [`Server`] restarted, the task tries to re-register the `client` with
+    /// the [`Server`] at the same address, keeping its `id`. Attempts are
+    /// retried with an exponential backoff, starting at
+    /// `SERVER_RECONNECT_BACKOFF_MS` milliseconds, and the task gives up after
+    /// `SERVER_RECONNECT_ATTEMPTS` failed attempts.
+    ///
+    /// [`Server`]: struct.Server.html
+    /// [`ControlMsg::Kill`]: enum.ControlMsg.html#variant.Kill
+    fn recv_server_msg(
+        client: Arc<Mutex<Self>>,
+        mut reader: FramedStream<ControlMsg>,
+        notifier: Arc<Notify>,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                match message::read_msg(&mut reader).await {
+                    Ok(Message {
+                        msg: ControlMsg::Kill,
+                        ..
+                    }) => {
+                        notifier.notify();
+                        return Ok(());
+                    }
+                    // re-registering may complete the network again
+                    Ok(Message {
+                        msg: ControlMsg::NetworkReady,
+                        ..
+                    }) => (),
+                    Ok(_) => return Err(LiquidError::UnexpectedMessage),
+                    Err(e) => {
+                        warn!("Lost connection to the Server: {}", e);
+                        reader = Client::reconnect_to_server(&client).await?;
+                    }
+                }
             }
+        });
+    }
 
-            // Add the connection with the new client to this directory
-            let conn = Connection { address, sink };
-            self.directory.insert(intro.sender_id, conn);
-            // NOTE: Not unsafe because message codec has no fields and
-            // can be converted to a different type without losing meaning
-            let new_stream = unsafe {
-                std::mem::transmute::<FramedStream<ControlMsg>, FramedStream<RT>>(
-                    stream,
-                )
+    /// Re-registers the given `client` with the [`Server`] it originally
+    /// registered with, retrying with an exponential backoff. On success,
+    /// the `client`'s connection to the [`Server`] is replaced and the stream
+    /// for reading further messages from the [`Server`] is returned.
+    ///
+    /// [`Server`]: struct.Server.html
+    async fn reconnect_to_server(
+        client: &Arc<Mutex<Self>>,
+    ) -> Result<FramedStream<ControlMsg>, LiquidError> {
+        let (server_address, id, my_address, network_name, num_nodes) = {
+            let unlocked = client.lock().await;
+            (
+                unlocked.server.address,
+                unlocked.id,
+                unlocked.address,
+                unlocked.network_name.clone(),
+                unlocked.num_nodes,
+            )
+        };
+        let mut backoff = SERVER_RECONNECT_BACKOFF_MS;
+        let mut attempt = 1;
+        loop {
+            delay_for(Duration::from_millis(backoff)).await;
+            let registration = match TcpStream::connect(server_address).await {
+                Ok(stream) => {
+                    register_with_server(
+                        stream,
+                        id,
+                        my_address,
+                        &network_name,
+                        num_nodes,
+                    )
+                    .await
+                }
+                Err(e) => Err(e.into()),
             };
-            streams.push(new_stream);
-            info!(
-                "Connected to id: {:#?} at address: {:#?}",
-                intro.sender_id, address
-            );
-            curr_clients += 1;
+            match registration {
+                Ok(Registration { stream, sink, .. }) => {
+                    info!(
+                        "Client in network {} re-registered with id {}",
+                        network_name, id
+                    );
+                    client.lock().await.server = Connection {
+                        address: server_address,
+                        sink,
+                    };
+                    return Ok(stream);
+                }
+                Err(e) if attempt == SERVER_RECONNECT_ATTEMPTS => {
+                    error!("Could not reconnect to the Server: {}", e);
+                    return Err(e);
+                }
+                Err(e) => {
+                    debug!("Reconnection attempt {} failed: {}", attempt, e);
+                    attempt += 1;
+                    backoff *= 2;
+                }
+            }
         }
     }
+}
 
-    /// Connects this `Client` with the `Client` running at the given
-    /// `(id, IP:Port)`. After connecting, adds the [`Connection`] to the other
-    /// `Client` to our directory for sending messages. The returned
-    /// `FramedStream<RT>` is used for reading messages via the `Stream` trait.
+impl<RT, S> Client<RT, S>
+where
+    RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static,
+    S: Transport,
+{
+    /// Creates a new `Client` listening at `address` from its `registration`
+    /// with the [`Server`] at `server_address`, in the network with the given
+    /// `network_name` and `num_nodes`. The new `Client` is not connected to
+    /// any other `Client` yet.
+    ///
+    /// Returns the `Client`, the ids and addresses of the `Client`s that were
+    /// already in the network, and the stream for reading further messages
+    /// from the [`Server`], e.g. the `ControlMsg::NetworkReady` message.
+    ///
+    /// [`Server`]: struct.Server.html
+    pub(crate) fn from_registration(
+        registration: Registration<S>,
+        address: SocketAddr,
+        server_address: SocketAddr,
+        num_nodes: usize,
+        network_name: &str,
+    ) -> (Self, Vec<(usize, SocketAddr)>, FramedStream<ControlMsg, S>) {
+        let Registration {
+            id,
+            msg_id,
+            dir,
+            stream,
+            sink,
+        } = registration;
+        info!(
+            "Client in network {} got id {} running at address {}",
+            network_name, id, &address
+        );
+        let client = Client {
+            id,
+            address,
+            msg_id: msg_id.wrapping_add(1),
+            directory: HashMap::new(),
+            num_nodes,
+            server: Connection {
+                address: server_address,
+                sink,
+            },
+            network_name: network_name.to_string(),
+        };
+
+        (client, dir, stream)
+    }
+
+    /// Accepts a connection from a newly started `Client` over the given
+    /// `transport`. Once the new `Client` has introduced itself, the
+    /// [`Connection`] to it is added to our directory so we can later send
+    /// messages to it, and the `FramedStream<RT, S>` for reading its
+    /// messages is returned.
+    ///
+    /// [`Connection`]: struct.Connection.html
+    pub(crate) async fn accept(
+        &mut self,
+        transport: S,
+    ) -> Result<FramedStream<RT, S>, LiquidError> {
+        let (reader, writer) = io::split(transport);
+        let mut stream =
+            FramedRead::new(reader, MessageCodec::<ControlMsg>::new());
+        let sink = FramedWrite::new(writer, MessageCodec::<RT>::new());
+        // read the introduction message from the new client
+        let (sender_id, intro_msg_id, address) =
+            read_introduction(&mut stream, &self.network_name).await?;
+
+        // increment the message id and check if there was an existing
+        // connection
+        self.msg_id = increment_msg_id(self.msg_id, intro_msg_id);
+        let is_existing_conn = self.directory.contains_key(&sender_id);
+
+        if is_existing_conn {
+            return Err(existing_conn_err(stream, sink));
+        }
+
+        // Add the connection with the new client to this directory
+        let conn = Connection { address, sink };
+        self.directory.insert(sender_id, conn);
+        info!(
+            "Connected to id: {:#?} at address: {:#?}",
+            sender_id, address
+        );
+        // NOTE: Not unsafe because message codec has no fields and
+        // can be converted to a different type without losing meaning
+        let stream = unsafe {
+            std::mem::transmute::<
+                FramedStream<ControlMsg, S>,
+                FramedStream<RT, S>,
+            >(stream)
+        };
+
+        Ok(stream)
+    }
+
+    /// Connects this `Client` with the `Client` with the given `client_id`
+    /// running at `client_addr` over the given `transport`, which must already
+    /// be connected to that `Client`. After introducing itself, adds the
+    /// [`Connection`] to the other `Client` to our directory for sending
+    /// messages. The returned `FramedStream<RT, S>` is used for reading
+    /// messages via the `Stream` trait.
     ///
     /// [`Connection`]: struct.Connection.html
     #[allow(clippy::map_entry)] // clippy is being dumb
-    async fn connect(
+    pub(crate) async fn connect_over(
         &mut self,
         client_id: usize,
         client_addr: SocketAddr,
-    ) -> Result<FramedStream<RT>, LiquidError> {
-        // Connect to the given client
-        let stream = TcpStream::connect(&client_addr).await?;
-        let (reader, writer) = io::split(stream);
+        transport: S,
+    ) -> Result<FramedStream<RT, S>, LiquidError> {
+        let (reader, writer) = io::split(transport);
         let stream = FramedRead::new(reader, MessageCodec::<RT>::new());
         let mut sink =
             FramedWrite::new(writer, MessageCodec::<ControlMsg>::new());
@@ -406,22 +617,22 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
         if self.directory.contains_key(&client_id) {
             Err(existing_conn_err(stream, sink))
         } else {
-            sink.send(Message::new(
+            send_introduction(
+                &mut sink,
                 self.msg_id,
                 self.id,
-                0,
-                ControlMsg::Introduction {
-                    address: self.address,
-                    network_name: self.network_name.clone(),
-                },
-            ))
+                self.address,
+                self.network_name.clone(),
+                self.num_nodes,
+            )
             .await?;
             // NOTE: Not unsafe because message codec has no fields and
             // can be converted to a different type without losing meaning
             let sink = unsafe {
-                std::mem::transmute::<FramedSink<ControlMsg>, FramedSink<RT>>(
-                    sink,
-                )
+                std::mem::transmute::<
+                    FramedSink<ControlMsg, S>,
+                    FramedSink<RT, S>,
+                >(sink)
             };
             let conn = Connection {
                 address: client_addr,
@@ -435,7 +646,7 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
             self.directory.insert(client_id, conn);
             // send the client our id and address so they can add us to
             // their directory
-            self.msg_id += 1;
+            self.msg_id = self.msg_id.wrapping_add(1);
 
             Ok(stream)
         }
@@ -445,6 +656,10 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
     /// Id's are automatically assigned by a [`Server`] during the registration
     /// period based on the order of connections.
     ///
+    /// The `message` is flushed before this function returns, so it is never
+    /// lost in a buffer if this `Client` is dropped or [`close`]d right after.
+    ///
+    /// [`close`]: struct.Client.html#method.close
     /// [`Server`]: struct.Server.html
     pub async fn send_msg(
         &mut self,
@@ -454,10 +669,40 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
         let m = Message::new(self.msg_id, self.id, target_id, message);
         message::send_msg(target_id, m, &mut self.directory).await?;
         debug!("sent a message with id, {}", self.msg_id);
-        self.msg_id += 1;
+        self.msg_id = self.msg_id.wrapping_add(1);
         Ok(())
     }
 
+    /// Flushes and closes the connections to all other `Client`s and to the
+    /// [`Server`]. Other `Client`s receive every message that was sent before
+    /// closing, followed by the end of the stream. Once closed, messages can
+    /// no longer be sent with this `Client`.
+    ///
+    /// All connections are closed even if closing one of them fails, in which
+    /// case the first error is returned.
+    ///
+    /// [`Server`]: struct.Server.html
+    pub async fn close(&mut self) -> Result<(), LiquidError> {
+        let mut result = Ok(());
+        for (_, mut conn) in self.directory.drain() {
+            let closed = conn.close().await;
+            result = result.and(closed);
+        }
+        let closed = self.server.close().await;
+        result.and(closed)
+    }
+
+    /// Returns the `id` of this `Client`, which is the `node_id` assigned by
+    /// the [`Server`] during registration. The `id` is fixed for the lifetime
+    /// of this `Client`: sending and receiving messages only ever advances the
+    /// message id, so e.g. `client.id() == 1` can always be used to check if
+    /// this is the first node.
+    ///
+    /// [`Server`]: struct.Server.html
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
     /// Broadcast the given `message` to all currently connected clients
     pub async fn broadcast(&mut self, message: RT) -> Result<(), LiquidError> {
         let d: Vec<usize> = self.directory.iter().map(|(k, _)| *k).collect();
@@ -466,26 +711,167 @@ impl<RT: Send + Sync + DeserializeOwned + Serialize + Clone + 'static>
         }
         Ok(())
     }
+}
 
-    /// Spawns a `tokio` task that will handle receiving [`ControlMsg::Kill`]
-    /// messages from the [`Server`]
+/// The result of a [`Client`]'s registration with the [`Server`]
+///
+/// [`Client`]: struct.Client.html
+/// [`Server`]: struct.Server.html
+#[derive(Debug)]
+pub(crate) struct Registration<S> {
+    /// The `node_id` assigned to the [`Client`] by the [`Server`]
     ///
+    /// [`Client`]: struct.Client.html
     /// [`Server`]: struct.Server.html
-    /// [`ControlMsg::Kill`]: enum.ControlMsg.html#variant.Kill
-    fn recv_server_msg(
-        mut reader: FramedStream<ControlMsg>,
-        notifier: Arc<Notify>,
-    ) {
-        tokio::spawn(async move {
-            let kill_msg: Message<ControlMsg> =
-                message::read_msg(&mut reader).await.unwrap();
-            match &kill_msg.msg {
-                ControlMsg::Kill => {
-                    notifier.notify();
-                    Ok(())
-                }
-                _ => Err(LiquidError::UnexpectedMessage),
+    pub(crate) id: usize,
+    /// The id of the directory message sent by the [`Server`]
+    ///
+    /// [`Server`]: struct.Server.html
+    pub(crate) msg_id: u64,
+    /// The ids and addresses of all other [`Client`]s already in the network
+    ///
+    /// [`Client`]: struct.Client.html
+    pub(crate) dir: Vec<(usize, SocketAddr)>,
+    /// The stream for reading further messages from the [`Server`]
+    ///
+    /// [`Server`]: struct.Server.html
+    pub(crate) stream: FramedStream<ControlMsg, S>,
+    /// The sink for sending messages to the [`Server`]
+    ///
+    /// [`Server`]: struct.Server.html
+    pub(crate) sink: FramedSink<ControlMsg, S>,
+}
+
+/// Registers a [`Client`] listening at `my_address` in the network with the
+/// given `network_name` and `num_nodes` with the [`Server`] connected over the
+/// given `transport`. Introduces the [`Client`] to the [`Server`] and waits
+/// for the [`Server`] to respond with the directory of existing [`Client`]s.
+///
+/// A new [`Client`] passes an `id` of `0` to be assigned the next id, while a
+/// [`Client`] that is re-registering passes the `id` it already has.
+///
+/// [`Client`]: struct.Client.html
+/// [`Server`]: struct.Server.html
+pub(crate) async fn register_with_server<S: Transport>(
+    transport: S,
+    id: usize,
+    my_address: SocketAddr,
+    network_name: &str,
+    num_nodes: usize,
+) -> Result<Registration<S>, LiquidError> {
+    let (reader, writer) = io::split(transport);
+    let mut stream = FramedRead::new(reader, MessageCodec::new());
+    let mut sink = FramedWrite::new(writer, MessageCodec::new());
+    // Tell the server our address and type
+    send_introduction(
+        &mut sink,
+        0,
+        id,
+        my_address,
+        network_name.to_string(),
+        num_nodes,
+    )
+    .await?;
+    // Server responds with the addresses of all currently connected clients
+    let dir_msg = message::read_msg(&mut stream).await?;
+    let dir = if let ControlMsg::Directory { dir } = dir_msg.msg {
+        dir
+    } else {
+        return Err(LiquidError::UnexpectedMessage);
+    };
+
+    Ok(Registration {
+        id: dir_msg.target_id,
+        msg_id: dir_msg.msg_id,
+        dir,
+        stream,
+        sink,
+    })
+}
+
+/// Waits for the [`Server`] to send a `ControlMsg::NetworkReady` over the
+/// given `stream`, i.e. until every [`Client`] in the network has registered.
+/// Returns a `LiquidError::UnexpectedMessage` if the [`Server`] sends any
+/// other message.
+///
+/// [`Client`]: struct.Client.html
+/// [`Server`]: struct.Server.html
+pub(crate) async fn wait_for_network_ready<S: Transport>(
+    stream: &mut FramedStream<ControlMsg, S>,
+) -> Result<(), LiquidError> {
+    match message::read_msg(stream).await?.msg {
+        ControlMsg::NetworkReady => Ok(()),
+        _ => Err(LiquidError::UnexpectedMessage),
+    }
+}
+
+/// Connects to the `Client` of a parent network listening at `addr`, e.g. to
+/// send it a `ControlMsg::Ready` in [`register_network`]. That `Client` only
+/// listens once it calls [`register_network`] itself, so a refused connection
+/// is retried with an exponential backoff, starting at
+/// `PEER_CONNECT_BACKOFF_MS` milliseconds, up to `PEER_CONNECT_ATTEMPTS`
+/// attempts.
+///
+/// [`register_network`]: struct.Client.html#method.register_network
+async fn connect_to_peer(addr: SocketAddr) -> Result<TcpStream, LiquidError> {
+    let mut backoff = PEER_CONNECT_BACKOFF_MS;
+    let mut attempt = 1;
+    loop {
+        match TcpStream::connect(addr).await {
+            Ok(socket) => return Ok(socket),
+            Err(e)
+                if e.kind() == io::ErrorKind::ConnectionRefused
+                    && attempt < PEER_CONNECT_ATTEMPTS =>
+            {
+                debug!("Connection attempt {} to {} failed", attempt, addr);
+                delay_for(Duration::from_millis(backoff)).await;
+                attempt += 1;
+                backoff *= 2;
             }
-        });
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Sends a `ControlMsg::Introduction` with the given `address`,
+/// `network_name` and `num_nodes` from the node with the given `sender_id`
+/// over the given `sink`
+pub(crate) async fn send_introduction<S: Transport>(
+    sink: &mut FramedSink<ControlMsg, S>,
+    msg_id: u64,
+    sender_id: usize,
+    address: SocketAddr,
+    network_name: String,
+    num_nodes: usize,
+) -> Result<(), LiquidError> {
+    let intro = ControlMsg::Introduction {
+        address,
+        network_name,
+        num_nodes,
+    };
+    sink.send(Message::new(msg_id, sender_id, 0, intro)).await
+}
+
+/// Reads a `ControlMsg::Introduction` from the given `stream` and returns the
+/// `(sender_id, msg_id, address)` of the node that introduced itself. Returns
+/// a `LiquidError::UnexpectedMessage` if the message is not an introduction
+/// or the node is not in the network with the given `accepted_network`.
+pub(crate) async fn read_introduction<S: Transport>(
+    stream: &mut FramedStream<ControlMsg, S>,
+    accepted_network: &str,
+) -> Result<(usize, u64, SocketAddr), LiquidError> {
+    let intro = message::read_msg(stream).await?;
+    match intro.msg {
+        // we only want to connect with other clients that are the same type
+        // as us
+        ControlMsg::Introduction {
+            address,
+            network_name,
+            ..
+        } if network_name == accepted_network => {
+            Ok((intro.sender_id, intro.msg_id, address))
+        }
+        // we should only receive `ControlMsg::Introduction` msgs here
+        _ => Err(LiquidError::UnexpectedMessage),
     }
 }
@@ -1,7 +1,7 @@
 //! Defines messages and codecs used to communicate with the network of nodes
 //! over `TCP`.
 use crate::error::LiquidError;
-use crate::network::Connection;
+use crate::network::{Connection, Transport};
 use crate::{BYTES_PER_KIB, MAX_FRAME_LEN_FRACTION};
 use bincode::{deserialize, serialize};
 use bytes::{Bytes, BytesMut};
@@ -19,18 +19,24 @@ use tokio_util::codec::{
 };
 
 /// A buffered and framed message codec for reading messages of type `T`
-pub(crate) type FramedStream<T> =
-    FramedRead<ReadHalf<TcpStream>, MessageCodec<T>>;
+/// from a [`Transport`] of type `S`
+///
+/// [`Transport`]: trait.Transport.html
+pub(crate) type FramedStream<T, S = TcpStream> =
+    FramedRead<ReadHalf<S>, MessageCodec<T>>;
 /// A buffered and framed message codec for sending messages of type `T`
-pub(crate) type FramedSink<T> =
-    FramedWrite<WriteHalf<TcpStream>, MessageCodec<T>>;
+/// over a [`Transport`] of type `S`
+///
+/// [`Transport`]: trait.Transport.html
+pub(crate) type FramedSink<T, S = TcpStream> =
+    FramedWrite<WriteHalf<S>, MessageCodec<T>>;
 
 /// A message that can sent between nodes for communication. The message
 /// is generic for type `T`
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Message<T> {
-    /// The id of this message
-    pub msg_id: usize,
+    /// The id of this message. Message ids wrap around to `0` on overflow
+    pub msg_id: u64,
     /// The id of the sender
     pub sender_id: usize,
     /// The id of the node this message is being sent to
@@ -54,10 +60,12 @@ pub enum ControlMsg {
     /// [`Client`]: struct.Client.html
     Directory { dir: Vec<(usize, SocketAddr)> },
     /// An introduction that a new [`Client`] sends to all other existing
-    /// [`Client`]s and the [`Server`]
+    /// [`Client`]s and the [`Server`], along with the number of nodes the
+    /// [`Client`] expects in its network
     Introduction {
         address: SocketAddr,
         network_name: String,
+        num_nodes: usize,
     },
     /// A message the [`Server`] sends to [`Client`]s to inform them to shut
     /// down
@@ -65,6 +73,12 @@ pub enum ControlMsg {
     /// [`Server`]: struct.Server.html
     /// [`Client`]: struct.Client.html
     Kill,
+    /// A message the [`Server`] broadcasts to all [`Client`]s in a network
+    /// once the expected number of [`Client`]s have registered in it
+    ///
+    /// [`Server`]: struct.Server.html
+    /// [`Client`]: struct.Client.html
+    NetworkReady,
     /// A message to notify other [`Client`]s when they are ready to register
     /// a new [`Client`] type
     Ready,
@@ -73,7 +87,7 @@ pub enum ControlMsg {
 impl<T> Message<T> {
     /// Creates a new `Message`.
     pub fn new(
-        msg_id: usize,
+        msg_id: u64,
         sender_id: usize,
         target_id: usize,
         msg: T,
@@ -101,7 +115,7 @@ pub struct MessageCodec<T> {
 impl<T> MessageCodec<T> {
     /// Creates a new `MessageCodec` with a maximum frame length that is 80%
     /// of the total memory on this machine.
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         let memo_info_kind = RefreshKind::new().with_memory();
         let sys = System::new_with_specifics(memo_info_kind);
         let total_memory = sys.get_total_memory() as f64;
@@ -117,6 +131,12 @@ impl<T> MessageCodec<T> {
     }
 }
 
+impl<T> Default for MessageCodec<T> {
+    fn default() -> Self {
+        MessageCodec::new()
+    }
+}
+
 impl<T: DeserializeOwned> Decoder for MessageCodec<T> {
     type Item = Message<T>;
     type Error = LiquidError;
@@ -150,8 +170,8 @@ impl<T: Serialize> Encoder<Message<T>> for MessageCodec<T> {
 }
 
 /// Asynchronously waits to read the next message from the given `reader`
-pub(crate) async fn read_msg<T: DeserializeOwned>(
-    reader: &mut FramedStream<T>,
+pub(crate) async fn read_msg<T: DeserializeOwned, S: Transport>(
+    reader: &mut FramedStream<T, S>,
 ) -> Result<Message<T>, LiquidError> {
     match reader.next().await {
         None => Err(LiquidError::StreamClosed),
@@ -161,10 +181,10 @@ pub(crate) async fn read_msg<T: DeserializeOwned>(
 
 /// Send the given `message` to the node with the given `target_id` using
 /// the given `directory`
-pub(crate) async fn send_msg<T: Serialize>(
+pub(crate) async fn send_msg<T: Serialize, S: Transport>(
     target_id: usize,
     message: Message<T>,
-    directory: &mut HashMap<usize, Connection<T>>,
+    directory: &mut HashMap<usize, Connection<T, S>>,
 ) -> Result<(), LiquidError> {
     match directory.get_mut(&target_id) {
         None => Err(LiquidError::UnknownId),
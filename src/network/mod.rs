@@ -19,6 +19,10 @@
 //!
 //! If an address is not provided, the [`Server`] defaults to `127.0.0.1:9000`
 //!
+//! If the [`Server`] is restarted at the same address, [`Client`]s notice that
+//! their connection to it was dropped and register again, keeping the
+//! `node_id`s they were assigned.
+//!
 //! # [`Client`] Design
 //!
 //! The [`Client`] is designed with concurrency in mind and can be used to
@@ -40,6 +44,15 @@
 //! }
 //! ```
 //!
+//! # Transports
+//!
+//! Messages are framed by a [`MessageCodec`] and sent over a [`Transport`],
+//! which is any bidirectional byte stream. Networked nodes use `TCP`, but
+//! both the [`Server`] and the [`Client`] are generic over the [`Transport`],
+//! so that registration and messaging between [`Client`]s can be tested with
+//! in-memory streams. Only [`Client::new`] and [`accept_new_connections`],
+//! which open their own connections, require `TCP`.
+//!
 //!
 //! [`Client`]: struct.Client.html
 //! [`Server`]: struct.Server.html
@@ -47,20 +60,24 @@
 //! [`accept_new_connections`]: struct.Server.html#method.accept_new_connections
 //! [`Client::register_network`]: struct.Client.html#method.register_network
 //! [`Client::new`]: struct.Client.html#method.new
+//! [`MessageCodec`]: struct.MessageCodec.html
+//! [`Transport`]: trait.Transport.html
 //! [`SelectAll`]: https://docs.rs/futures/0.3.4/futures/stream/struct.SelectAll.html
 use crate::error::LiquidError;
 use crate::network::message::FramedSink;
-use std::net::Shutdown;
+use futures::SinkExt;
+use serde::Serialize;
+use socket2::{Domain, Socket, Type};
 use std::net::SocketAddr;
 use tokio::io::{ReadHalf, WriteHalf};
-use tokio::net::TcpStream;
+use tokio::net::{TcpListener, TcpStream};
 use tokio_util::codec::{FramedRead, FramedWrite};
 
 /// A connection to another [`Client`], used for directed communication
 ///
 /// [`Client`]: struct.Client.html
 #[derive(Debug)]
-pub(crate) struct Connection<T> {
+pub(crate) struct Connection<T, S = TcpStream> {
     /// The address of another [`Client`] that we're connected to
     ///
     /// [`Client`]: struct.Client.html
@@ -69,23 +86,59 @@ pub(crate) struct Connection<T> {
     /// other [`Client`]
     ///
     /// [`Client`]: struct.Client.html
-    pub(crate) sink: FramedSink<T>,
+    pub(crate) sink: FramedSink<T, S>,
+}
+
+impl<T: Serialize, S: Transport> Connection<T, S> {
+    /// Flushes any messages that are still buffered and shuts down the
+    /// sending half of this `Connection`, so the other [`Client`] sees the end
+    /// of the stream once it has read every message.
+    ///
+    /// [`Client`]: struct.Client.html
+    pub(crate) async fn close(&mut self) -> Result<(), LiquidError> {
+        self.sink.close().await
+    }
 }
 
-pub(crate) fn existing_conn_err<T, U>(
-    stream: FramedRead<ReadHalf<TcpStream>, MessageCodec<T>>,
-    sink: FramedWrite<WriteHalf<TcpStream>, MessageCodec<U>>,
+pub(crate) fn existing_conn_err<T, U, S: Transport>(
+    stream: FramedRead<ReadHalf<S>, MessageCodec<T>>,
+    sink: FramedWrite<WriteHalf<S>, MessageCodec<U>>,
 ) -> LiquidError {
-    // Already have an open connection to this client, shut
-    // down the one we just created.
+    // Already have an open connection to this client, close the one we just
+    // created by dropping it. The other side may have already closed it, in
+    // which case there is nothing left to close.
     let reader = stream.into_inner();
-    let unsplit = reader.unsplit(sink.into_inner());
-    unsplit.shutdown(Shutdown::Both).unwrap();
+    drop(reader.unsplit(sink.into_inner()));
     LiquidError::ReconnectionError
 }
 
-pub(crate) fn increment_msg_id(cur_id: usize, id: usize) -> usize {
-    std::cmp::max(cur_id, id) + 1
+/// Binds a `TcpListener` to the given `address` with `SO_REUSEADDR` set, so
+/// that a node restarted at the same address can bind it again right away
+/// instead of failing with `AddrInUse` while the connections of the previous
+/// node are in `TIME_WAIT`.
+pub(crate) fn bind_reusable(
+    address: &SocketAddr,
+) -> Result<TcpListener, LiquidError> {
+    let domain = match address {
+        SocketAddr::V4(_) => Domain::ipv4(),
+        SocketAddr::V6(_) => Domain::ipv6(),
+    };
+    let socket = Socket::new(domain, Type::stream(), None)?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&(*address).into())?;
+    socket.listen(1024)?;
+    let listener = socket.into_tcp_listener();
+    listener.set_nonblocking(true)?;
+    Ok(TcpListener::from_std(listener)?)
+}
+
+/// Returns the next message id to use after receiving a message with the
+/// given `id` when our current message id is `cur_id`. Only ever affects
+/// message ids, never the id of a node. Message ids wrap around to `0` on
+/// overflow instead of panicking, so long-lived nodes can't crash because of
+/// them.
+pub(crate) fn increment_msg_id(cur_id: u64, id: u64) -> u64 {
+    std::cmp::max(cur_id, id).wrapping_add(1)
 }
 
 mod client;
@@ -96,4 +149,35 @@ pub(crate) use message::FramedStream;
 pub use message::{ControlMsg, Message, MessageCodec};
 
 mod server;
-pub use server::Server;
+pub use server::{DuplicateAddressPolicy, Server, ServerStats};
+
+mod transport;
+pub use transport::Transport;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increment_msg_id() {
+        assert_eq!(increment_msg_id(3, 7), 8);
+        assert_eq!(increment_msg_id(7, 3), 8);
+        assert_eq!(increment_msg_id(u64::MAX, 3), 0);
+    }
+
+    #[tokio::test]
+    async fn test_bind_reusable_after_close() {
+        let address = "127.0.0.1:9205".parse().unwrap();
+        let mut listener = bind_reusable(&address).unwrap();
+        let (accepted, connected) =
+            tokio::join!(listener.accept(), TcpStream::connect(address));
+        let (accepted, _) = accepted.unwrap();
+        let connected = connected.unwrap();
+        // closing the accepted side first leaves the address in `TIME_WAIT`
+        drop(accepted);
+        drop(listener);
+        drop(connected);
+
+        assert!(bind_reusable(&address).is_ok());
+    }
+}
@@ -1,37 +1,107 @@
 //! Represents a server node in a distributed system, with implementations
 //! provided for `LiquidML` use cases.
 use crate::error::LiquidError;
-use crate::network::{message, Connection, ControlMsg, Message, MessageCodec};
-use log::info;
+use crate::network::{
+    bind_reusable, message, Connection, ControlMsg, Message, MessageCodec,
+    Transport,
+};
+use log::{info, warn};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::io::split;
-use tokio::net::TcpListener;
+use tokio::net::TcpStream;
 use tokio_util::codec::{FramedRead, FramedWrite};
 
-/// Represents a registration `Server` in a distributed system.
+/// Represents a registration `Server` in a distributed system. The `Server`
+/// communicates with [`Client`]s over a [`Transport`] of type `S`, which is a
+/// `TcpStream` unless otherwise specified.
+///
+/// [`Client`]: struct.Client.html
+/// [`Transport`]: trait.Transport.html
 #[derive(Debug)]
-pub struct Server {
+pub struct Server<S = TcpStream> {
     /// The `address` of this `Server`
     pub(crate) address: SocketAddr,
-    /// The id of the current message
-    pub(crate) msg_id: usize,
+    /// The id of the current message, wraps around to `0` on overflow
+    pub(crate) msg_id: u64,
     /// A directory which is a `HashMap` of network names to that network,
     /// (a `HashMap` of `node_id` to a [`Connection`]).
     ///
     /// [`Connection`]: struct.Connection.html
     pub(crate) directory:
-        HashMap<String, HashMap<usize, Connection<ControlMsg>>>,
+        HashMap<String, HashMap<usize, Connection<ControlMsg, S>>>,
+    /// The number of messages this `Server` has sent, see [`ServerStats`]
+    ///
+    /// [`ServerStats`]: struct.ServerStats.html
+    messages_sent: AtomicU64,
+    /// When this `Server` was created
+    started: Instant,
+    /// What to do when a [`Client`] registers from the same address as a
+    /// node that is already in its network
+    ///
+    /// [`Client`]: struct.Client.html
+    duplicate_address_policy: DuplicateAddressPolicy,
+}
+
+/// What a [`Server`] does when a [`Client`] registers from the same
+/// `IP:Port` address as a node that is already registered in the same
+/// network, e.g. because the node crashed and was restarted.
+///
+/// [`Server`]: struct.Server.html
+/// [`Client`]: struct.Client.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAddressPolicy {
+    /// Drop the stale connection and give the new [`Client`] the `node_id`
+    /// of the node it replaces. This is the default.
+    ///
+    /// [`Client`]: struct.Client.html
+    Replace,
+    /// Reject the registration with a `LiquidError::DuplicateAddress`,
+    /// keeping the existing node
+    Reject,
+}
+
+impl Default for DuplicateAddressPolicy {
+    fn default() -> Self {
+        DuplicateAddressPolicy::Replace
+    }
+}
+
+/// A snapshot of how a [`Server`] has been used, returned by [`stats`]. Useful
+/// for operators running a registration [`Server`] to see its load.
+///
+/// [`Server`]: struct.Server.html
+/// [`stats`]: struct.Server.html#method.stats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerStats {
+    /// The number of messages sent by the [`Server`] to [`Client`]s,
+    /// including directory messages sent during registration
+    ///
+    /// [`Server`]: struct.Server.html
+    /// [`Client`]: struct.Client.html
+    pub messages_sent: u64,
+    /// The number of registered nodes in each network, keyed by network name
+    pub nodes_per_network: HashMap<String, usize>,
+    /// How long it has been since the [`Server`] was created
+    ///
+    /// [`Server`]: struct.Server.html
+    pub uptime: Duration,
 }
 
 impl Server {
     /// Create a new `Server` running on the given `address` in the format of
-    /// `IP:Port`.
+    /// `IP:Port`. Returns a `LiquidError::ParseError` if the `address` is not
+    /// in that format.
     pub async fn new(address: &str) -> Result<Self, LiquidError> {
         Ok(Server {
             msg_id: 0,
             directory: HashMap::new(),
-            address: address.parse().unwrap(),
+            address: address.parse()?,
+            messages_sent: AtomicU64::new(0),
+            started: Instant::now(),
+            duplicate_address_policy: DuplicateAddressPolicy::default(),
         })
     }
 
@@ -42,57 +112,157 @@ impl Server {
     /// from the [`Client`] since this is not required for performing simple
     /// registration.
     ///
+    /// Registrations that are rejected because of the
+    /// [`DuplicateAddressPolicy`] are logged and do not stop this `Server`.
+    ///
     /// [`Client`]: struct.Client.html
+    /// [`DuplicateAddressPolicy`]: enum.DuplicateAddressPolicy.html
     pub async fn accept_new_connections(&mut self) -> Result<(), LiquidError> {
-        let mut listener = TcpListener::bind(&self.address).await?;
+        let mut listener = bind_reusable(&self.address)?;
         loop {
             // wait on connections from new clients
             let (socket, _) = listener.accept().await?;
-            let (reader, writer) = split(socket);
-            let mut stream = FramedRead::new(reader, MessageCodec::new());
-            let sink = FramedWrite::new(writer, MessageCodec::new());
-            // Receive the listening IP:Port address of the new client
-            let address = message::read_msg(&mut stream).await?;
-            let (address, network_name) = if let ControlMsg::Introduction {
+            match self.register(socket).await {
+                Err(LiquidError::DuplicateAddress(address)) => {
+                    warn!("Rejected a duplicate registration from {}", address)
+                }
+                result => {
+                    result?;
+                }
+            }
+        }
+    }
+}
+
+impl<S: Transport> Server<S> {
+    /// Create a new `Server` with the given `address` that does not listen
+    /// for `TCP` connections itself. [`Client`]s are instead registered by
+    /// passing an already connected [`Transport`] to the [`register`] method,
+    /// which is useful for testing registration with in-memory streams.
+    ///
+    /// [`Client`]: struct.Client.html
+    /// [`Transport`]: trait.Transport.html
+    /// [`register`]: struct.Server.html#method.register
+    pub fn with_address(address: SocketAddr) -> Self {
+        Server {
+            msg_id: 0,
+            directory: HashMap::new(),
+            address,
+            messages_sent: AtomicU64::new(0),
+            started: Instant::now(),
+            duplicate_address_policy: DuplicateAddressPolicy::default(),
+        }
+    }
+
+    /// Sets what this `Server` does when a [`Client`] registers from the same
+    /// address as a node already in its network. Defaults to
+    /// `DuplicateAddressPolicy::Replace`.
+    ///
+    /// [`Client`]: struct.Client.html
+    pub fn set_duplicate_address_policy(
+        &mut self,
+        policy: DuplicateAddressPolicy,
+    ) {
+        self.duplicate_address_policy = policy;
+    }
+
+    /// Performs the registration handshake with a newly started [`Client`]
+    /// that is connected to this `Server` over the given `transport`. Reads
+    /// the [`Client`]'s introduction, assigns it the next `node_id` in its
+    /// network, and responds with a directory of the other [`Client`]s
+    /// already in that network. Returns the assigned `node_id`.
+    ///
+    /// A [`Client`] that introduces itself with a non-zero id is
+    /// re-registering, e.g. because this `Server` was restarted, and keeps
+    /// that id. Any existing connection for that id is replaced.
+    ///
+    /// A [`Client`] at the same address as another node in its network is
+    /// handled according to the [`DuplicateAddressPolicy`] of this `Server`,
+    /// and a `LiquidError::DuplicateAddress` is returned if it is rejected.
+    ///
+    /// Once the number of [`Client`]s in the network reaches the number of
+    /// nodes the [`Client`] expects, a `ControlMsg::NetworkReady` is
+    /// broadcast to every [`Client`] in that network.
+    ///
+    /// [`Client`]: struct.Client.html
+    /// [`DuplicateAddressPolicy`]: enum.DuplicateAddressPolicy.html
+    pub async fn register(
+        &mut self,
+        transport: S,
+    ) -> Result<usize, LiquidError> {
+        let (reader, writer) = split(transport);
+        let mut stream = FramedRead::new(reader, MessageCodec::new());
+        let sink = FramedWrite::new(writer, MessageCodec::new());
+        // Receive the listening IP:Port address of the new client
+        let intro = message::read_msg(&mut stream).await?;
+        // a client that already has an id is re-registering, e.g. after this
+        // `Server` restarted
+        let requested_id = intro.sender_id;
+        let (address, network_name, num_nodes) =
+            if let ControlMsg::Introduction {
                 address,
                 network_name,
-            } = address.msg
+                num_nodes,
+            } = intro.msg
             {
-                (address, network_name)
+                (address, network_name, num_nodes)
             } else {
                 return Err(LiquidError::UnexpectedMessage);
             };
-            let conn = Connection { address, sink };
-
-            let target_id;
-            let dir;
-            match self.directory.get_mut(&network_name) {
-                Some(d) => {
-                    // there are some existing clients of this type
-                    target_id = d.len() + 1; // node id's start at 1
-                    dir = d.iter().map(|(k, v)| (*k, v.address)).collect();
-                    d.insert(target_id, conn);
-                }
-                None => {
-                    target_id = 1;
-                    dir = Vec::new();
-                    let mut d = HashMap::new();
-                    d.insert(target_id, conn);
-                    self.directory.insert(network_name.clone(), d);
-                }
-            };
+        let conn = Connection { address, sink };
+
+        let d = self
+            .directory
+            .entry(network_name.clone())
+            .or_insert_with(HashMap::new);
+        // a node that is already registered from this address, e.g. one that
+        // crashed and was restarted
+        let stale_id = d
+            .iter()
+            .find(|(id, c)| c.address == address && **id != requested_id)
+            .map(|(id, _)| *id);
+        if let Some(stale_id) = stale_id {
+            if self.duplicate_address_policy == DuplicateAddressPolicy::Reject {
+                return Err(LiquidError::DuplicateAddress(address));
+            }
+            d.remove(&stale_id);
+        }
+        let target_id = if requested_id != 0 {
+            requested_id
+        } else if let Some(stale_id) = stale_id {
+            stale_id
+        } else {
+            // node id's start at 1
+            d.keys().max().map_or(1, |id| id + 1)
+        };
+        let dir = d
+            .iter()
+            .filter(|(k, _)| **k != target_id)
+            .map(|(k, v)| (*k, v.address))
+            .collect();
+        // a re-registering client replaces its stale connection, if any
+        d.insert(target_id, conn);
+        let is_ready = d.len() == num_nodes;
 
+        info!(
+            "Connected to address: {:#?} joining network {:#?}, assigning id: {:#?}",
+            &address,
+            &network_name,
+            target_id
+        );
+
+        // Send the new client the list of existing nodes.
+        let dir_msg = ControlMsg::Directory { dir };
+        self.send_msg(target_id, &network_name, dir_msg).await?;
+        if is_ready {
             info!(
-                "Connected to address: {:#?} joining network {:#?}, assigning id: {:#?}",
-                &address,
-                &network_name,
-                target_id
+                "All {} nodes in network {} registered",
+                num_nodes, network_name
             );
-
-            // Send the new client the list of existing nodes.
-            let dir_msg = ControlMsg::Directory { dir };
-            self.send_msg(target_id, &network_name, dir_msg).await?;
+            self.broadcast(ControlMsg::NetworkReady, &network_name)
+                .await?;
         }
+        Ok(target_id)
     }
 
     /// Send the given `message` to a [`Client`] running in the network with
@@ -112,10 +282,25 @@ impl Server {
             self.directory.get_mut(network_name).unwrap(),
         )
         .await?;
-        self.msg_id += 1;
+        self.msg_id = self.msg_id.wrapping_add(1);
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
+    /// Returns a snapshot of the message count, the number of nodes in each
+    /// network, and the uptime of this `Server`.
+    pub fn stats(&self) -> ServerStats {
+        ServerStats {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            nodes_per_network: self
+                .directory
+                .iter()
+                .map(|(name, nodes)| (name.clone(), nodes.len()))
+                .collect(),
+            uptime: self.started.elapsed(),
+        }
+    }
+
     /// Broadcast the given `message` to all currently connected [`Clients`]
     /// in the network with the given `network_name`
     ///
@@ -140,3 +325,94 @@ impl Server {
         Ok(())
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::network::client::register_with_server;
+    use tokio::net::UnixStream;
+
+    #[tokio::test]
+    async fn test_stats() {
+        let mut server = Server::<UnixStream>::with_address(
+            "127.0.0.1:9210".parse().unwrap(),
+        );
+        assert_eq!(server.stats().messages_sent, 0);
+        assert!(server.stats().nodes_per_network.is_empty());
+
+        // each registration sends one directory message, the registrations
+        // are kept so that the connections stay open
+        let mut registrations = Vec::new();
+        for (port, network_name) in &[(9211, "a"), (9212, "a"), (9213, "b")] {
+            let (server_end, client_end) = UnixStream::pair().unwrap();
+            let addr = format!("127.0.0.1:{}", port).parse().unwrap();
+            let (id, reg) = tokio::join!(
+                server.register(server_end),
+                register_with_server(client_end, 0, addr, network_name, 3)
+            );
+            id.unwrap();
+            registrations.push(reg.unwrap());
+        }
+        let stats = server.stats();
+        assert_eq!(stats.messages_sent, 3);
+        assert_eq!(stats.nodes_per_network.len(), 2);
+        assert_eq!(stats.nodes_per_network["a"], 2);
+        assert_eq!(stats.nodes_per_network["b"], 1);
+
+        for _ in 0..5 {
+            server.send_msg(1, "b", ControlMsg::Kill).await.unwrap();
+        }
+        assert_eq!(server.stats().messages_sent, 8);
+        assert!(server.stats().uptime >= stats.uptime);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_address() {
+        let mut server = Server::<UnixStream>::with_address(
+            "127.0.0.1:9214".parse().unwrap(),
+        );
+        let addr_1 = "127.0.0.1:9215".parse().unwrap();
+        let addr_2 = "127.0.0.1:9216".parse().unwrap();
+        let mut registrations = Vec::new();
+        for addr in &[addr_1, addr_2] {
+            let (server_end, client_end) = UnixStream::pair().unwrap();
+            let (id, reg) = tokio::join!(
+                server.register(server_end),
+                register_with_server(client_end, 0, *addr, "dup", 3)
+            );
+            id.unwrap();
+            registrations.push(reg.unwrap());
+        }
+
+        // by default, a restarted node replaces the stale one and keeps its id
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let (id, reg) = tokio::join!(
+            server.register(server_end),
+            register_with_server(client_end, 0, addr_1, "dup", 3)
+        );
+        assert_eq!(id.unwrap(), 1);
+        assert_eq!(reg.unwrap().dir, vec![(2, addr_2)]);
+        assert_eq!(server.stats().nodes_per_network["dup"], 2);
+
+        // or it can be rejected
+        server.set_duplicate_address_policy(DuplicateAddressPolicy::Reject);
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let (id, reg) = tokio::join!(
+            server.register(server_end),
+            register_with_server(client_end, 0, addr_2, "dup", 3)
+        );
+        assert!(
+            matches!(id, Err(LiquidError::DuplicateAddress(a)) if a == addr_2)
+        );
+        assert!(reg.is_err());
+        assert_eq!(server.stats().nodes_per_network["dup"], 2);
+
+        // the same address in another network is not a duplicate
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let (id, _reg) = tokio::join!(
+            server.register(server_end),
+            register_with_server(client_end, 0, addr_2, "other", 3)
+        );
+        assert_eq!(id.unwrap(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Defines the [`Transport`] trait that abstracts over the underlying byte
+//! streams that messages between nodes are sent over.
+//!
+//! [`Transport`]: trait.Transport.html
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// A bidirectional byte stream that framed messages can be read from and
+/// written to. A `Transport` is split into read and write halves which are
+/// wrapped by a [`MessageCodec`].
+///
+/// `Transport` is implemented for every type that is both `AsyncRead` and
+/// `AsyncWrite`. Networked nodes use a `TcpStream`, while tests may use an
+/// in-memory pair of connected streams (e.g. `UnixStream::pair`) to drive
+/// message flows without opening any `TCP` sockets.
+///
+/// [`MessageCodec`]: struct.MessageCodec.html
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<S: AsyncRead + AsyncWrite + Send + Unpin + 'static> Transport for S {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::network::client::{
+        read_introduction, register_with_server, send_introduction,
+        wait_for_network_ready,
+    };
+    use crate::network::{message, Client, ControlMsg, MessageCodec, Server};
+    use std::net::SocketAddr;
+    use tokio::io::split;
+    use tokio::net::UnixStream;
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    #[tokio::test]
+    async fn test_in_memory_handshake() {
+        let network_name = "in-memory";
+        let addr_1: SocketAddr = "127.0.0.1:9201".parse().unwrap();
+        let addr_2: SocketAddr = "127.0.0.1:9202".parse().unwrap();
+        let mut server = Server::<UnixStream>::with_address(
+            "127.0.0.1:9200".parse().unwrap(),
+        );
+
+        // register the first node, it should get an empty directory
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let (id, reg_1) = tokio::join!(
+            server.register(server_end),
+            register_with_server(client_end, 0, addr_1, network_name, 2)
+        );
+        let mut reg_1 = reg_1.unwrap();
+        assert_eq!(id.unwrap(), 1);
+        assert_eq!(reg_1.id, 1);
+        assert!(reg_1.dir.is_empty());
+
+        // register the second node, it should be told about the first node
+        let (server_end, client_end) = UnixStream::pair().unwrap();
+        let (id, reg_2) = tokio::join!(
+            server.register(server_end),
+            register_with_server(client_end, 0, addr_2, network_name, 2)
+        );
+        let mut reg_2 = reg_2.unwrap();
+        assert_eq!(id.unwrap(), 2);
+        assert_eq!(reg_2.id, 2);
+        assert_eq!(reg_2.dir, vec![(1, addr_1)]);
+
+        // both nodes are told that the network is now full
+        for reg in &mut [&mut reg_1, &mut reg_2] {
+            let msg = message::read_msg(&mut reg.stream).await.unwrap();
+            assert!(matches!(msg.msg, ControlMsg::NetworkReady));
+        }
+
+        // the second node connects to the first node and introduces itself
+        let (end_1, end_2) = UnixStream::pair().unwrap();
+        let (reader_1, writer_1) = split(end_1);
+        let (reader_2, writer_2) = split(end_2);
+        let mut stream_1 =
+            FramedRead::new(reader_1, MessageCodec::<ControlMsg>::new());
+        let mut sink_1 =
+            FramedWrite::new(writer_1, MessageCodec::<ControlMsg>::new());
+        let mut stream_2 =
+            FramedRead::new(reader_2, MessageCodec::<ControlMsg>::new());
+        let mut sink_2 =
+            FramedWrite::new(writer_2, MessageCodec::<ControlMsg>::new());
+        send_introduction(
+            &mut sink_2,
+            reg_2.msg_id,
+            reg_2.id,
+            addr_2,
+            network_name.to_string(),
+            2,
+        )
+        .await
+        .unwrap();
+        let intro = read_introduction(&mut stream_1, network_name)
+            .await
+            .unwrap();
+        assert_eq!(intro, (2, reg_2.msg_id, addr_2));
+
+        // messages now flow in both directions over the connection
+        send_introduction(
+            &mut sink_1,
+            reg_1.msg_id,
+            reg_1.id,
+            addr_1,
+            network_name.to_string(),
+            2,
+        )
+        .await
+        .unwrap();
+        let msg = message::read_msg(&mut stream_2).await.unwrap();
+        assert_eq!(msg.sender_id, 1);
+
+        // a node from a different network is rejected
+        send_introduction(&mut sink_2, 0, 2, addr_2, "other".to_string(), 2)
+            .await
+            .unwrap();
+        assert!(read_introduction(&mut stream_1, network_name)
+            .await
+            .is_err());
+    }
+    #[tokio::test]
+    async fn test_in_memory_clients() {
+        let network_name = "in-memory-clients";
+        let server_addr: SocketAddr = "127.0.0.1:9210".parse().unwrap();
+        let addr_1: SocketAddr = "127.0.0.1:9211".parse().unwrap();
+        let addr_2: SocketAddr = "127.0.0.1:9212".parse().unwrap();
+        let mut server = Server::<UnixStream>::with_address(server_addr);
+
+        // both clients register with the server over in-memory streams
+        let mut clients = vec![];
+        for addr in &[addr_1, addr_2] {
+            let (server_end, client_end) = UnixStream::pair().unwrap();
+            let (id, reg) = tokio::join!(
+                server.register(server_end),
+                register_with_server(client_end, 0, *addr, network_name, 2)
+            );
+            let (client, dir, stream) =
+                Client::<ControlMsg, UnixStream>::from_registration(
+                    reg.unwrap(),
+                    *addr,
+                    server_addr,
+                    2,
+                    network_name,
+                );
+            assert_eq!(client.id(), id.unwrap());
+            clients.push((client, dir, stream));
+        }
+        let (mut client_2, dir_2, mut server_stream_2) = clients.pop().unwrap();
+        let (mut client_1, dir_1, mut server_stream_1) = clients.pop().unwrap();
+        assert!(dir_1.is_empty());
+        assert_eq!(dir_2, vec![(1, addr_1)]);
+        wait_for_network_ready(&mut server_stream_1).await.unwrap();
+        wait_for_network_ready(&mut server_stream_2).await.unwrap();
+
+        // the second client connects to the first one from its directory
+        let (end_1, end_2) = UnixStream::pair().unwrap();
+        let (stream_1, stream_2) = tokio::join!(
+            client_1.accept(end_1),
+            client_2.connect_over(1, addr_1, end_2)
+        );
+        let (mut stream_1, mut stream_2) =
+            (stream_1.unwrap(), stream_2.unwrap());
+
+        // messages now flow in both directions between the clients
+        client_2.send_msg(1, ControlMsg::Ready).await.unwrap();
+        let msg = message::read_msg(&mut stream_1).await.unwrap();
+        assert_eq!((msg.sender_id, msg.target_id), (2, 1));
+        assert!(matches!(msg.msg, ControlMsg::Ready));
+        client_1.broadcast(ControlMsg::Kill).await.unwrap();
+        let msg = message::read_msg(&mut stream_2).await.unwrap();
+        assert_eq!((msg.sender_id, msg.target_id), (1, 2));
+        assert!(matches!(msg.msg, ControlMsg::Kill));
+
+        // a second connection from the same client is rejected
+        let (end_1, end_2) = UnixStream::pair().unwrap();
+        let (accepted, connected) = tokio::join!(
+            client_1.accept(end_1),
+            client_2.connect_over(1, addr_1, end_2)
+        );
+        assert!(connected.is_err());
+        assert!(accepted.is_err());
+    }
+}
@@ -1,5 +1,76 @@
-use liquid_ml::dataframe::LocalDataFrame;
-use sorer::dataframe::Data;
+use futures::future::{join, join_all};
+use futures::{SinkExt, StreamExt};
+use liquid_ml::dataframe::{
+    Agg, Column, CsvOptions, Data, DataType, JoinKind, LocalDataFrame, Row,
+    Rower, Window,
+};
+use liquid_ml::error::LiquidError;
+use liquid_ml::kv::{KVStats, KVStore, Key, StoredValue};
+use liquid_ml::network::{Client, ControlMsg, Message, MessageCodec, Server};
+use liquid_ml::LiquidML;
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::split;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::time::{delay_for, timeout};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+const CLUSTER_TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Starts a registration `Server` at the given `address` in the background
+/// and waits a moment so that it is listening before clients connect.
+async fn start_server(address: &str) {
+    let mut server = Server::new(address).await.unwrap();
+    tokio::spawn(async move { server.accept_new_connections().await });
+    delay_for(Duration::from_millis(100)).await;
+}
+
+/// Runs the given cluster `test`, failing instead of hanging if it does not
+/// finish within `CLUSTER_TEST_TIMEOUT`.
+async fn with_timeout(test: impl Future<Output = ()>) {
+    timeout(CLUSTER_TEST_TIMEOUT, test)
+        .await
+        .expect("the cluster test timed out");
+}
+
+/// Starts a registration `Server` on `server_port` and a cluster of
+/// `num_nodes` `LiquidML` nodes listening on consecutive ports starting at
+/// `first_port`. The nodes are returned in order of their `node_id`.
+async fn start_cluster(
+    server_port: usize,
+    first_port: usize,
+    num_nodes: usize,
+) -> Vec<LiquidML> {
+    let server_addr = format!("127.0.0.1:{}", server_port);
+    start_server(&server_addr).await;
+    let addrs: Vec<String> = (first_port..first_port + num_nodes)
+        .map(|port| format!("127.0.0.1:{}", port))
+        .collect();
+    let mut apps: Vec<LiquidML> = join_all(
+        addrs
+            .iter()
+            .map(|addr| LiquidML::new(addr, &server_addr, num_nodes)),
+    )
+    .await
+    .into_iter()
+    .map(|app| app.unwrap())
+    .collect();
+    apps.sort_by_key(|app| app.node_id);
+    apps
+}
 
 #[test]
 fn test_from_sor() {
@@ -15,3 +86,1655 @@ fn test_from_sor() {
     assert_eq!(got.get(2, 1).unwrap(), Data::Float(0.5));
     assert_eq!(got.get(3, 1).unwrap(), Data::String("hello".to_string()));
 }
+
+#[test]
+fn test_from_sor_parallel() {
+    for file_name in &["tests/test.sor", "tests/distributed.sor"] {
+        let serial = LocalDataFrame::from_sor(file_name, 0, 1_000_000);
+        let parallel =
+            LocalDataFrame::from_sor_parallel(file_name, 0, 1_000_000).unwrap();
+        assert_eq!(serial.get_schema(), parallel.get_schema());
+        assert_eq!(serial.data, parallel.data);
+    }
+
+    // only the complete lines in the range are read
+    let contents = "<1> <2> <3> <4>\n<5> <6> <7> <8>\n<9> <10> <11> <12>\n";
+    let file_name = write_temp_sor(
+        &format!("liquid_ml_parallel_range_{}.sor", process::id()),
+        contents,
+    );
+    let df = LocalDataFrame::from_sor_parallel(&file_name, 3, 30).unwrap();
+    assert_eq!(df.n_rows(), 1);
+    assert_eq!(df.get(0, 0).unwrap(), Data::Int(5));
+    assert_eq!(df.get(3, 0).unwrap(), Data::Int(8));
+    let df = LocalDataFrame::from_sor_parallel(&file_name, 16, 100).unwrap();
+    assert_eq!(df.n_rows(), 2);
+    fs::remove_file(&file_name).unwrap();
+
+    assert!(matches!(
+        LocalDataFrame::from_sor_parallel("tests/missing.sor", 0, 10),
+        Err(_)
+    ));
+}
+
+/// Writes the given SoR `contents` to a file with the given `name` in the
+/// temporary directory and returns its path.
+fn write_temp_sor(name: &str, contents: &str) -> String {
+    let path = env::temp_dir().join(name);
+    fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_from_sor_many() {
+    let day_1 = write_temp_sor("liquid_ml_day_1.sor", "<12> <a>\n<34> <b>\n");
+    let day_2 = write_temp_sor("liquid_ml_day_2.sor", "<56> <c>\n");
+    let other = write_temp_sor("liquid_ml_other.sor", "<1.5> <12>\n");
+
+    let df = LocalDataFrame::from_sor_many(&[day_1.as_str(), day_2.as_str()])
+        .unwrap();
+    let n_rows = LocalDataFrame::from_sor(&day_1, 0, 100).n_rows()
+        + LocalDataFrame::from_sor(&day_2, 0, 100).n_rows();
+    assert_eq!(df.n_rows(), n_rows);
+    assert_eq!(df.n_rows(), 3);
+    assert_eq!(df.get(0, 2).unwrap(), Data::Int(56));
+    assert_eq!(df.get(1, 1).unwrap(), Data::String("b".to_string()));
+
+    match LocalDataFrame::from_sor_many(&[day_1.as_str(), other.as_str()]) {
+        Err(LiquidError::SchemaMismatch(file)) => assert_eq!(file, other),
+        _ => panic!("expected the schemas to mismatch"),
+    }
+}
+
+#[tokio::test]
+async fn test_run_until_returns_value() {
+    start_server("127.0.0.1:9100").await;
+    let app = LiquidML::new("127.0.0.1:9101", "127.0.0.1:9100", 1)
+        .await
+        .unwrap();
+    let sum = app
+        .run_until(|kv: Arc<KVStore<LocalDataFrame>>| async move {
+            let key = Key::new("nums", 1);
+            let nums = Column::Int((1..=10).map(Some).collect());
+            kv.put(key.clone(), LocalDataFrame::from(nums))
+                .await
+                .unwrap();
+            let df = kv.get(&key).await.unwrap();
+            (0..df.n_rows()).fold(0, |acc, i| match df.get(0, i).unwrap() {
+                Data::Int(x) => acc + x,
+                _ => unreachable!(),
+            })
+        })
+        .await;
+    assert_eq!(sum, 55);
+}
+
+#[tokio::test]
+async fn test_concurrent_blobs_to_node_1() {
+    with_timeout(async {
+        let apps = start_cluster(9110, 9111, 3).await;
+        join_all(
+            apps[1..]
+                .iter()
+                .map(|app| app.kv.send_blob(1, vec![app.node_id as u8])),
+        )
+        .await
+        .into_iter()
+        .for_each(|res| res.unwrap());
+        let mut received = Vec::new();
+        {
+            let mut blob_receiver = apps[0].blob_receiver.lock().await;
+            for _ in 1..apps.len() {
+                let blob =
+                    timeout(Duration::from_secs(5), blob_receiver.recv())
+                        .await
+                        .unwrap()
+                        .unwrap();
+                received.push(blob[0]);
+            }
+        }
+        received.sort();
+        assert_eq!(received, vec![2, 3]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_small_blob_capacity_does_not_stall() {
+    let server_addr = "127.0.0.1:9460";
+    start_server(server_addr).await;
+    let mut apps: Vec<LiquidML> =
+        join_all((9461..9464).map(|port| async move {
+            let addr = format!("127.0.0.1:{}", port);
+            LiquidML::with_blob_capacity(&addr, server_addr, 3, 1).await
+        }))
+        .await
+        .into_iter()
+        .map(|app| app.unwrap())
+        .collect();
+    apps.sort_by_key(|app| app.node_id);
+    // more blobs are sent to node 1 than its channel can hold
+    join_all(
+        apps[1..]
+            .iter()
+            .map(|app| app.kv.send_blob(1, vec![app.node_id as u8])),
+    )
+    .await
+    .into_iter()
+    .for_each(|res| res.unwrap());
+
+    // node 1 still processes other messages before it receives the blobs
+    let key = Key::new("unblocked", 2);
+    let df = LocalDataFrame::from(Data::Int(2));
+    apps[1].kv.put(key.clone(), df).await.unwrap();
+    let got = timeout(Duration::from_secs(5), apps[0].kv.wait_and_get(&key))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(got.get(0, 0).unwrap(), Data::Int(2));
+
+    let mut received = Vec::new();
+    let mut blob_receiver = apps[0].blob_receiver.lock().await;
+    for _ in 1..apps.len() {
+        let blob = timeout(Duration::from_secs(5), blob_receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        received.push(blob[0]);
+    }
+    received.sort();
+    assert_eq!(received, vec![2, 3]);
+}
+
+#[tokio::test]
+async fn test_put_if_absent() {
+    start_server("127.0.0.1:9120").await;
+    let app = LiquidML::new("127.0.0.1:9121", "127.0.0.1:9120", 1)
+        .await
+        .unwrap();
+    let key = Key::new("once", 1);
+    let first = LocalDataFrame::from(Data::Int(1));
+    let second = LocalDataFrame::from(Data::Int(2));
+    assert!(app.kv.put_if_absent(key.clone(), first).await.unwrap());
+    assert!(!app.kv.put_if_absent(key.clone(), second).await.unwrap());
+    let df = app.kv.get(&key).await.unwrap();
+    assert_eq!(df.get(0, 0).unwrap(), Data::Int(1));
+}
+
+#[tokio::test]
+async fn test_io_error_kind_is_preserved() {
+    let _listener = std::net::TcpListener::bind("127.0.0.1:9130").unwrap();
+    let mut server = Server::new("127.0.0.1:9130").await.unwrap();
+    match server.accept_new_connections().await {
+        Err(err @ LiquidError::NetworkError(_)) => {
+            assert!(err.source().is_some());
+            if let LiquidError::NetworkError(io_err) = err {
+                assert_eq!(io_err.kind(), io::ErrorKind::AddrInUse);
+            }
+        }
+        _ => panic!("expected the bind to fail with a network error"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_large_value_in_chunks() {
+    with_timeout(async {
+        let apps = start_cluster(9150, 9151, 2).await;
+        let n_rows = 2_000_000;
+        let ints = (0..n_rows).map(|i| Some(i as i64)).collect();
+        let floats = (0..n_rows).map(|i| Some(i as f64 / 2.0)).collect();
+        let expected = LocalDataFrame::from(vec![
+            Column::Int(ints),
+            Column::Float(floats),
+        ]);
+        let key = Key::new("large", 1);
+        apps[0].kv.put(key.clone(), expected.clone()).await.unwrap();
+        let got = apps[1].kv.wait_and_get(&key).await.unwrap();
+        assert_eq!(got.n_rows(), n_rows);
+        assert_eq!(*got, expected);
+    })
+    .await;
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Summary {
+    name: String,
+    counts: Vec<usize>,
+    mean: f64,
+}
+
+#[tokio::test]
+async fn test_send_typed() {
+    with_timeout(async {
+        let apps = start_cluster(9160, 9161, 2).await;
+        let summary = Summary {
+            name: "node 2".to_string(),
+            counts: vec![1, 2, 3],
+            mean: 2.0,
+        };
+        apps[1].kv.send_typed(1, &summary).await.unwrap();
+        let got: Summary = apps[0].recv_typed().await.unwrap();
+        assert_eq!(got, summary);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_panicking_task_is_observed() {
+    start_server("127.0.0.1:9170").await;
+    let kvs: Vec<Arc<KVStore<LocalDataFrame>>> =
+        join_all(["127.0.0.1:9171", "127.0.0.1:9172"].iter().map(|addr| {
+            // dropping the receiver makes forwarding any blob panic
+            let (blob_sender, _) = mpsc::channel(1);
+            KVStore::new(
+                "127.0.0.1:9170".to_string(),
+                addr.to_string(),
+                blob_sender,
+                2,
+                "panics".to_string(),
+            )
+        }))
+        .await;
+    for kv in &kvs {
+        assert!(kv.check_tasks().await.is_ok());
+        for id in 1..=2 {
+            // sending to ourselves fails, but the other node gets the blob
+            let _ = kv.send_blob(id, vec![0]).await;
+        }
+    }
+    for kv in &kvs {
+        let failure = timeout(Duration::from_secs(5), kv.task_failed())
+            .await
+            .unwrap();
+        assert!(matches!(failure, LiquidError::NodeTaskFailed(_)));
+        assert!(kv.check_tasks().await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_gets() {
+    start_server("127.0.0.1:9180").await;
+    let app = LiquidML::new("127.0.0.1:9181", "127.0.0.1:9180", 1)
+        .await
+        .unwrap();
+    let keys: Vec<Key> =
+        (0..20).map(|i| Key::new(&format!("df{}", i), 1)).collect();
+    for (i, key) in keys.iter().enumerate() {
+        let df = LocalDataFrame::from(Data::Int(i as i64));
+        app.kv.put(key.clone(), df).await.unwrap();
+    }
+    // a reader waiting for a key that isn't there yet holds no lock while it
+    // waits, so the other reads overlap with it
+    let waited = Arc::new(AtomicUsize::new(0));
+    let waiter = {
+        let kv = app.kv.clone();
+        let waited = waited.clone();
+        tokio::spawn(async move {
+            let df = kv.wait_and_get(&Key::new("later", 1)).await.unwrap();
+            waited.fetch_add(1, Ordering::SeqCst);
+            df
+        })
+    };
+    delay_for(Duration::from_millis(100)).await;
+    // more keys than fit in the cache, so some gets read the store itself
+    let gets = (0..200).map(|i| {
+        let kv = app.kv.clone();
+        let key = keys[i % keys.len()].clone();
+        tokio::spawn(async move { kv.get(&key).await.unwrap() })
+    });
+    let results = timeout(Duration::from_secs(5), join_all(gets))
+        .await
+        .unwrap();
+    for (i, df) in results.into_iter().enumerate() {
+        let expected = (i % keys.len()) as i64;
+        assert_eq!(df.unwrap().get(0, 0).unwrap(), Data::Int(expected));
+    }
+    assert_eq!(waited.load(Ordering::SeqCst), 0);
+
+    let df = LocalDataFrame::from(Data::Int(-1));
+    app.kv.put(Key::new("later", 1), df).await.unwrap();
+    let got = timeout(Duration::from_secs(5), waiter)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(got.get(0, 0).unwrap(), Data::Int(-1));
+}
+
+/// Returns an iterator of `n_chunks` chunks that each have one `Int` column
+/// of `chunk_len` rows, numbered consecutively from `0`.
+fn int_chunks(
+    n_chunks: usize,
+    chunk_len: usize,
+) -> impl Iterator<Item = Vec<Column>> {
+    (0..n_chunks).map(move |i| {
+        let start = i * chunk_len;
+        let col = (start..start + chunk_len).map(|x| Some(x as i64)).collect();
+        vec![Column::Int(col)]
+    })
+}
+
+#[tokio::test]
+async fn test_gather() {
+    with_timeout(async {
+        let mut apps = start_cluster(9190, 9191, 2).await;
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("gathered", int_chunks(4, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let mut gathered =
+            join_all(apps.iter().map(|app| app.gather("gathered"))).await;
+        assert!(gathered.pop().unwrap().unwrap().is_none());
+        let df = gathered.pop().unwrap().unwrap().unwrap();
+        assert_eq!(df.n_rows(), apps[0].data_frames["gathered"].num_rows);
+        assert_eq!(df.n_rows(), 40);
+        assert_eq!(df.get(0, 25).unwrap(), Data::Int(25));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_server_new_invalid_address() {
+    for address in &["not-an-address", "localhost:9000", "127.0.0.1"] {
+        match Server::new(address).await {
+            Err(LiquidError::ParseError(_)) => (),
+            _ => panic!("expected {} to fail to parse", address),
+        }
+    }
+}
+
+/// Computes the mean of the first `Int` column of a data frame. The running
+/// sum and count are only turned into the mean by `finalize`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct MeanRower {
+    sum: i64,
+    count: usize,
+    mean: Option<f64>,
+}
+
+impl Rower for MeanRower {
+    fn visit(&mut self, row: &Row) -> bool {
+        if let Data::Int(x) = row.get(0).unwrap() {
+            self.sum += x;
+            self.count += 1;
+        }
+        true
+    }
+
+    fn join(mut self, other: Self) -> Self {
+        self.sum += other.sum;
+        self.count += other.count;
+        self
+    }
+
+    fn finalize(mut self) -> Self {
+        self.mean = Some(self.sum as f64 / self.count as f64);
+        self
+    }
+}
+
+#[tokio::test]
+async fn test_rower_finalize() {
+    with_timeout(async {
+        let mut apps = start_cluster(9200, 9201, 2).await;
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("means", int_chunks(2, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let rower = MeanRower {
+            sum: 0,
+            count: 0,
+            mean: None,
+        };
+        let mut results =
+            join_all(apps.iter().map(|app| app.map("means", rower.clone())))
+                .await;
+        assert!(results.pop().unwrap().unwrap().is_none());
+        let result = results.pop().unwrap().unwrap().unwrap();
+        assert_eq!(result.count, 20);
+        assert_eq!(result.mean, Some(9.5));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_kv_stats() {
+    with_timeout(async {
+        let apps = start_cluster(9210, 9211, 2).await;
+        let key = Key::new("counted", 1);
+        let df = LocalDataFrame::from(Data::Int(7));
+        apps[0].kv.put(key.clone(), df).await.unwrap();
+        assert_eq!(apps[1].kv.stats(), KVStats::default());
+
+        // a remote get goes over the network
+        apps[1].kv.wait_and_get(&key).await.unwrap();
+        let remote = apps[1].kv.stats();
+        assert_eq!(remote.remote_fetches, 1);
+        assert_eq!(remote.local_hits, 0);
+        assert!(remote.bytes_received > 0);
+        assert_eq!(apps[0].kv.stats().bytes_sent, remote.bytes_received);
+
+        // a local get only counts a hit
+        let before = apps[0].kv.stats();
+        apps[0].kv.get(&key).await.unwrap();
+        let after = apps[0].kv.stats();
+        assert_eq!(after.local_hits, before.local_hits + 1);
+        assert_eq!(after.remote_fetches, before.remote_fetches);
+        assert_eq!(after.bytes_sent, before.bytes_sent);
+        assert_eq!(after.bytes_received, before.bytes_received);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_node_id_is_stable() {
+    with_timeout(async {
+        let apps = start_cluster(9220, 9221, 2).await;
+        for (i, app) in apps.iter().enumerate() {
+            assert_eq!(app.node_id, i + 1);
+            assert_eq!(app.kv.id(), app.node_id);
+        }
+        for i in 0..10 {
+            apps[1].kv.send_typed(1, &i).await.unwrap();
+            let got: usize = apps[0].recv_typed().await.unwrap();
+            assert_eq!(got, i);
+            assert_eq!(apps[0].kv.id(), 1);
+            assert_eq!(apps[1].kv.id(), 2);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_separate_network_names() {
+    with_timeout(async {
+        start_server("127.0.0.1:9230").await;
+        let (app_a, app_b) = tokio::join!(
+            LiquidML::with_network_name(
+                "127.0.0.1:9231",
+                "127.0.0.1:9230",
+                1,
+                "a"
+            ),
+            LiquidML::with_network_name(
+                "127.0.0.1:9232",
+                "127.0.0.1:9230",
+                1,
+                "b"
+            )
+        );
+        let mut apps = vec![app_a.unwrap(), app_b.unwrap()];
+        for (app, name) in apps.iter().zip(["a", "b"].iter()) {
+            // each app is the first and only node in its own network
+            assert_eq!(app.node_id, 1);
+            assert_eq!(app.kv.network_name(), *name);
+        }
+
+        // data frames with the same name don't conflict across networks
+        for (i, app) in apps.iter_mut().enumerate() {
+            let chunks = int_chunks(1, (i + 1) * 10);
+            app.df_from_iter("nums", chunks).await.unwrap();
+        }
+        assert_eq!(apps[0].data_frames["nums"].n_rows(), 10);
+        assert_eq!(apps[1].data_frames["nums"].n_rows(), 20);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_client_reregisters_after_server_restart() {
+    let server_addr = "127.0.0.1:9240";
+    let mut listener = TcpListener::bind(server_addr).await.unwrap();
+    let mut server = Server::new(server_addr).await.unwrap();
+    let (id, client) = tokio::join!(
+        async {
+            let (socket, _) = listener.accept().await.unwrap();
+            server.register(socket).await.unwrap()
+        },
+        Client::<String>::new(
+            server_addr.to_string(),
+            "127.0.0.1".to_string(),
+            Some("9241".to_string()),
+            1,
+            "restart".to_string(),
+        )
+    );
+    let (client, _streams, kill_notifier) = client.unwrap();
+    assert_eq!(id, 1);
+
+    // restart the server, the client should register again with its old id
+    drop(server);
+    drop(listener);
+    let mut listener = TcpListener::bind(server_addr).await.unwrap();
+    let mut server = Server::new(server_addr).await.unwrap();
+    let (socket, _) = timeout(Duration::from_secs(5), listener.accept())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(server.register(socket).await.unwrap(), 1);
+    assert_eq!(client.lock().await.id(), 1);
+
+    // the new server can reach the client
+    server
+        .send_msg(1, "restart", ControlMsg::Kill)
+        .await
+        .unwrap();
+    timeout(Duration::from_secs(5), kill_notifier.notified())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_kv_store_of_weights() {
+    start_server("127.0.0.1:9250").await;
+    let kvs: Vec<Arc<KVStore<Vec<f64>>>> =
+        join_all(["127.0.0.1:9251", "127.0.0.1:9252"].iter().map(|addr| {
+            let (blob_sender, _) = mpsc::channel(2);
+            KVStore::new(
+                "127.0.0.1:9250".to_string(),
+                addr.to_string(),
+                blob_sender,
+                2,
+                "weights".to_string(),
+            )
+        }))
+        .await;
+    let (node_1, node_2) = if kvs[0].id() == 1 {
+        (&kvs[0], &kvs[1])
+    } else {
+        (&kvs[1], &kvs[0])
+    };
+
+    // node 2 puts its weights on node 1, then reads them back over the network
+    let weights = vec![0.25, -1.5, 3.0];
+    let key = Key::new("weights", 1);
+    node_2.put(key.clone(), weights.clone()).await.unwrap();
+    assert_eq!(*node_1.wait_and_get(&key).await.unwrap(), weights);
+    assert_eq!(*node_2.wait_and_get(&key).await.unwrap(), weights);
+}
+
+#[tokio::test]
+async fn test_message_sent_before_close_is_received() {
+    start_server("127.0.0.1:9260").await;
+    let (a, b) = tokio::join!(
+        Client::<String>::new(
+            "127.0.0.1:9260".to_string(),
+            "127.0.0.1".to_string(),
+            Some("9261".to_string()),
+            2,
+            "close".to_string(),
+        ),
+        Client::<String>::new(
+            "127.0.0.1:9260".to_string(),
+            "127.0.0.1".to_string(),
+            Some("9262".to_string()),
+            2,
+            "close".to_string(),
+        )
+    );
+    let (sender, _, _) = a.unwrap();
+    let (receiver, mut streams, _) = b.unwrap();
+    let target_id = receiver.lock().await.id();
+
+    let payload = "x".repeat(1_000_000);
+    {
+        let mut sender = sender.lock().await;
+        sender.send_msg(target_id, payload.clone()).await.unwrap();
+        sender.close().await.unwrap();
+    }
+    let msg = timeout(Duration::from_secs(5), streams.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(msg.msg, payload);
+    // the connection was closed after the message
+    let end = timeout(Duration::from_secs(5), streams.next())
+        .await
+        .unwrap();
+    assert!(end.is_none());
+    assert!(sender
+        .lock()
+        .await
+        .send_msg(target_id, payload)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_map_with_empty_chunk() {
+    with_timeout(async {
+        let mut apps = start_cluster(9270, 9271, 3).await;
+        // node 2 gets the empty chunk
+        let chunks = vec![
+            vec![Column::Int((0..10).map(Some).collect())],
+            vec![Column::Int(Vec::new())],
+            vec![Column::Int((10..20).map(Some).collect())],
+        ];
+        join_all(
+            apps.iter_mut().map(|app| {
+                app.df_from_iter("sparse", chunks.clone().into_iter())
+            }),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let rower = MeanRower {
+            sum: 0,
+            count: 0,
+            mean: None,
+        };
+        let mut results =
+            join_all(apps.iter().map(|app| app.map("sparse", rower.clone())))
+                .await;
+        assert!(results.pop().unwrap().unwrap().is_none());
+        assert!(results.pop().unwrap().unwrap().is_none());
+        let result = results.pop().unwrap().unwrap().unwrap();
+        assert_eq!(result.count, 20);
+        assert_eq!(result.mean, Some(9.5));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_broadcast_blob() {
+    with_timeout(async {
+        let apps = start_cluster(9280, 9281, 3).await;
+        let weights: Vec<u8> = (0..=255).collect();
+        apps[0].kv.broadcast_blob(weights.clone()).await.unwrap();
+        for app in &apps[1..] {
+            let blob = timeout(Duration::from_secs(5), async {
+                app.blob_receiver.lock().await.recv().await
+            })
+            .await
+            .unwrap();
+            assert_eq!(blob, Some(weights.clone()));
+        }
+        assert_eq!(apps[0].kv.stats().bytes_sent, 2 * 256);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_send_msg_to_addr_connects_lazily() {
+    start_server("127.0.0.1:9290").await;
+    let (client, _, _) = Client::<ControlMsg>::new(
+        "127.0.0.1:9290".to_string(),
+        "127.0.0.1".to_string(),
+        Some("9291".to_string()),
+        1,
+        "lazy".to_string(),
+    )
+    .await
+    .unwrap();
+    let peer_addr = "127.0.0.1:9292".parse().unwrap();
+    let mut listener = TcpListener::bind(peer_addr).await.unwrap();
+    let peer = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut stream = FramedRead::new(socket, MessageCodec::new());
+        let intro: Message<ControlMsg> = stream.next().await.unwrap().unwrap();
+        let msg: Message<ControlMsg> = stream.next().await.unwrap().unwrap();
+        (intro, msg)
+    });
+
+    // the peer is not in the directory, so a new connection is made
+    let mut client = client.lock().await;
+    let new_stream = client
+        .send_msg_to_addr(2, peer_addr, ControlMsg::Kill)
+        .await
+        .unwrap();
+    assert!(new_stream.is_some());
+    let (intro, msg) = timeout(Duration::from_secs(5), peer)
+        .await
+        .unwrap()
+        .unwrap();
+    match intro.msg {
+        ControlMsg::Introduction { address, .. } => {
+            assert_eq!(address, client.address)
+        }
+        _ => panic!("expected an introduction"),
+    }
+    assert_eq!(msg.sender_id, client.id());
+    assert_eq!(msg.target_id, 2);
+    match msg.msg {
+        ControlMsg::Kill => (),
+        _ => panic!("expected the message that was sent"),
+    }
+
+    // the existing connection is reused for later messages
+    let new_stream = client
+        .send_msg_to_addr(2, peer_addr, ControlMsg::Kill)
+        .await
+        .unwrap();
+    assert!(new_stream.is_none());
+}
+
+#[tokio::test]
+async fn test_run_until_cancelled() {
+    with_timeout(async {
+        let mut apps = start_cluster(9295, 9296, 1).await;
+        let app = apps.pop().unwrap();
+        let (cancel_sender, cancel_receiver) = oneshot::channel::<()>();
+        let node = tokio::spawn(app.run_until_cancelled(
+            |kv| async move { assert_eq!(kv.id(), 1) },
+            async {
+                cancel_receiver.await.ok();
+            },
+        ));
+        cancel_sender.send(()).unwrap();
+        let cancelled = timeout(Duration::from_secs(5), node)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(cancelled);
+    })
+    .await;
+}
+
+#[test]
+fn test_from_sor_bytes() {
+    let contents =
+        "<1> <12> <1.5> <\"hi\">\n<0> <-3> <2> <bye>\n<1> <> <0.25> <>\n";
+    let file_name = write_temp_sor("liquid_ml_bytes.sor", contents);
+    let from_file = LocalDataFrame::from_sor(&file_name, 0, contents.len());
+    let from_bytes =
+        LocalDataFrame::from_sor_bytes(contents.as_bytes(), None).unwrap();
+    assert_eq!(from_bytes.get_schema(), from_file.get_schema());
+    assert_eq!(from_bytes.data, from_file.data);
+    assert_eq!(from_bytes.n_rows(), 3);
+
+    // a given schema is used instead of inferring one
+    let schema = from_file.get_schema().clone();
+    let with_schema =
+        LocalDataFrame::from_sor_bytes(contents.as_bytes(), Some(schema))
+            .unwrap();
+    assert_eq!(with_schema.data, from_file.data);
+
+    assert!(LocalDataFrame::from_sor_bytes(&[0xff, 0xfe], None).is_err());
+}
+
+#[tokio::test]
+async fn test_df_from_sor_does_not_block_runtime() {
+    with_timeout(async {
+        let mut apps = start_cluster(9300, 9301, 2).await;
+        let contents: String = (0..200_000)
+            .map(|i| format!("<{}> <{}.5> <\"row {}\">\n", i, i, i))
+            .collect();
+        let file_name = write_temp_sor("liquid_ml_big.sor", &contents);
+
+        // a ping on the same runtime is answered while node 1 parses the file
+        let start = Instant::now();
+        let (results, pinged_after) = tokio::join!(
+            join_all(
+                apps.iter_mut()
+                    .map(|app| app.df_from_sor("big", &file_name))
+            ),
+            async {
+                delay_for(Duration::from_millis(10)).await;
+                start.elapsed()
+            }
+        );
+        results.into_iter().for_each(|result| result.unwrap());
+        assert!(pinged_after < Duration::from_millis(250));
+        for app in &apps {
+            assert_eq!(app.data_frames["big"].n_rows(), 200_000);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_rebalance() {
+    with_timeout(async {
+        let mut apps = start_cluster(9310, 9311, 3).await;
+        // chunks are distributed round-robin, so node 1 gets 10 rows and the
+        // other nodes get 1 row each
+        let chunks = vec![
+            vec![Column::Int((0..10).map(Some).collect())],
+            vec![Column::Int(vec![Some(10)])],
+            vec![Column::Int(vec![Some(11)])],
+        ];
+        join_all(
+            apps.iter_mut().map(|app| {
+                app.df_from_iter("skewed", chunks.clone().into_iter())
+            }),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+
+        let old_keys: Vec<Key> = apps[0].data_frames["skewed"]
+            .df_chunk_map
+            .values()
+            .cloned()
+            .collect();
+        join_all(apps.iter_mut().map(|app| app.rebalance("skewed")))
+            .await
+            .into_iter()
+            .for_each(|result| result.unwrap());
+        // the chunks of the old data frame are gone from the nodes that owned
+        // them
+        for key in &old_keys {
+            assert!(apps[key.home - 1].kv.get(key).await.is_err());
+        }
+        let df = &apps[0].data_frames["skewed"];
+        assert_eq!(df.n_rows(), 12);
+        let mut rows_per_node = vec![0; 3];
+        for (range, key) in &df.df_chunk_map {
+            rows_per_node[key.home - 1] += range.end - range.start;
+        }
+        let max = *rows_per_node.iter().max().unwrap();
+        let min = *rows_per_node.iter().min().unwrap();
+        assert!(max - min <= 1);
+
+        // the rows are still in the same order
+        let gathered = join_all(apps.iter().map(|app| app.gather("skewed")))
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .find_map(|df| df)
+            .unwrap();
+        assert_eq!(*gathered.data[0], Column::Int((0..12).map(Some).collect()));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_kv_store_of_mixed_values() {
+    start_server("127.0.0.1:9320").await;
+    let (blob_sender, _) = mpsc::channel(2);
+    let kv: Arc<KVStore<StoredValue>> = KVStore::new(
+        "127.0.0.1:9320".to_string(),
+        "127.0.0.1:9321".to_string(),
+        blob_sender,
+        1,
+        "mixed".to_string(),
+    )
+    .await;
+    let frame = LocalDataFrame::from(vec![Column::Int(vec![Some(1), None])]);
+    let frame_key = Key::new("frame", 1);
+    let blob_key = Key::new("blob", 1);
+    let rower_key = Key::new("rower", 1);
+    let rower = MeanRower {
+        sum: 3,
+        count: 2,
+        mean: None,
+    };
+    kv.put_frame(frame_key.clone(), frame.clone())
+        .await
+        .unwrap();
+    kv.put_blob(blob_key.clone(), vec![1, 2, 3]).await.unwrap();
+    kv.put_rower(rower_key.clone(), &rower).await.unwrap();
+
+    assert_eq!(kv.get_frame(&frame_key).await.unwrap(), frame);
+    assert_eq!(kv.get_blob(&blob_key).await.unwrap(), vec![1, 2, 3]);
+    let got: MeanRower = kv.get_rower(&rower_key).await.unwrap();
+    assert_eq!((got.sum, got.count), (3, 2));
+    // the wrong accessor is a type mismatch
+    assert!(matches!(
+        kv.get_blob(&frame_key).await,
+        Err(LiquidError::TypeMismatch)
+    ));
+    assert!(matches!(
+        kv.get_frame(&blob_key).await,
+        Err(LiquidError::TypeMismatch)
+    ));
+}
+
+#[tokio::test]
+async fn test_new_waits_for_network_ready() {
+    let server_addr = "127.0.0.1:9330";
+    start_server(server_addr).await;
+    let returned = AtomicUsize::new(0);
+    let start = |addr| {
+        let returned = &returned;
+        async move {
+            let app = LiquidML::new(addr, server_addr, 3).await.unwrap();
+            returned.fetch_add(1, Ordering::SeqCst);
+            app
+        }
+    };
+    let (app_1, app_2, app_3) =
+        tokio::join!(start("127.0.0.1:9331"), start("127.0.0.1:9332"), async {
+            // none of the first two nodes is ready without the third
+            delay_for(Duration::from_millis(500)).await;
+            assert_eq!(returned.load(Ordering::SeqCst), 0);
+            start("127.0.0.1:9333").await
+        });
+    assert_eq!(returned.load(Ordering::SeqCst), 3);
+    let mut ids = vec![app_1.node_id, app_2.node_id, app_3.node_id];
+    ids.sort();
+    assert_eq!(ids, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_clients_wait_for_network_ready() {
+    let server_addr = "127.0.0.1:9450";
+    start_server(server_addr).await;
+    let returned = Arc::new(AtomicUsize::new(0));
+    let start = |port: &str| {
+        let returned = returned.clone();
+        let port = port.to_string();
+        tokio::spawn(async move {
+            let (client, _, _) = Client::<ControlMsg>::new(
+                server_addr.to_string(),
+                "127.0.0.1".to_string(),
+                Some(port),
+                3,
+                "ready".to_string(),
+            )
+            .await
+            .unwrap();
+            let id = client.lock().await.id();
+            returned.fetch_add(1, Ordering::SeqCst);
+            id
+        })
+    };
+    let node_1 = start("9451");
+    delay_for(Duration::from_millis(100)).await;
+    let node_2 = start("9452");
+    delay_for(Duration::from_millis(100)).await;
+
+    // the third node connects to the first two before it registers with the
+    // `Server`, so they have all their connections but the network is not
+    // ready yet
+    let node_3_addr = "127.0.0.1:9453".parse().unwrap();
+    let intro = || ControlMsg::Introduction {
+        address: node_3_addr,
+        network_name: "ready".to_string(),
+        num_nodes: 3,
+    };
+    let mut peer_sinks = vec![];
+    for (id, addr) in &[(1, "127.0.0.1:9451"), (2, "127.0.0.1:9452")] {
+        let socket = TcpStream::connect(addr).await.unwrap();
+        let mut sink = FramedWrite::new(socket, MessageCodec::new());
+        sink.send(Message::new(0, 3, *id, intro())).await.unwrap();
+        peer_sinks.push(sink);
+    }
+    delay_for(Duration::from_millis(500)).await;
+    assert_eq!(returned.load(Ordering::SeqCst), 0);
+
+    // both nodes return once the third node registers
+    let socket = TcpStream::connect(server_addr).await.unwrap();
+    let (reader, writer) = split(socket);
+    let mut server_stream = FramedRead::new(reader, MessageCodec::new());
+    let mut server_sink = FramedWrite::new(writer, MessageCodec::new());
+    server_sink
+        .send(Message::new(0, 0, 0, intro()))
+        .await
+        .unwrap();
+    let dir: Message<ControlMsg> = server_stream.next().await.unwrap().unwrap();
+    assert_eq!(dir.target_id, 3);
+    let (id_1, id_2) = timeout(Duration::from_secs(5), join(node_1, node_2))
+        .await
+        .unwrap();
+    assert_eq!((id_1.unwrap(), id_2.unwrap()), (1, 2));
+    assert_eq!(returned.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_distributed_join() {
+    with_timeout(async {
+        let mut apps = start_cluster(9340, 9341, 2).await;
+        // the left ids are 0..20 and the right ids are the even numbers 0..40
+        let right_chunks = vec![
+            vec![Column::Int((0..10).map(|x| Some(x * 2)).collect())],
+            vec![Column::Int((10..20).map(|x| Some(x * 2)).collect())],
+        ];
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("left", int_chunks(2, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(apps.iter_mut().map(|app| {
+            app.df_from_iter("right", right_chunks.clone().into_iter())
+        }))
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.join("left", "right", 0, 0, JoinKind::Inner)),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let df = &apps[0].data_frames["left-right-joined"];
+        assert_eq!(df.n_rows(), 10);
+        assert_eq!(df.n_cols(), 2);
+        assert_eq!(apps[1].data_frames["left-right-joined"].n_rows(), 10);
+
+        let gathered =
+            join_all(apps.iter().map(|app| app.gather("left-right-joined")))
+                .await
+                .into_iter()
+                .map(|result| result.unwrap())
+                .find_map(|df| df)
+                .unwrap();
+        let mut pairs: Vec<(Data, Data)> = (0..gathered.n_rows())
+            .map(|row_idx| {
+                (
+                    gathered.get(0, row_idx).unwrap(),
+                    gathered.get(1, row_idx).unwrap(),
+                )
+            })
+            .collect();
+        pairs.sort_by_key(|(left, _)| match left {
+            Data::Int(x) => *x,
+            _ => panic!("expected an int key"),
+        });
+        let expected: Vec<(Data, Data)> = (0..10)
+            .map(|x| (Data::Int(x * 2), Data::Int(x * 2)))
+            .collect();
+        assert_eq!(pairs, expected);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_outer_joins() {
+    with_timeout(async {
+        let mut apps = start_cluster(9440, 9441, 2).await;
+        // the left ids are 0..20 and the right ids are the even numbers 0..40,
+        // with a chunk of each on both nodes so unmatched rows are padded on a
+        // different node than the one they started on
+        let right_chunks = vec![
+            vec![Column::Int((0..10).map(|x| Some(x * 2)).collect())],
+            vec![Column::Int((10..20).map(|x| Some(x * 2)).collect())],
+        ];
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("left", int_chunks(2, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(apps.iter_mut().map(|app| {
+            app.df_from_iter("right", right_chunks.clone().into_iter())
+        }))
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+
+        // joining the same data frames again gets a new name every time
+        let kinds = vec![
+            (JoinKind::Left, "left-right-joined"),
+            (JoinKind::Right, "left-right-joined-1"),
+            (JoinKind::Outer, "left-right-joined-2"),
+        ];
+        for (kind, name) in kinds {
+            join_all(
+                apps.iter_mut()
+                    .map(|app| app.join("left", "right", 0, 0, kind)),
+            )
+            .await
+            .into_iter()
+            .for_each(|result| result.unwrap());
+            let gathered = join_all(apps.iter().map(|app| app.gather(name)))
+                .await
+                .into_iter()
+                .map(|result| result.unwrap())
+                .find_map(|df| df)
+                .unwrap();
+            let mut pairs: Vec<(Data, Data)> = (0..gathered.n_rows())
+                .map(|row_idx| {
+                    (
+                        gathered.get(0, row_idx).unwrap(),
+                        gathered.get(1, row_idx).unwrap(),
+                    )
+                })
+                .collect();
+            pairs.sort_by_key(|pair| match pair {
+                (Data::Int(x), _) | (Data::Null, Data::Int(x)) => *x,
+                _ => panic!("expected an int key"),
+            });
+
+            let matched = |x: i64| {
+                if x % 2 == 0 {
+                    (Data::Int(x), Data::Int(x))
+                } else {
+                    (Data::Int(x), Data::Null)
+                }
+            };
+            let expected: Vec<(Data, Data)> = match kind {
+                JoinKind::Left => (0..20).map(matched).collect(),
+                JoinKind::Right => (0..20)
+                    .map(|x| x * 2)
+                    .map(|x| {
+                        if x < 20 {
+                            (Data::Int(x), Data::Int(x))
+                        } else {
+                            (Data::Null, Data::Int(x))
+                        }
+                    })
+                    .collect(),
+                JoinKind::Inner => unreachable!(),
+                JoinKind::Outer => (0..20)
+                    .map(matched)
+                    .chain((10..20).map(|x| (Data::Null, Data::Int(x * 2))))
+                    .collect(),
+            };
+            assert_eq!(pairs, expected);
+        }
+
+        // the shuffled partitions are removed once they are joined
+        for app in apps.iter() {
+            for side in &["left", "right"] {
+                for from in 1..=2 {
+                    let name =
+                        format!("left-right-joined-{}-from-{}", side, from);
+                    let key = Key::new(&name, app.node_id);
+                    assert!(app.kv.get(&key).await.is_err());
+                }
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_group_by() {
+    with_timeout(async {
+        let mut apps = start_cluster(9350, 9351, 2).await;
+        // 4 chunks of 10 rows, so every node has more than one chunk
+        let chunks = (0..4).map(|i| {
+            let ids = (i * 10..i * 10 + 10).map(Some).collect();
+            vec![
+                Column::Int((0..10).map(|x| Some(x % 3)).collect()),
+                Column::Int(ids),
+            ]
+        });
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("grouped", chunks.clone())),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let aggs = [(1, Agg::Count), (1, Agg::Sum), (1, Agg::Mean)];
+        let grouped = join_all(
+            apps.iter().map(|app| app.group_by("grouped", &[0], &aggs)),
+        )
+        .await
+        .into_iter()
+        .map(|result| result.unwrap())
+        .find_map(|df| df)
+        .unwrap();
+        assert_eq!(grouped.n_rows(), 3);
+        let gathered = join_all(apps.iter().map(|app| app.gather("grouped")))
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .find_map(|df| df)
+            .unwrap();
+        let expected = gathered.group_by(&[0], &aggs).unwrap();
+        for row_idx in 0..expected.n_rows() {
+            let key = expected.get(0, row_idx).unwrap();
+            let grouped_idx = (0..grouped.n_rows())
+                .find(|&idx| grouped.get(0, idx).unwrap() == key)
+                .unwrap();
+            for col_idx in 1..4 {
+                assert_eq!(
+                    grouped.get(col_idx, grouped_idx).unwrap(),
+                    expected.get(col_idx, row_idx).unwrap()
+                );
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_describe() {
+    with_timeout(async {
+        let mut apps = start_cluster(9360, 9361, 2).await;
+        // 4 chunks of 10 rows, so every node has more than one chunk
+        let chunks = (0..4).map(|i| {
+            let values = (i * 10..i * 10 + 10).map(Some).collect();
+            let labels = (0..10).map(|x| Some(format!("label{}", x % 3)));
+            vec![Column::Int(values), Column::String(labels.collect())]
+        });
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("described", chunks.clone())),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let described =
+            join_all(apps.iter().map(|app| app.describe("described")))
+                .await
+                .into_iter()
+                .map(|result| result.unwrap())
+                .find_map(|df| df)
+                .unwrap();
+        let gathered = join_all(apps.iter().map(|app| app.gather("described")))
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .find_map(|df| df)
+            .unwrap();
+        let expected = gathered.describe();
+        assert_eq!(described.n_rows(), expected.n_rows());
+        for stat in &["count", "null_count", "distinct", "min", "50%", "max"] {
+            let row_idx = described.get_row_idx(stat).unwrap();
+            for col_idx in 0..2 {
+                assert_eq!(
+                    described.get(col_idx, row_idx).unwrap(),
+                    expected.get(col_idx, row_idx).unwrap()
+                );
+            }
+        }
+        let mean_idx = described.get_row_idx("mean").unwrap();
+        match described.get(0, mean_idx).unwrap() {
+            Data::Float(mean) => assert!((mean - 19.5).abs() < 1e-9),
+            other => panic!("unexpected mean {:?}", other),
+        }
+        let distinct_idx = described.get_row_idx("distinct").unwrap();
+        assert_eq!(described.get(1, distinct_idx).unwrap(), Data::Float(3.0));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_unique() {
+    with_timeout(async {
+        let mut apps = start_cluster(9370, 9371, 2).await;
+        // 4 chunks of 10 rows with only 6 distinct rows, spread over both nodes
+        let chunks = (0..4).map(|_| {
+            vec![
+                Column::Int((0..10).map(|x| Some(x % 3)).collect()),
+                Column::Bool((0..10).map(|x| Some(x % 2 == 0)).collect()),
+            ]
+        });
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("dupes", chunks.clone())),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(apps.iter_mut().map(|app| app.unique("dupes", None)))
+            .await
+            .into_iter()
+            .for_each(|result| result.unwrap());
+        // deduplicating again on a subset leaves one row per value of column 0
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.unique("dupes-unique", Some(&[0]))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        for &(df_name, expected) in
+            &[("dupes-unique", 6), ("dupes-unique-unique", 3)]
+        {
+            let gathered = join_all(apps.iter().map(|app| app.gather(df_name)))
+                .await
+                .into_iter()
+                .map(|result| result.unwrap())
+                .find_map(|df| df)
+                .unwrap();
+            assert_eq!(gathered.n_rows(), expected);
+            assert_eq!(gathered.unique(None).unwrap().n_rows(), expected);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_map_column_and_with_column() {
+    with_timeout(async {
+        let mut apps = start_cluster(9380, 9381, 2).await;
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("mapped", int_chunks(4, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(apps.iter_mut().map(|app| {
+            app.map_column("mapped", 0, &DataType::Float, |value| match value {
+                Data::Int(x) => Data::Float(*x as f64 * 1.5),
+                _ => Data::Null,
+            })
+        }))
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(apps.iter_mut().map(|app| {
+            app.with_column("mapped", "big", &DataType::Bool, |row| {
+                match row.get(0).unwrap() {
+                    Data::Float(x) => Data::Bool(*x > 30.0),
+                    _ => Data::Null,
+                }
+            })
+        }))
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let gathered = join_all(apps.iter().map(|app| app.gather("mapped")))
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .find_map(|df| df)
+            .unwrap();
+        assert_eq!(gathered.n_rows(), 40);
+        assert_eq!(gathered.get_col_idx("big"), Some(1));
+        for row_idx in 0..gathered.n_rows() {
+            let value = row_idx as f64 * 1.5;
+            assert_eq!(gathered.get(0, row_idx).unwrap(), Data::Float(value));
+            assert_eq!(
+                gathered.get(1, row_idx).unwrap(),
+                Data::Bool(value > 30.0)
+            );
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_window() {
+    with_timeout(async {
+        let mut apps = start_cluster(9390, 9391, 2).await;
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("windowed", int_chunks(4, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let windows = vec![
+            ("sum", Window::RollingSum(3)),
+            ("lag", Window::Lag(12)),
+            ("lead", Window::Lead(2)),
+            ("cumsum", Window::CumSum),
+        ];
+        for (name, window) in windows.iter() {
+            join_all(
+                apps.iter_mut()
+                    .map(|app| app.with_window("windowed", 0, *window, name)),
+            )
+            .await
+            .into_iter()
+            .for_each(|result| result.unwrap());
+        }
+        let gathered = join_all(apps.iter().map(|app| app.gather("windowed")))
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .find_map(|df| df)
+            .unwrap();
+        assert_eq!(gathered.n_rows(), 40);
+        for (name, window) in windows {
+            let col_idx = gathered.get_col_idx(name).unwrap();
+            let expected =
+                LocalDataFrame::from(gathered.window(0, window).unwrap());
+            for row_idx in 0..gathered.n_rows() {
+                assert_eq!(
+                    gathered.get(col_idx, row_idx).unwrap(),
+                    expected.get(0, row_idx).unwrap()
+                );
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_sample() {
+    with_timeout(async {
+        let mut apps = start_cluster(9400, 9401, 2).await;
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("population", int_chunks(4, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(apps.iter_mut().map(|app| app.sample("population", 0.25, 5)))
+            .await
+            .into_iter()
+            .for_each(|result| result.unwrap());
+        let gathered =
+            join_all(apps.iter().map(|app| app.gather("population-sample-5")))
+                .await
+                .into_iter()
+                .map(|result| result.unwrap())
+                .find_map(|df| df)
+                .unwrap();
+        assert_eq!(gathered.n_rows(), 10);
+        let values: Vec<i64> = (0..gathered.n_rows())
+            .map(|i| match gathered.get(0, i).unwrap() {
+                Data::Int(x) => x,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        assert!(values.iter().all(|&x| x >= 0 && x < 40));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_distributed_train_test_split() {
+    with_timeout(async {
+        let mut apps = start_cluster(9410, 9411, 2).await;
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_iter("points", int_chunks(4, 10))),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.train_test_split("points", 0.3, 11)),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        let mut values = Vec::new();
+        for (name, expected_rows) in
+            &[("points-train", 28), ("points-test", 12)]
+        {
+            let gathered = join_all(apps.iter().map(|app| app.gather(name)))
+                .await
+                .into_iter()
+                .map(|result| result.unwrap())
+                .find_map(|df| df)
+                .unwrap();
+            assert_eq!(gathered.n_rows(), *expected_rows);
+            let set: Vec<i64> = (0..gathered.n_rows())
+                .map(|i| match gathered.get(0, i).unwrap() {
+                    Data::Int(x) => x,
+                    _ => unreachable!(),
+                })
+                .collect();
+            assert!(set.windows(2).all(|w| w[0] < w[1]));
+            values.extend(set);
+        }
+        values.sort();
+        assert_eq!(values, (0..40).collect::<Vec<i64>>());
+    })
+    .await;
+}
+
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn test_df_from_parquet() {
+    with_timeout(async {
+        let mut apps = start_cluster(9420, 9421, 2).await;
+        let mut df =
+            LocalDataFrame::from(Column::Int((0..20).map(Some).collect()));
+        df.add_column(
+            Column::Float((0..20).map(|i| Some(i as f64 / 2.0)).collect()),
+            Some("half".to_string()),
+        )
+        .unwrap();
+        // write row groups of 5 rows, so each node gets 2 row groups
+        let path = env::temp_dir()
+            .join(format!("liquid_ml_distributed_{}.parquet", process::id()));
+        let file_name = path.to_str().unwrap();
+        let batch = df.to_arrow().unwrap();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(5)
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            fs::File::create(file_name).unwrap(),
+            batch.schema(),
+            Some(properties),
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_parquet("halves", file_name)),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        for app in &apps {
+            let ddf = &app.data_frames["halves"];
+            assert_eq!(ddf.n_rows(), 20);
+            assert_eq!(ddf.get_col_idx("half"), Some(1));
+        }
+        let gathered = join_all(apps.iter().map(|app| app.gather("halves")))
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .find_map(|df| df)
+            .unwrap();
+        fs::remove_file(file_name).unwrap();
+        assert_eq!(gathered.data, df.data);
+    })
+    .await;
+}
+
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn test_df_from_missing_parquet() {
+    with_timeout(async {
+        let mut apps = start_cluster(9470, 9471, 2).await;
+        let results =
+            timeout(
+                Duration::from_secs(5),
+                join_all(apps.iter_mut().map(|app| {
+                    app.df_from_parquet("missing", "tests/missing.pq")
+                })),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(results[0], Err(LiquidError::NetworkError(_))));
+        assert!(matches!(results[1], Err(LiquidError::RemoteError(_))));
+        assert!(!apps[0].data_frames.contains_key("missing"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_df_from_csv() {
+    with_timeout(async {
+        let mut apps = start_cluster(9430, 9431, 3).await;
+        let contents: String = std::iter::once("id,label\n".to_string())
+            .chain((0..10).map(|i| format!("{},\"row {}\"\n", i, i)))
+            .collect();
+        let path = env::temp_dir()
+            .join(format!("liquid_ml_distributed_{}.csv", process::id()));
+        let file_name = path.to_str().unwrap();
+        fs::write(file_name, contents).unwrap();
+
+        let options = CsvOptions::default();
+        join_all(
+            apps.iter_mut()
+                .map(|app| app.df_from_csv("labels", file_name, &options)),
+        )
+        .await
+        .into_iter()
+        .for_each(|result| result.unwrap());
+        for app in &apps {
+            let ddf = &app.data_frames["labels"];
+            assert_eq!(ddf.n_rows(), 10);
+            assert_eq!(ddf.get_col_idx("label"), Some(1));
+        }
+        let gathered = join_all(apps.iter().map(|app| app.gather("labels")))
+            .await
+            .into_iter()
+            .map(|result| result.unwrap())
+            .find_map(|df| df)
+            .unwrap();
+        let local = LocalDataFrame::from_csv(file_name, &options).unwrap();
+        fs::remove_file(file_name).unwrap();
+        assert_eq!(gathered.data, local.data);
+        assert_eq!(gathered.get_schema().schema, local.get_schema().schema);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_df_from_missing_csv() {
+    with_timeout(async {
+        let mut apps = start_cluster(9480, 9481, 2).await;
+        let options = CsvOptions::default();
+        let results = timeout(
+            Duration::from_secs(5),
+            join_all(apps.iter_mut().map(|app| {
+                app.df_from_csv("missing", "tests/missing.csv", &options)
+            })),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(results[0], Err(LiquidError::NetworkError(_))));
+        assert!(matches!(results[1], Err(LiquidError::RemoteError(_))));
+        assert!(!apps[0].data_frames.contains_key("missing"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_register_network_in_any_order() {
+    let server_addr = "127.0.0.1:9490";
+    start_server(server_addr).await;
+    let new_client = |port: &str| {
+        Client::<ControlMsg>::new(
+            server_addr.to_string(),
+            "127.0.0.1".to_string(),
+            Some(port.to_string()),
+            2,
+            "parent".to_string(),
+        )
+    };
+    let parent_1 = tokio::spawn(new_client("9491"));
+    delay_for(Duration::from_millis(100)).await;
+    let (parent_1, parent_2) = join(parent_1, new_client("9492")).await;
+    let (parent_1, _streams_1, _) = parent_1.unwrap().unwrap();
+    let (parent_2, _streams_2, _) = parent_2.unwrap();
+
+    // node 1 registers the new network long before node 2 is listening for
+    // its `Ready` message
+    let register = |parent: Arc<Mutex<Client<ControlMsg>>>| async move {
+        Client::register_network::<ControlMsg>(parent, "child".to_string())
+            .await
+            .unwrap()
+            .0
+            .lock()
+            .await
+            .id()
+    };
+    let node_1 = tokio::spawn(register(parent_1));
+    delay_for(Duration::from_millis(300)).await;
+    let (id_1, id_2) =
+        timeout(Duration::from_secs(5), join(node_1, register(parent_2)))
+            .await
+            .unwrap();
+    assert_eq!((id_1.unwrap(), id_2), (1, 2));
+}
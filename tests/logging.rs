@@ -0,0 +1,54 @@
+//! Tests that output from `liquid_ml` goes through the `log` facade. These
+//! live in their own test binary since only one global logger may be set.
+use liquid_ml::network::{Client, Server};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::delay_for;
+
+/// A logger that records the level and target of every log message
+struct CaptureLogger {
+    records: Arc<Mutex<Vec<(Level, String)>>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((record.level(), record.target().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+#[tokio::test]
+async fn test_client_connection_logs_at_info() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let logger = CaptureLogger {
+        records: records.clone(),
+    };
+    log::set_logger(Box::leak(Box::new(logger))).unwrap();
+    log::set_max_level(LevelFilter::Debug);
+
+    let mut server = Server::new("127.0.0.1:9140").await.unwrap();
+    tokio::spawn(async move { server.accept_new_connections().await });
+    delay_for(Duration::from_millis(100)).await;
+    let (_client, _streams, _) = Client::<String>::new(
+        "127.0.0.1:9140".to_string(),
+        "127.0.0.1".to_string(),
+        None,
+        1,
+        "logging".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let records = records.lock().unwrap();
+    assert!(records.iter().any(|(level, target)| *level == Level::Info
+        && target.starts_with("liquid_ml::network::client")));
+}